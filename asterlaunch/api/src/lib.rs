@@ -0,0 +1,16 @@
+/*
+ * AsterLaunch API
+ * 
+ * HTTP server over the indexer's SQLite database:
+ * - Token list and detail shaped like the frontend's `Token`
+ * - Trades, candles and wallet portfolios
+ * - WebSocket stream of trades, curve updates and launches
+ * - Runs locally next to the indexer; no RPC access needed
+ */
+
+pub mod model;
+pub mod query;
+pub mod routes;
+pub mod stream;
+
+pub use routes::{router, ApiConfig, AppState};
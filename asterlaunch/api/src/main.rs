@@ -0,0 +1,51 @@
+//! AsterLaunch API server
+//!
+//! ```text
+//! asterlaunch-api --db asterlaunch.db --listen 127.0.0.1:8080
+//! ```
+
+use std::{net::SocketAddr, path::PathBuf, process::ExitCode, time::Duration};
+
+use anyhow::{Context, Result};
+use asterlaunch_api::{router, stream, ApiConfig, AppState};
+use asterlaunch_indexer::Store;
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[command(about = "REST API over the AsterLaunch indexer database")]
+struct Args {
+    /// SQLite database written by asterlaunch-indexer
+    #[arg(long, default_value = "asterlaunch.db")]
+    db: PathBuf,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// How often the stream checks the database for new entries (ms)
+    #[arg(long, default_value_t = 250)]
+    poll_ms: u64,
+}
+
+async fn run(args: Args) -> Result<()> {
+    let state = AppState::new(Store::open(&args.db)?, ApiConfig::default());
+    tokio::spawn(stream::follow_feed(state.clone(), Duration::from_millis(args.poll_ms)));
+
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("cannot listen on {}", args.listen))?;
+    eprintln!("listening on http://{}", args.listen);
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Args::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
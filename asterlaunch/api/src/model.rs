@@ -0,0 +1,126 @@
+//! Response bodies, shaped like the frontend types
+//!
+//! `Token` follows `asterlaunch/src/types/index.ts` and also carries the
+//! field names astra-wallet's `DataContext` uses, so both apps can read it.
+
+use asterlaunch_candles::{Candle, Interval, MarketCapCandle, PricePoint};
+use asterlaunch_trade_decoder::model::Trade;
+use serde::{Deserialize, Serialize};
+
+/// A token, matching the frontend's `Token`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Token {
+    /// Mint address
+    pub id: String,
+    pub name: String,
+    pub ticker: String,
+    pub description: String,
+    pub image_url: String,
+
+    /// Milliseconds since the epoch
+    pub created_at: i64,
+    pub creator: String,
+
+    /// SOL value of the total supply at the spot price
+    pub current_market_cap: f64,
+
+    /// Spot price (SOL per whole token)
+    pub price: f64,
+
+    /// Spot price change over the last 24 hours (percent)
+    pub change_24h: f64,
+
+    /// SOL traded over the last 24 hours
+    pub volume_24h: f64,
+
+    /// Whole tokens
+    pub total_supply: f64,
+    pub tokens_sold: f64,
+
+    /// SOL held by the curve
+    pub liquidity_locked: f64,
+
+    /// Progress towards the graduation market cap (0-100)
+    pub graduation_progress: f64,
+    pub is_graduated: bool,
+    pub holder_count: u64,
+
+    /// Latest trades, newest first (token detail only)
+    pub trades: Vec<Trade>,
+
+    /// Chart points, oldest first (token detail only)
+    pub price_history: Vec<PricePoint>,
+
+    /// astra-wallet's names for the fields above
+    pub market_cap: f64,
+    pub holders: u64,
+    pub liquidity: f64,
+    pub image: String,
+
+    /// `native` or `anchor`
+    pub program: String,
+    pub bonding_curve: String,
+    pub decimals: u8,
+}
+
+/// Candles of a token
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candles {
+    pub mint: String,
+    pub interval: Interval,
+    pub candles: Vec<Candle>,
+
+    /// Market-cap candles (lamports), one per candle
+    pub market_cap: Vec<MarketCapCandle>,
+}
+
+/// A token a wallet holds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Holding {
+    pub token: Token,
+
+    /// Whole tokens
+    pub balance: f64,
+
+    /// SOL value at the spot price
+    pub value: f64,
+}
+
+/// A wallet's holdings and trades
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Portfolio {
+    pub wallet: String,
+    pub holdings: Vec<Holding>,
+
+    /// SOL value of all holdings
+    pub total_value: f64,
+
+    /// Latest trades, newest first
+    pub trades: Vec<Trade>,
+}
+
+/// A streamed feed entry
+///
+/// `type` is `tokenLaunched`, `trade` or `curve`; `seq` increases by entry
+/// and is what a reconnecting client passes as `since`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedMessage {
+    pub seq: i64,
+
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    pub mint: String,
+
+    /// Trader or token creator
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallet: Option<String>,
+
+    pub slot: u64,
+    pub data: serde_json::Value,
+}
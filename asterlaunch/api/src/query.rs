@@ -0,0 +1,256 @@
+//! Reads from the indexer database
+
+use anyhow::Result;
+use crate::model::FeedMessage;
+use asterlaunch_trade_decoder::model::{ui_amount, CurveSnapshot, Side, Trade, LAMPORTS_PER_SOL};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+/// A row of the `tokens` table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenRow {
+    pub mint: String,
+    pub program: String,
+    pub creator: String,
+    pub bonding_curve: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub description: String,
+    pub total_supply: u64,
+    pub decimals: u8,
+    pub created_at: i64,
+    pub price_lamports: u64,
+    pub sol_reserves: u64,
+    pub token_reserves: u64,
+    pub graduated_at: Option<i64>,
+
+    /// Virtual reserves of an Anchor curve, 0 for native tokens
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+}
+
+const TOKEN_COLUMNS: &str = "mint, program, creator, bonding_curve, name, symbol, uri, description, \
+    total_supply, decimals, created_at, price_lamports, sol_reserves, token_reserves, graduated_at, \
+    virtual_sol_reserves, virtual_token_reserves";
+
+fn token_row(row: &Row) -> rusqlite::Result<TokenRow> {
+    Ok(TokenRow {
+        mint: row.get(0)?,
+        program: row.get(1)?,
+        creator: row.get(2)?,
+        bonding_curve: row.get(3)?,
+        name: row.get(4)?,
+        symbol: row.get(5)?,
+        uri: row.get(6)?,
+        description: row.get(7)?,
+        total_supply: row.get::<_, i64>(8)? as u64,
+        decimals: row.get(9)?,
+        created_at: row.get(10)?,
+        price_lamports: row.get::<_, i64>(11)? as u64,
+        sol_reserves: row.get::<_, i64>(12)? as u64,
+        token_reserves: row.get::<_, i64>(13)? as u64,
+        graduated_at: row.get(14)?,
+        virtual_sol_reserves: row.get::<_, i64>(15)? as u64,
+        virtual_token_reserves: row.get::<_, i64>(16)? as u64,
+    })
+}
+
+pub fn tokens(conn: &Connection) -> Result<Vec<TokenRow>> {
+    let mut statement = conn.prepare(&format!("SELECT {} FROM tokens", TOKEN_COLUMNS))?;
+    let rows = statement.query_map([], token_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+pub fn token(conn: &Connection, mint: &str) -> Result<Option<TokenRow>> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {} FROM tokens WHERE mint = ?1", TOKEN_COLUMNS),
+            [mint],
+            token_row,
+        )
+        .optional()?)
+}
+
+/// Graduation threshold a program was initialized with, if indexed
+pub fn graduation_threshold(conn: &Connection, program: &str) -> Result<Option<u64>> {
+    Ok(conn
+        .query_row(
+            "SELECT graduation_threshold FROM platforms WHERE program = ?1",
+            [program],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+        .map(|threshold| threshold as u64))
+}
+
+/// Lamports traded in a mint since a time (milliseconds)
+pub fn volume_since(conn: &Connection, mint: &str, since: i64) -> Result<u64> {
+    let volume: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(sol_amount), 0) FROM trades WHERE mint = ?1 AND timestamp >= ?2",
+        params![mint, since],
+        |row| row.get(0),
+    )?;
+    Ok(volume as u64)
+}
+
+/// Spot price at a time: the last one before it, or the first one after it
+/// if the mint had not traded yet
+pub fn price_at(conn: &Connection, mint: &str, at: i64) -> Result<Option<u64>> {
+    let before = conn
+        .query_row(
+            "SELECT price_lamports FROM snapshots WHERE mint = ?1 AND timestamp <= ?2
+             ORDER BY slot DESC, trade_id DESC LIMIT 1",
+            params![mint, at],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?;
+    let price = match before {
+        Some(price) => Some(price),
+        None => conn
+            .query_row(
+                "SELECT price_lamports FROM snapshots WHERE mint = ?1 ORDER BY slot, trade_id LIMIT 1",
+                [mint],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?,
+    };
+    Ok(price.map(|price| price as u64))
+}
+
+/// Wallets holding a mint, excluding its bonding curve
+pub fn holder_count(conn: &Connection, mint: &str, bonding_curve: &str) -> Result<u64> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(DISTINCT owner) FROM holders
+         WHERE mint = ?1 AND amount > 0 AND owner IS NOT NULL AND owner != ?2",
+        params![mint, bonding_curve],
+        |row| row.get(0),
+    )?;
+    Ok(count as u64)
+}
+
+/// Rows of one mint or one wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter<'a> {
+    Mint(&'a str),
+    Wallet(&'a str),
+}
+
+impl Filter<'_> {
+    fn column(&self) -> (&'static str, &str) {
+        match self {
+            Filter::Mint(mint) => ("mint", mint),
+            Filter::Wallet(wallet) => ("wallet", wallet),
+        }
+    }
+}
+
+const TRADE_COLUMNS: &str = "t.id, t.side, t.token_amount, t.sol_amount, t.timestamp, t.wallet, \
+    t.signature, t.mint, t.slot, t.decimals, t.trade_seq";
+
+fn trade_row(row: &Row) -> rusqlite::Result<Trade> {
+    let token_amount = row.get::<_, i64>(2)? as u64;
+    let sol_amount = row.get::<_, i64>(3)? as u64;
+    let decimals: u8 = row.get(9)?;
+    let amount = ui_amount(token_amount, decimals);
+
+    Ok(Trade {
+        id: row.get(0)?,
+        side: match row.get::<_, String>(1)?.as_str() {
+            "sell" => Side::Sell,
+            _ => Side::Buy,
+        },
+        amount,
+        price: if amount > 0.0 { sol_amount as f64 / LAMPORTS_PER_SOL / amount } else { 0.0 },
+        timestamp: row.get(4)?,
+        wallet: row.get(5)?,
+        signature: row.get(6)?,
+        mint: row.get(7)?,
+        slot: row.get::<_, i64>(8)? as u64,
+        trade_seq: row.get::<_, i64>(10)? as u64,
+        token_amount,
+        sol_amount,
+        decimals,
+    })
+}
+
+/// Trades, newest first
+pub fn trades(conn: &Connection, filter: Filter, limit: u32, offset: u32) -> Result<Vec<Trade>> {
+    let (column, value) = filter.column();
+    let mut statement = conn.prepare(&format!(
+        "SELECT {} FROM trades t WHERE t.{} = ?1
+         ORDER BY t.slot DESC, t.signature DESC, t.event_index DESC LIMIT ?2 OFFSET ?3",
+        TRADE_COLUMNS, column
+    ))?;
+    let rows = statement.query_map(params![value, limit, offset], trade_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Every trade of a mint with the curve state after it, for candles
+pub fn history(conn: &Connection, mint: &str) -> Result<Vec<(Trade, CurveSnapshot)>> {
+    let mut statement = conn.prepare(&format!(
+        "SELECT {}, s.price_lamports, s.sol_reserves, s.token_reserves
+         FROM trades t JOIN snapshots s ON s.trade_id = t.id WHERE t.mint = ?1",
+        TRADE_COLUMNS
+    ))?;
+    let rows = statement.query_map([mint], |row| {
+        let trade = trade_row(row)?;
+        let price_lamports = row.get::<_, i64>(11)? as u64;
+        let snapshot = CurveSnapshot {
+            mint: trade.mint.clone(),
+            signature: trade.signature.clone(),
+            slot: trade.slot,
+            timestamp: trade.timestamp,
+            price: price_lamports as f64 / LAMPORTS_PER_SOL,
+            price_lamports,
+            sol_reserves: row.get::<_, i64>(12)? as u64,
+            token_reserves: row.get::<_, i64>(13)? as u64,
+            decimals: trade.decimals,
+        };
+        Ok((trade, snapshot))
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Balances of a wallet (base units) across its token accounts, by mint
+pub fn balances(conn: &Connection, wallet: &str) -> Result<Vec<(String, u64)>> {
+    let mut statement = conn.prepare(
+        "SELECT h.mint, SUM(h.amount) FROM holders h JOIN tokens t ON t.mint = h.mint
+         WHERE h.owner = ?1 AND h.amount > 0 GROUP BY h.mint ORDER BY h.mint",
+    )?;
+    let rows = statement.query_map([wallet], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Sequence number of the latest feed entry, 0 if the feed is empty
+pub fn latest_seq(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("SELECT COALESCE(MAX(seq), 0) FROM feed", [], |row| row.get(0))?)
+}
+
+/// Feed entries after a sequence number, oldest first, of everything or of
+/// one mint or wallet
+pub fn feed(conn: &Connection, after: i64, filter: Option<Filter>, limit: u32) -> Result<Vec<FeedMessage>> {
+    let (condition, value) = match &filter {
+        Some(filter) => {
+            let (column, value) = filter.column();
+            (format!("{} = ?2", column), value)
+        }
+        None => ("1".to_string(), ""),
+    };
+    let mut statement = conn.prepare(&format!(
+        "SELECT seq, kind, mint, wallet, slot, payload FROM feed
+         WHERE seq > ?1 AND {} ORDER BY seq LIMIT ?3",
+        condition
+    ))?;
+    let rows = statement.query_map(params![after, value, limit], |row| {
+        let payload: String = row.get(5)?;
+        Ok(FeedMessage {
+            seq: row.get(0)?,
+            kind: row.get(1)?,
+            mint: row.get(2)?,
+            wallet: row.get(3)?,
+            slot: row.get::<_, i64>(4)? as u64,
+            data: serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null),
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
@@ -0,0 +1,511 @@
+//! HTTP routes
+//!
+//! ```text
+//! GET /api/tokens?sort=marketCap|volume|recent&limit=&offset=
+//! GET /api/tokens/:mint
+//! GET /api/tokens/:mint/trades?limit=&offset=
+//! GET /api/tokens/:mint/candles?interval=1m&from=&to=
+//! GET /api/portfolio/:wallet
+//! GET /api/stream (WebSocket, see [`crate::stream`])
+//! ```
+
+use std::{
+    cmp::Reverse,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use asterlaunch_candles::{candle::market_cap, CandleEngine, Interval, TokenInfo};
+use asterlaunch_curve::ConstantProductCurve;
+use asterlaunch_indexer::Store;
+use asterlaunch_trade_decoder::model::{ui_amount, LAMPORTS_PER_SOL};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use rusqlite::Connection;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use crate::{
+    model::{Candles, FeedMessage, Holding, Portfolio, Token},
+    query::{self, Filter, TokenRow},
+    stream,
+};
+
+const DAY_MS: i64 = 86_400_000;
+
+/// Largest page of tokens or trades
+const MAX_LIMIT: u32 = 500;
+
+/// Feed entries buffered per stream connection before it must catch up from
+/// the database
+const FEED_CAPACITY: usize = 1024;
+
+/// Server settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiConfig {
+    /// Trades included in a token's detail
+    pub detail_trades: u32,
+
+    /// Candle width of a token's `priceHistory`
+    pub history_interval: Interval,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            detail_trades: 50,
+            history_interval: Interval::FiveMinutes,
+        }
+    }
+}
+
+/// Shared state of the handlers
+#[derive(Clone)]
+pub struct AppState {
+    store: Arc<Mutex<Store>>,
+    pub(crate) config: ApiConfig,
+    pub(crate) feed: broadcast::Sender<Arc<FeedMessage>>,
+}
+
+impl AppState {
+    /// Stream clients only get live entries once [`stream::follow_feed`] runs
+    pub fn new(store: Store, config: ApiConfig) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(store)),
+            config,
+            feed: broadcast::channel(FEED_CAPACITY).0,
+        }
+    }
+
+    /// Run queries against the database
+    pub(crate) fn read<T>(&self, f: impl FnOnce(&Connection) -> Result<T, ApiError>) -> Result<T, ApiError> {
+        let store = self.store.lock().map_err(|_| ApiError::Internal("database lock poisoned".to_string()))?;
+        f(store.connection())
+    }
+}
+
+/// An error response, `{ "error": "..." }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    NotFound(String),
+    BadRequest(String),
+    Internal(String),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        ApiError::Internal(format!("{:#}", err))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::Internal(message) => (StatusCode::INTERNAL_SERVER_ERROR, message),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+/// Routes of the API; responses allow any origin so local frontends can call it
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/api/tokens", get(list_tokens))
+        .route("/api/tokens/:mint", get(get_token))
+        .route("/api/tokens/:mint/trades", get(get_trades))
+        .route("/api/tokens/:mint/candles", get(get_candles))
+        .route("/api/portfolio/:wallet", get(get_portfolio))
+        .route("/api/stream", get(stream::stream))
+        .layer(axum::middleware::map_response(allow_any_origin))
+        .with_state(state)
+}
+
+async fn allow_any_origin(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    response
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+/// Build a token's response; `detail` adds its trades and price history
+fn build_token(conn: &Connection, row: &TokenRow, config: &ApiConfig, now: i64, detail: bool) -> anyhow::Result<Token> {
+    let market_cap_lamports = market_cap(row.price_lamports, row.total_supply, row.decimals);
+    let market_cap = market_cap_lamports as f64 / LAMPORTS_PER_SOL;
+    let price = row.price_lamports as f64 / LAMPORTS_PER_SOL;
+
+    let change_24h = match query::price_at(conn, &row.mint, now - DAY_MS)? {
+        Some(reference) if reference > 0 => (row.price_lamports as f64 / reference as f64 - 1.0) * 100.0,
+        _ => 0.0,
+    };
+
+    let is_graduated = row.graduated_at.is_some();
+    let graduation_progress = if is_graduated {
+        100.0
+    } else {
+        graduation_progress(conn, row, row.sol_reserves, row.token_reserves)?
+    };
+
+    let holder_count = query::holder_count(conn, &row.mint, &row.bonding_curve)?;
+    let liquidity = row.sol_reserves as f64 / LAMPORTS_PER_SOL;
+
+    let (trades, price_history) = if detail {
+        let trades = query::trades(conn, Filter::Mint(&row.mint), config.detail_trades, 0)?;
+        let history = query::history(conn, &row.mint)?;
+        let mut engine = CandleEngine::new();
+        engine.set_token(&row.mint, token_info(row));
+        engine.rebuild_mint(&row.mint, history.iter().map(|(trade, snapshot)| (trade, snapshot)));
+        let points = engine
+            .price_points(&row.mint, config.history_interval, i64::MIN, i64::MAX)
+            .unwrap_or_default();
+        (trades, points)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    Ok(Token {
+        id: row.mint.clone(),
+        name: row.name.clone(),
+        ticker: row.symbol.clone(),
+        description: row.description.clone(),
+        image_url: row.uri.clone(),
+        created_at: row.created_at,
+        creator: row.creator.clone(),
+        current_market_cap: market_cap,
+        price,
+        change_24h,
+        volume_24h: query::volume_since(conn, &row.mint, now - DAY_MS)? as f64 / LAMPORTS_PER_SOL,
+        total_supply: ui_amount(row.total_supply, row.decimals),
+        tokens_sold: ui_amount(row.total_supply.saturating_sub(row.token_reserves), row.decimals),
+        liquidity_locked: liquidity,
+        graduation_progress,
+        is_graduated,
+        holder_count,
+        trades,
+        price_history,
+        market_cap,
+        holders: holder_count,
+        liquidity,
+        image: row.uri.clone(),
+        program: row.program.clone(),
+        bonding_curve: row.bonding_curve.clone(),
+        decimals: row.decimals,
+    })
+}
+
+/// Progress (0-100) of a curve with the given reserves towards graduation
+///
+/// Native curves graduate once sold out. Anchor curves graduate once the
+/// program's market cap reaches the threshold its platform was initialized
+/// with; without an indexed threshold the progress is 0
+pub(crate) fn graduation_progress(
+    conn: &Connection,
+    row: &TokenRow,
+    sol_reserves: u64,
+    token_reserves: u64,
+) -> anyhow::Result<f64> {
+    let sold = row.total_supply.saturating_sub(token_reserves);
+    let (part, whole) = match row.program.as_str() {
+        "anchor" => {
+            let Some(threshold) = query::graduation_threshold(conn, &row.program)? else {
+                return Ok(0.0);
+            };
+            let curve = ConstantProductCurve {
+                virtual_sol_reserves: row.virtual_sol_reserves,
+                virtual_token_reserves: row.virtual_token_reserves,
+                real_sol_reserves: sol_reserves,
+                real_token_reserves: sold,
+                total_supply: row.total_supply,
+                decimals: row.decimals,
+            };
+            (curve.market_cap().unwrap_or(0), threshold)
+        }
+        _ => (sold, row.total_supply),
+    };
+    if whole == 0 {
+        return Ok(100.0);
+    }
+    Ok((part as f64 / whole as f64 * 100.0).min(100.0))
+}
+
+fn token_info(row: &TokenRow) -> TokenInfo {
+    TokenInfo {
+        supply: row.total_supply,
+        decimals: row.decimals,
+    }
+}
+
+fn find_token(conn: &Connection, mint: &str) -> Result<TokenRow, ApiError> {
+    query::token(conn, mint)?.ok_or_else(|| ApiError::NotFound(format!("unknown token {}", mint)))
+}
+
+/// Order of the token list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TokenSort {
+    MarketCap,
+    Volume,
+    #[default]
+    Recent,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    #[serde(default)]
+    sort: TokenSort,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+async fn list_tokens(State(state): State<AppState>, Query(params): Query<ListParams>) -> Result<Json<Vec<Token>>, ApiError> {
+    let now = now_ms();
+    let mut tokens = state.read(|conn| {
+        query::tokens(conn)?
+            .iter()
+            .map(|row| build_token(conn, row, &state.config, now, false).map_err(ApiError::from))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    match params.sort {
+        TokenSort::MarketCap => tokens.sort_by(|a, b| b.current_market_cap.total_cmp(&a.current_market_cap)),
+        TokenSort::Volume => tokens.sort_by(|a, b| b.volume_24h.total_cmp(&a.volume_24h)),
+        TokenSort::Recent => tokens.sort_by_key(|token| Reverse(token.created_at)),
+    }
+
+    let offset = params.offset.unwrap_or(0) as usize;
+    let limit = params.limit.unwrap_or(100).min(MAX_LIMIT) as usize;
+    Ok(Json(tokens.into_iter().skip(offset).take(limit).collect()))
+}
+
+async fn get_token(State(state): State<AppState>, Path(mint): Path<String>) -> Result<Json<Token>, ApiError> {
+    let now = now_ms();
+    let token = state.read(|conn| {
+        let row = find_token(conn, &mint)?;
+        Ok(build_token(conn, &row, &state.config, now, true)?)
+    })?;
+    Ok(Json(token))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageParams {
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+async fn get_trades(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(params): Query<PageParams>,
+) -> Result<Json<Vec<asterlaunch_trade_decoder::model::Trade>>, ApiError> {
+    let limit = params.limit.unwrap_or(100).min(MAX_LIMIT);
+    let trades = state.read(|conn| Ok(query::trades(conn, Filter::Mint(&mint), limit, params.offset.unwrap_or(0))?))?;
+    Ok(Json(trades))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CandleParams {
+    interval: Option<String>,
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+async fn get_candles(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(params): Query<CandleParams>,
+) -> Result<Json<Candles>, ApiError> {
+    let interval = match &params.interval {
+        Some(interval) => interval.parse::<Interval>().map_err(ApiError::BadRequest)?,
+        None => Interval::OneMinute,
+    };
+    let from = params.from.unwrap_or(i64::MIN);
+    let to = params.to.unwrap_or(i64::MAX);
+
+    let (row, history) = state.read(|conn| Ok((find_token(conn, &mint)?, query::history(conn, &mint)?)))?;
+
+    let mut engine = CandleEngine::new();
+    engine.set_token(&mint, token_info(&row));
+    engine.rebuild_mint(&mint, history.iter().map(|(trade, snapshot)| (trade, snapshot)));
+
+    Ok(Json(Candles {
+        mint: mint.clone(),
+        interval,
+        candles: engine.candles(&mint, interval, from, to).into_iter().cloned().collect(),
+        market_cap: engine.market_cap_candles(&mint, interval, from, to).unwrap_or_default(),
+    }))
+}
+
+async fn get_portfolio(State(state): State<AppState>, Path(wallet): Path<String>) -> Result<Json<Portfolio>, ApiError> {
+    let now = now_ms();
+    let portfolio = state.read(|conn| {
+        let mut holdings = Vec::new();
+        for (mint, amount) in query::balances(conn, &wallet)? {
+            let Some(row) = query::token(conn, &mint)? else {
+                continue;
+            };
+            let token = build_token(conn, &row, &state.config, now, false)?;
+            let balance = ui_amount(amount, row.decimals);
+            holdings.push(Holding {
+                value: balance * token.price,
+                balance,
+                token,
+            });
+        }
+
+        Ok(Portfolio {
+            total_value: holdings.iter().map(|holding| holding.value).sum(),
+            trades: query::trades(conn, Filter::Wallet(&wallet), state.config.detail_trades, 0)?,
+            wallet: wallet.clone(),
+            holdings,
+        })
+    })?;
+
+    Ok(Json(portfolio))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asterlaunch_indexer::store::{HolderRow, NewToken, TradeRow};
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    fn store() -> Store {
+        let mut store = Store::open_in_memory().unwrap();
+        let batch = store.batch().unwrap();
+        batch.set_graduation_threshold("anchor", 400 * LAMPORTS_PER_SOL as u64, 1).unwrap();
+        for (mint, program, created_at) in [("early", "anchor", 1_000), ("late", "native", 2_000)] {
+            batch
+                .insert_token(&NewToken {
+                    mint: mint.to_string(),
+                    program: program.to_string(),
+                    creator: "creator".to_string(),
+                    bonding_curve: format!("{}-curve", mint),
+                    name: mint.to_string(),
+                    symbol: mint.to_uppercase(),
+                    uri: "https://example.com/token.json".to_string(),
+                    description: String::new(),
+                    total_supply: 1_000_000_000_000,
+                    decimals: 6,
+                    created_at,
+                    slot: 1,
+                    signature: format!("{}-create", mint),
+                    virtual_sol_reserves: 20 * LAMPORTS_PER_SOL as u64,
+                    virtual_token_reserves: 1_000_000_000_000,
+                })
+                .unwrap();
+        }
+
+        // "early" trades twice, selling half its supply, and ends at 0.0001 SOL
+        // per token
+        let now = now_ms();
+        for (index, (price, timestamp)) in [(50_000, now - 2 * DAY_MS), (100_000, now - 60_000)].into_iter().enumerate() {
+            batch
+                .insert_trade(&TradeRow {
+                    id: format!("sig{}:0", index),
+                    signature: format!("sig{}", index),
+                    event_index: 0,
+                    slot: 10 + index as u64,
+                    timestamp,
+                    mint: "early".to_string(),
+                    wallet: "alice".to_string(),
+                    side: "buy".to_string(),
+                    token_amount: 2_000_000,
+                    sol_amount: 200_000,
+                    decimals: 6,
+                    liquidity_fee: 0,
+                    platform_fee: 1_000,
+                    creator_fee: 500,
+                    referral_fee: 0,
+                    trade_seq: index as u64 + 1,
+                    price_lamports: price,
+                    sol_reserves: 30 * LAMPORTS_PER_SOL as u64,
+                    token_reserves: 500_000_000_000,
+                })
+                .unwrap();
+        }
+        for (account, owner, amount) in [("a1", "alice", 3_000_000), ("a2", "alice", 1_000_000), ("c", "early-curve", 9)] {
+            batch
+                .upsert_holder(&HolderRow {
+                    account: account.to_string(),
+                    mint: "early".to_string(),
+                    owner: Some(owner.to_string()),
+                    amount,
+                    slot: 11,
+                })
+                .unwrap();
+        }
+        batch.commit().unwrap();
+        store
+    }
+
+    async fn get_json(uri: &str) -> (StatusCode, serde_json::Value) {
+        let app = router(AppState::new(store(), ApiConfig::default()));
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_token_list_and_detail() {
+        let (status, tokens) = get_json("/api/tokens").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(tokens[0]["id"], "late");
+        // A native curve graduates once sold out
+        assert_eq!(tokens[0]["graduationProgress"], 0.0);
+
+        let (_, tokens) = get_json("/api/tokens?sort=marketCap&limit=1").await;
+        assert_eq!(tokens.as_array().unwrap().len(), 1);
+        assert_eq!(tokens[0]["id"], "early");
+        // 0.0001 SOL per token for a million tokens
+        assert_eq!(tokens[0]["currentMarketCap"], 100.0);
+        // 50 SOL against half of the virtual tokens is a 100 SOL market cap,
+        // a quarter of the threshold
+        assert_eq!(tokens[0]["graduationProgress"], 25.0);
+        assert_eq!(tokens[0]["change24h"], 100.0);
+        assert_eq!(tokens[0]["volume24h"], 0.0002);
+        assert_eq!(tokens[0]["holderCount"], 1);
+
+        let (_, token) = get_json("/api/tokens/early").await;
+        assert_eq!(token["trades"].as_array().unwrap().len(), 2);
+        assert_eq!(token["trades"][0]["txHash"], "sig1");
+        assert_eq!(token["priceHistory"].as_array().unwrap().len(), 2);
+
+        let (status, error) = get_json("/api/tokens/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(error["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_candles_and_portfolio() {
+        let (_, candles) = get_json("/api/tokens/early/candles?interval=1d").await;
+        assert_eq!(candles["candles"].as_array().unwrap().len(), 2);
+        assert_eq!(candles["marketCap"][1]["close"], 100_000_000_000u64);
+
+        let (status, _) = get_json("/api/tokens/early/candles?interval=2m").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, portfolio) = get_json("/api/portfolio/alice").await;
+        assert_eq!(portfolio["holdings"][0]["balance"], 4.0);
+        assert_eq!(portfolio["totalValue"], 0.0004);
+        assert_eq!(portfolio["trades"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,280 @@
+//! Live feed over WebSocket
+//!
+//! ```text
+//! GET /api/stream                  everything
+//! GET /api/stream?mint=<MINT>      one token
+//! GET /api/stream?wallet=<WALLET>  one wallet's trades and launches
+//! GET /api/stream?since=<SEQ>      replay entries after SEQ first
+//! ```
+//!
+//! Every message is a [`FeedMessage`]. After the replay the server sends
+//! `{"type":"subscribed","seq":N}`; a client that reconnects with
+//! `since=<last seq seen>` misses nothing. `curve` messages also carry the
+//! token's `marketCap` (SOL) and `graduationProgress`.
+
+use std::{sync::Arc, time::Duration};
+
+use asterlaunch_candles::candle::market_cap;
+use asterlaunch_indexer::store::feed_kind;
+use asterlaunch_trade_decoder::model::LAMPORTS_PER_SOL;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    response::Response,
+};
+use rusqlite::Connection;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    model::FeedMessage,
+    query::{self, Filter},
+    routes::{graduation_progress, ApiError, AppState},
+};
+
+/// Feed entries read per query
+const PAGE: u32 = 500;
+
+/// What a client subscribes to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Topic {
+    All,
+    Mint(String),
+    Wallet(String),
+}
+
+impl Topic {
+    fn filter(&self) -> Option<Filter<'_>> {
+        match self {
+            Topic::All => None,
+            Topic::Mint(mint) => Some(Filter::Mint(mint)),
+            Topic::Wallet(wallet) => Some(Filter::Wallet(wallet)),
+        }
+    }
+
+    pub fn matches(&self, message: &FeedMessage) -> bool {
+        match self {
+            Topic::All => true,
+            Topic::Mint(mint) => message.mint == *mint,
+            Topic::Wallet(wallet) => message.wallet.as_deref() == Some(wallet.as_str()),
+        }
+    }
+}
+
+/// Feed entries after `after`, with curve entries filled in
+pub fn feed_page(conn: &Connection, after: i64, topic: &Topic) -> anyhow::Result<Vec<FeedMessage>> {
+    let mut messages = query::feed(conn, after, topic.filter(), PAGE)?;
+    for message in &mut messages {
+        enrich(conn, message)?;
+    }
+    Ok(messages)
+}
+
+/// Add the market cap and graduation progress to a curve entry
+fn enrich(conn: &Connection, message: &mut FeedMessage) -> anyhow::Result<()> {
+    if message.kind != feed_kind::CURVE {
+        return Ok(());
+    }
+    let Some(row) = query::token(conn, &message.mint)? else {
+        return Ok(());
+    };
+    let Some(data) = message.data.as_object_mut() else {
+        return Ok(());
+    };
+
+    let graduated = data.get("isGraduated").and_then(|value| value.as_bool()).unwrap_or(false);
+    let field = |name: &str| data.get(name).and_then(|value| value.as_u64());
+    let price_lamports = field("priceLamports").unwrap_or(row.price_lamports);
+    let sol_reserves = field("solReserves").unwrap_or(row.sol_reserves);
+    let token_reserves = field("tokenReserves").unwrap_or(row.token_reserves);
+    let market_cap_lamports = market_cap(price_lamports, row.total_supply, row.decimals);
+    let progress = if graduated {
+        100.0
+    } else {
+        graduation_progress(conn, &row, sol_reserves, token_reserves)?
+    };
+
+    data.insert("marketCap".to_string(), (market_cap_lamports as f64 / LAMPORTS_PER_SOL).into());
+    data.insert("graduationProgress".to_string(), progress.into());
+    Ok(())
+}
+
+/// Tail the feed table and broadcast new entries to every connection
+pub async fn follow_feed(state: AppState, period: Duration) {
+    let mut last = state.read(|conn| Ok(query::latest_seq(conn)?)).unwrap_or(0);
+    let mut ticker = tokio::time::interval(period);
+    loop {
+        ticker.tick().await;
+        let messages = match state.read(|conn| Ok(feed_page(conn, last, &Topic::All)?)) {
+            Ok(messages) => messages,
+            Err(err) => {
+                eprintln!("feed: {:?}", err);
+                continue;
+            }
+        };
+        for message in messages {
+            last = message.seq;
+            // No receivers just means no clients are connected
+            let _ = state.feed.send(Arc::new(message));
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamParams {
+    mint: Option<String>,
+    wallet: Option<String>,
+    since: Option<i64>,
+}
+
+pub async fn stream(
+    State(state): State<AppState>,
+    Query(params): Query<StreamParams>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let topic = match (params.mint, params.wallet) {
+        (Some(_), Some(_)) => return Err(ApiError::BadRequest("pass either mint or wallet, not both".to_string())),
+        (Some(mint), None) => Topic::Mint(mint),
+        (None, Some(wallet)) => Topic::Wallet(wallet),
+        (None, None) => Topic::All,
+    };
+    Ok(ws.on_upgrade(move |socket| serve(socket, state, topic, params.since)))
+}
+
+async fn serve(mut socket: WebSocket, state: AppState, topic: Topic, since: Option<i64>) {
+    // Subscribe before reading the table so nothing falls in between
+    let mut live = state.feed.subscribe();
+    let mut last = match since {
+        Some(seq) => seq,
+        None => match state.read(|conn| Ok(query::latest_seq(conn)?)) {
+            Ok(seq) => seq,
+            Err(_) => return,
+        },
+    };
+
+    if catch_up(&mut socket, &state, &topic, &mut last).await.is_err() {
+        return;
+    }
+    let subscribed = serde_json::json!({ "type": "subscribed", "seq": last });
+    if socket.send(Message::Text(subscribed.to_string())).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            received = live.recv() => match received {
+                Ok(message) => {
+                    if message.seq <= last {
+                        continue;
+                    }
+                    last = message.seq;
+                    if topic.matches(&message) && send(&mut socket, &message).await.is_err() {
+                        return;
+                    }
+                }
+                // Too slow for the channel: read what was missed from the table
+                Err(RecvError::Lagged(_)) => {
+                    if catch_up(&mut socket, &state, &topic, &mut last).await.is_err() {
+                        return;
+                    }
+                }
+                Err(RecvError::Closed) => return,
+            },
+            incoming = socket.recv() => match incoming {
+                None | Some(Err(_)) | Some(Ok(Message::Close(_))) => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Send every stored entry after `last`
+async fn catch_up(socket: &mut WebSocket, state: &AppState, topic: &Topic, last: &mut i64) -> Result<(), axum::Error> {
+    loop {
+        let page = match state.read(|conn| Ok(feed_page(conn, *last, topic)?)) {
+            Ok(page) => page,
+            Err(err) => {
+                eprintln!("feed: {:?}", err);
+                return Ok(());
+            }
+        };
+        if page.is_empty() {
+            return Ok(());
+        }
+        for message in &page {
+            send(socket, message).await?;
+            *last = message.seq;
+        }
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &FeedMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).map_err(axum::Error::new)?;
+    socket.send(Message::Text(text)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::{router, ApiConfig};
+    use asterlaunch_indexer::Store;
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite;
+
+    fn push(store: &mut Store, kind: &str, mint: &str, wallet: Option<&str>, payload: serde_json::Value) {
+        let batch = store.batch().unwrap();
+        batch.push_feed(kind, mint, wallet, 1, &payload).unwrap();
+        batch.commit().unwrap();
+    }
+
+    async fn next_json(
+        socket: &mut tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+    ) -> serde_json::Value {
+        let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+            .await
+            .expect("no message")
+            .unwrap()
+            .unwrap();
+        match message {
+            tungstenite::Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_then_live() {
+        let dir = std::env::temp_dir().join(format!("asterlaunch-stream-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("feed.db");
+        let _ = std::fs::remove_file(&path);
+
+        let mut writer = Store::open(&path).unwrap();
+        push(&mut writer, feed_kind::TRADE, "mint-a", Some("alice"), serde_json::json!({ "id": "t1" }));
+        push(&mut writer, feed_kind::TRADE, "mint-b", Some("bob"), serde_json::json!({ "id": "t2" }));
+
+        let state = AppState::new(Store::open(&path).unwrap(), ApiConfig::default());
+        tokio::spawn(follow_feed(state.clone(), Duration::from_millis(10)));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await.unwrap() });
+
+        let url = format!("ws://{}/api/stream?mint=mint-a&since=0", address);
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        let replayed = next_json(&mut socket).await;
+        assert_eq!(replayed["seq"], 1);
+        assert_eq!(replayed["type"], "trade");
+        assert_eq!(replayed["data"]["id"], "t1");
+        assert_eq!(next_json(&mut socket).await, serde_json::json!({ "type": "subscribed", "seq": 1 }));
+
+        push(&mut writer, feed_kind::TRADE, "mint-b", Some("bob"), serde_json::json!({ "id": "t3" }));
+        push(&mut writer, feed_kind::CURVE, "mint-a", None, serde_json::json!({ "isGraduated": true }));
+        let live = next_json(&mut socket).await;
+        assert_eq!(live["seq"], 4);
+        assert_eq!(live["type"], "curve");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,151 @@
+//! Candle types
+//!
+//! Prices are kept as the lamports-per-whole-token integers the programs
+//! compute (`BondingCurveState::spot_price` on the native program); they
+//! only become floating point SOL in `PricePoint`.
+
+use serde::{Deserialize, Serialize};
+
+use asterlaunch_trade_decoder::model::{Trade, LAMPORTS_PER_SOL};
+
+/// Position of a trade in chain order
+///
+/// Trades in the same slot are ordered by signature, which is deterministic
+/// but not necessarily their execution order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TradeKey {
+    pub slot: u64,
+    pub signature: String,
+    pub index: u32,
+}
+
+impl TradeKey {
+    /// Key of a decoded trade (its ID is `<signature>:<event index>`)
+    pub fn of(trade: &Trade) -> Self {
+        let index = trade
+            .id
+            .rsplit_once(':')
+            .and_then(|(_, index)| index.parse().ok())
+            .unwrap_or(0);
+
+        Self {
+            slot: trade.slot,
+            signature: trade.signature.clone(),
+            index,
+        }
+    }
+}
+
+/// OHLCV candle of one mint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candle {
+    /// Start of the candle (milliseconds since the epoch)
+    pub timestamp: i64,
+
+    /// Spot prices after the trades (lamports per whole token)
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+
+    /// Tokens traded (base units)
+    pub volume: u64,
+
+    /// Lamports paid by buyers and received by sellers
+    pub volume_sol: u64,
+
+    pub trades: u32,
+
+    #[serde(skip)]
+    open_key: Option<TradeKey>,
+
+    #[serde(skip)]
+    close_key: Option<TradeKey>,
+}
+
+impl Candle {
+    pub(crate) fn new(timestamp: i64) -> Self {
+        Self {
+            timestamp,
+            open: 0,
+            high: 0,
+            low: u64::MAX,
+            close: 0,
+            volume: 0,
+            volume_sol: 0,
+            trades: 0,
+            open_key: None,
+            close_key: None,
+        }
+    }
+
+    /// Add a trade, in any order relative to the trades already added
+    pub(crate) fn apply(&mut self, key: &TradeKey, price: u64, token_amount: u64, sol_amount: u64) {
+        if self.open_key.as_ref().is_none_or(|open| key < open) {
+            self.open = price;
+            self.open_key = Some(key.clone());
+        }
+        if self.close_key.as_ref().is_none_or(|close| key > close) {
+            self.close = price;
+            self.close_key = Some(key.clone());
+        }
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.volume = self.volume.saturating_add(token_amount);
+        self.volume_sol = self.volume_sol.saturating_add(sol_amount);
+        self.trades += 1;
+    }
+
+    /// Market-cap candle, given the mint's total supply (base units)
+    pub fn market_cap(&self, supply: u64, decimals: u8) -> MarketCapCandle {
+        let cap = |price: u64| market_cap(price, supply, decimals);
+
+        MarketCapCandle {
+            timestamp: self.timestamp,
+            open: cap(self.open),
+            high: cap(self.high),
+            low: cap(self.low),
+            close: cap(self.close),
+        }
+    }
+
+    /// Frontend chart point at the candle's close
+    pub fn price_point(&self, supply: u64, decimals: u8) -> PricePoint {
+        PricePoint {
+            timestamp: self.timestamp,
+            price: self.close as f64 / LAMPORTS_PER_SOL,
+            market_cap: market_cap(self.close, supply, decimals) as f64 / LAMPORTS_PER_SOL,
+        }
+    }
+}
+
+/// Market-cap candle (lamports)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketCapCandle {
+    pub timestamp: i64,
+    pub open: u128,
+    pub high: u128,
+    pub low: u128,
+    pub close: u128,
+}
+
+/// A chart point, matching the frontend's `PricePoint`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricePoint {
+    /// Milliseconds since the epoch
+    pub timestamp: i64,
+
+    /// SOL per whole token
+    pub price: f64,
+
+    /// SOL value of the total supply
+    pub market_cap: f64,
+}
+
+/// Lamport value of the total supply at a price per whole token
+pub fn market_cap(price: u64, supply: u64, decimals: u8) -> u128 {
+    price as u128 * supply as u128 / 10u128.pow(decimals as u32)
+}
@@ -0,0 +1,323 @@
+//! Per-mint candle aggregation
+//!
+//! Every trade updates one candle per interval. Candles are keyed by start
+//! time and open/close are chosen by chain order, so trades can arrive in
+//! any order; trades already seen (by ID) are ignored, so replays are safe.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use asterlaunch_program::events::AsterLaunchEvent;
+use asterlaunch_trade_decoder::{
+    model::{CurveSnapshot, Trade},
+    DecodedTransaction,
+};
+
+use crate::{
+    candle::{Candle, MarketCapCandle, PricePoint, TradeKey},
+    interval::Interval,
+};
+
+/// Supply of a mint, needed for market caps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenInfo {
+    /// Total supply (base units)
+    pub supply: u64,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Default)]
+struct Series {
+    candles: [BTreeMap<i64, Candle>; Interval::ALL.len()],
+    seen: HashSet<String>,
+}
+
+/// Candles of every mint
+#[derive(Debug, Default)]
+pub struct CandleEngine {
+    series: HashMap<String, Series>,
+    tokens: HashMap<String, TokenInfo>,
+}
+
+impl CandleEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build candles from a transaction history, in any order
+    pub fn rebuild<'a>(history: impl IntoIterator<Item = &'a DecodedTransaction>) -> Self {
+        let mut engine = Self::new();
+        for tx in history {
+            engine.ingest_transaction(tx);
+        }
+        engine
+    }
+
+    /// Drop a mint's candles and rebuild them from its trades
+    pub fn rebuild_mint<'a>(
+        &mut self,
+        mint: &str,
+        history: impl IntoIterator<Item = (&'a Trade, &'a CurveSnapshot)>,
+    ) {
+        self.series.remove(mint);
+        for (trade, snapshot) in history {
+            if trade.mint == mint {
+                self.ingest(trade, snapshot);
+            }
+        }
+    }
+
+    /// Record a mint's supply, for tokens created before ingestion started
+    pub fn set_token(&mut self, mint: &str, info: TokenInfo) {
+        self.tokens.insert(mint.to_string(), info);
+    }
+
+    pub fn token(&self, mint: &str) -> Option<TokenInfo> {
+        self.tokens.get(mint).copied()
+    }
+
+    /// Ingest a decoded transaction's token creations and trades, returning
+    /// the number of new trades
+    pub fn ingest_transaction(&mut self, tx: &DecodedTransaction) -> usize {
+        for decoded in &tx.events {
+            if let AsterLaunchEvent::TokenCreated(created) = &decoded.event {
+                self.set_token(
+                    &created.mint.to_string(),
+                    TokenInfo {
+                        supply: created.total_supply,
+                        decimals: created.decimals,
+                    },
+                );
+            }
+        }
+
+        // The decoder emits one snapshot per trade, in the same order
+        tx.trades
+            .iter()
+            .zip(&tx.snapshots)
+            .filter(|(trade, snapshot)| self.ingest(trade, snapshot))
+            .count()
+    }
+
+    /// Ingest a trade and the curve state after it, returning false if the
+    /// trade was already ingested
+    pub fn ingest(&mut self, trade: &Trade, snapshot: &CurveSnapshot) -> bool {
+        let series = self.series.entry(trade.mint.clone()).or_default();
+        if !series.seen.insert(trade.id.clone()) {
+            return false;
+        }
+
+        let key = TradeKey::of(trade);
+        for interval in Interval::ALL {
+            let start = interval.bucket_start(snapshot.timestamp);
+            series.candles[interval.index()]
+                .entry(start)
+                .or_insert_with(|| Candle::new(start))
+                .apply(&key, snapshot.price_lamports, trade.token_amount, trade.sol_amount);
+        }
+
+        true
+    }
+
+    /// Candles starting in `[from, to)` (milliseconds), oldest first
+    pub fn candles(&self, mint: &str, interval: Interval, from: i64, to: i64) -> Vec<&Candle> {
+        if from >= to {
+            return Vec::new();
+        }
+
+        self.series
+            .get(mint)
+            .map(|series| series.candles[interval.index()].range(from..to).map(|(_, candle)| candle).collect())
+            .unwrap_or_default()
+    }
+
+    /// Latest candle of a mint
+    pub fn last_candle(&self, mint: &str, interval: Interval) -> Option<&Candle> {
+        self.series
+            .get(mint)?
+            .candles[interval.index()]
+            .last_key_value()
+            .map(|(_, candle)| candle)
+    }
+
+    /// Market-cap candles starting in `[from, to)`, if the mint's supply is known
+    pub fn market_cap_candles(
+        &self,
+        mint: &str,
+        interval: Interval,
+        from: i64,
+        to: i64,
+    ) -> Option<Vec<MarketCapCandle>> {
+        let info = self.token(mint)?;
+        Some(
+            self.candles(mint, interval, from, to)
+                .into_iter()
+                .map(|candle| candle.market_cap(info.supply, info.decimals))
+                .collect(),
+        )
+    }
+
+    /// Frontend chart points starting in `[from, to)`, if the mint's supply is known
+    pub fn price_points(&self, mint: &str, interval: Interval, from: i64, to: i64) -> Option<Vec<PricePoint>> {
+        let info = self.token(mint)?;
+        Some(
+            self.candles(mint, interval, from, to)
+                .into_iter()
+                .map(|candle| candle.price_point(info.supply, info.decimals))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asterlaunch_curve::LinearCurve;
+    use asterlaunch_program::state::BondingCurveState;
+    use asterlaunch_trade_decoder::model::Side;
+
+    fn curve() -> BondingCurveState {
+        BondingCurveState::from_curve(
+            &LinearCurve {
+                initial_price: 1_000,
+                price_increment: 3,
+                total_supply: 1_000_000,
+                tokens_sold: 0,
+                decimals: 0,
+            },
+            0,
+        )
+    }
+
+    /// Buy or sell on the curve and decode the resulting trade
+    fn trade(curve: &mut BondingCurveState, n: usize, side: Side, amount: u64, timestamp: i64) -> (Trade, CurveSnapshot) {
+        let sol_amount = match side {
+            Side::Buy => {
+                let cost = curve.calculate_buy_price(amount).unwrap();
+                curve.tokens_sold += amount;
+                cost
+            }
+            Side::Sell => {
+                let value = curve.calculate_sell_price(amount).unwrap();
+                curve.tokens_sold -= amount;
+                value
+            }
+        };
+        let signature = format!("sig{:03}", n);
+
+        let trade = Trade {
+            id: format!("{}:0", signature),
+            side,
+            amount: amount as f64,
+            price: sol_amount as f64 / amount as f64 / 1e9,
+            timestamp,
+            wallet: String::new(),
+            signature: signature.clone(),
+            mint: "mint".to_string(),
+            slot: n as u64,
+            trade_seq: n as u64,
+            token_amount: amount,
+            sol_amount,
+            decimals: 0,
+        };
+        let snapshot = CurveSnapshot {
+            mint: "mint".to_string(),
+            signature,
+            slot: n as u64,
+            timestamp,
+            price: 0.0,
+            price_lamports: curve.spot_price().unwrap(),
+            sol_reserves: 0,
+            token_reserves: curve.total_supply - curve.tokens_sold,
+            decimals: 0,
+        };
+        (trade, snapshot)
+    }
+
+    fn history() -> (Vec<(Trade, CurveSnapshot)>, BondingCurveState) {
+        let mut curve = curve();
+        let trades = vec![
+            trade(&mut curve, 0, Side::Buy, 100, 0),
+            trade(&mut curve, 1, Side::Buy, 500, 400),
+            trade(&mut curve, 2, Side::Sell, 200, 1_500),
+            trade(&mut curve, 3, Side::Buy, 50, 61_000),
+            trade(&mut curve, 4, Side::Sell, 10, 61_000),
+        ];
+        (trades, curve)
+    }
+
+    #[test]
+    fn test_candles_match_curve_prices() {
+        let (trades, curve) = history();
+        let mut engine = CandleEngine::new();
+        for (trade, snapshot) in &trades {
+            assert!(engine.ingest(trade, snapshot));
+        }
+
+        let seconds = engine.candles("mint", Interval::OneSecond, 0, i64::MAX);
+        assert_eq!(seconds.len(), 3);
+        assert_eq!(seconds[0].open, 1_300);
+        assert_eq!(seconds[0].close, 2_800);
+        assert_eq!(seconds[0].trades, 2);
+        assert_eq!(seconds[1].timestamp, 1_000);
+        assert_eq!(seconds[2].timestamp, 61_000);
+
+        let minutes = engine.candles("mint", Interval::OneMinute, 0, i64::MAX);
+        assert_eq!(minutes.len(), 2);
+        assert_eq!((minutes[0].open, minutes[0].high, minutes[0].low, minutes[0].close), (1_300, 2_800, 1_300, 2_200));
+        assert_eq!(minutes[0].volume, 800);
+
+        // The chart closes at the curve's on-chain spot price
+        let day = engine.last_candle("mint", Interval::OneDay).unwrap();
+        assert_eq!(day.close, curve.spot_price().unwrap());
+        assert_eq!(day.volume_sol, trades.iter().map(|(trade, _)| trade.sol_amount).sum::<u64>());
+    }
+
+    #[test]
+    fn test_out_of_order_and_replayed_trades() {
+        let (trades, _) = history();
+        let mut in_order = CandleEngine::new();
+        for (trade, snapshot) in &trades {
+            in_order.ingest(trade, snapshot);
+        }
+
+        // Late trades and replays produce the same candles
+        let mut shuffled = CandleEngine::new();
+        for i in [3, 0, 4, 2, 1, 0, 3] {
+            shuffled.ingest(&trades[i].0, &trades[i].1);
+        }
+        assert!(!shuffled.ingest(&trades[2].0, &trades[2].1));
+
+        for interval in Interval::ALL {
+            assert_eq!(
+                in_order.candles("mint", interval, i64::MIN, i64::MAX),
+                shuffled.candles("mint", interval, i64::MIN, i64::MAX)
+            );
+        }
+
+        // Rebuilding a mint drops its candles and replays its history
+        shuffled.rebuild_mint("mint", trades[..2].iter().map(|(trade, snapshot)| (trade, snapshot)));
+        assert_eq!(shuffled.candles("mint", Interval::OneDay, 0, i64::MAX)[0].trades, 2);
+    }
+
+    #[test]
+    fn test_market_cap() {
+        let (trades, _) = history();
+        let mut engine = CandleEngine::new();
+        for (trade, snapshot) in &trades {
+            engine.ingest(trade, snapshot);
+        }
+        assert!(engine.price_points("mint", Interval::OneMinute, 0, i64::MAX).is_none());
+
+        engine.set_token("mint", TokenInfo { supply: 1_000_000, decimals: 0 });
+        let caps = engine.market_cap_candles("mint", Interval::OneMinute, 0, i64::MAX).unwrap();
+        assert_eq!(caps[0].high, 2_800_000_000);
+
+        let points = engine.price_points("mint", Interval::OneMinute, 0, i64::MAX).unwrap();
+        assert_eq!(points[0].price, 0.0000022);
+        assert_eq!(points[0].market_cap, 2.2);
+
+        let json = serde_json::to_value(points[0]).unwrap();
+        assert!(json.get("marketCap").is_some());
+        assert_eq!("5m".parse::<Interval>().unwrap(), Interval::FiveMinutes);
+    }
+}
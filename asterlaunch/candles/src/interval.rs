@@ -0,0 +1,76 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Candle width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Interval {
+    #[serde(rename = "1s")]
+    OneSecond,
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl Interval {
+    /// Every interval, narrowest first
+    pub const ALL: [Interval; 5] = [
+        Interval::OneSecond,
+        Interval::OneMinute,
+        Interval::FiveMinutes,
+        Interval::OneHour,
+        Interval::OneDay,
+    ];
+
+    /// Width in milliseconds
+    pub fn millis(self) -> i64 {
+        match self {
+            Interval::OneSecond => 1_000,
+            Interval::OneMinute => 60_000,
+            Interval::FiveMinutes => 300_000,
+            Interval::OneHour => 3_600_000,
+            Interval::OneDay => 86_400_000,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Interval::OneSecond => "1s",
+            Interval::OneMinute => "1m",
+            Interval::FiveMinutes => "5m",
+            Interval::OneHour => "1h",
+            Interval::OneDay => "1d",
+        }
+    }
+
+    /// Start of the candle containing a timestamp (milliseconds, UTC aligned)
+    pub fn bucket_start(self, timestamp: i64) -> i64 {
+        timestamp.div_euclid(self.millis()) * self.millis()
+    }
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Interval::ALL
+            .into_iter()
+            .find(|interval| interval.label() == s)
+            .ok_or_else(|| format!("Unknown Interval: {}", s))
+    }
+}
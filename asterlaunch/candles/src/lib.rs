@@ -0,0 +1,18 @@
+/*
+ * AsterLaunch Candles
+ * 
+ * Aggregates decoded trades into per-mint price charts:
+ * - OHLCV candles at 1s, 1m, 5m, 1h and 1d
+ * - Market-cap candles and frontend `PricePoint`s
+ * - Prices are the curve's on-chain spot price in lamports, so chart
+ *   prices match quotes exactly
+ * - Out-of-order, late and replayed trades land in the same candles
+ */
+
+pub mod candle;
+pub mod engine;
+pub mod interval;
+
+pub use candle::{Candle, MarketCapCandle, PricePoint, TradeKey};
+pub use engine::{CandleEngine, TokenInfo};
+pub use interval::Interval;
@@ -0,0 +1,226 @@
+//! Curve parameter tools
+//!
+//! `solve` finds curve parameters that reach a graduation target and prints
+//! the price path up to graduation.
+//!
+//! ```text
+//! asterlaunch-curve solve --family native --total-supply 1000000 --sold-bps 8000 --sol-raised 85
+//! asterlaunch-curve solve --family anchor --total-supply 1000000000 --virtual-token-reserves 1073000000 --sold-bps 7930 --market-cap 400
+//! ```
+
+use std::process::ExitCode;
+
+use anyhow::{bail, Context, Result};
+use asterlaunch_curve::{
+    constant_product::ConstantProductCurve,
+    solver::{self, Checkpoint, Graduation, Target},
+    CurveError, FeeStructure, LinearCurve, BPS,
+};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+
+/// Mint decimals of tokens launched by the Anchor program
+const ANCHOR_TOKEN_DECIMALS: u8 = 6;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+#[derive(Parser, Debug)]
+#[command(about = "Choose AsterLaunch bonding curve parameters")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Solve curve parameters for a graduation target and print the price path
+    Solve(SolveArgs),
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Family {
+    /// The native program's linear curve
+    Native,
+    /// The Anchor program's constant-product curve
+    Anchor,
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("target").required(true).args(["sol_raised", "market_cap"])))]
+struct SolveArgs {
+    #[arg(long, value_enum)]
+    family: Family,
+
+    /// Total supply in whole tokens
+    #[arg(long)]
+    total_supply: u64,
+
+    /// Mint decimals (the Anchor program always mints 6)
+    #[arg(long, default_value_t = ANCHOR_TOKEN_DECIMALS)]
+    decimals: u8,
+
+    /// Share of the supply sold at graduation (basis points)
+    #[arg(long, default_value_t = 8000)]
+    sold_bps: u16,
+
+    /// SOL raised by graduation, excluding fees
+    #[arg(long, value_parser = parse_sol)]
+    sol_raised: Option<u64>,
+
+    /// Market cap at graduation (SOL)
+    #[arg(long, value_parser = parse_sol)]
+    market_cap: Option<u64>,
+
+    /// Native: price of the first token (lamports)
+    #[arg(long, default_value_t = 100_000)]
+    initial_price: u64,
+
+    /// Anchor: virtual token reserves in whole tokens (default: the total supply)
+    #[arg(long)]
+    virtual_token_reserves: Option<u64>,
+
+    /// Rows in the price path
+    #[arg(long, default_value_t = 10)]
+    checkpoints: u64,
+}
+
+fn solve(args: SolveArgs) -> Result<()> {
+    if args.sold_bps == 0 || args.sold_bps as u64 > BPS {
+        bail!("--sold-bps must be between 1 and {}", BPS);
+    }
+    if args.family == Family::Anchor && args.decimals != ANCHOR_TOKEN_DECIMALS {
+        bail!("the Anchor program mints {} decimals", ANCHOR_TOKEN_DECIMALS);
+    }
+    let units = 10u64.checked_pow(args.decimals as u32).context("too many decimals")?;
+    let base_units = |tokens: u64| tokens.checked_mul(units).context("supply does not fit in base units");
+
+    let target = match (args.sol_raised, args.market_cap) {
+        (Some(sol), _) => Target::SolRaised(sol),
+        (None, Some(cap)) => Target::MarketCap(cap),
+        (None, None) => unreachable!("clap requires a target"),
+    };
+    let graduation = Graduation {
+        total_supply: base_units(args.total_supply)?,
+        decimals: args.decimals,
+        sold_bps: args.sold_bps,
+        target,
+    };
+
+    let path: Box<dyn Fn(u64) -> Result<Checkpoint, CurveError>> = match args.family {
+        Family::Native => {
+            let curve = graduation
+                .solve_linear(args.initial_price)
+                .context("no price increment reaches the target from this initial price")?;
+            print_linear(&curve);
+            Box::new(move |sold| solver::linear_checkpoint(&curve, sold))
+        }
+        Family::Anchor => {
+            let virtual_token_reserves = base_units(args.virtual_token_reserves.unwrap_or(args.total_supply))?;
+            let curve = graduation
+                .solve_constant_product(virtual_token_reserves)
+                .context("no virtual SOL reserves reach the target; the virtual token reserves must exceed the tokens sold")?;
+            print_constant_product(&curve, graduation.tokens_sold())?;
+            Box::new(move |sold| solver::constant_product_checkpoint(&curve, sold))
+        }
+    };
+
+    let reached = path(graduation.tokens_sold())?;
+    match target {
+        Target::SolRaised(sol) => println!("target:             {} SOL raised, reached {}", sol_string(sol), sol_string(reached.sol_raised)),
+        Target::MarketCap(cap) => println!("target:             {} SOL market cap, reached {}", sol_string(cap), sol_string(reached.market_cap)),
+    }
+
+    println!();
+    println!("{:>8}  {:>20}  {:>16}  {:>16}  {:>16}", "sold", "tokens sold", "price (lamports)", "SOL raised", "market cap (SOL)");
+    let checkpoints = args.checkpoints.max(1);
+    for row in 1..=checkpoints {
+        let bps = args.sold_bps as u64 * row / checkpoints;
+        let sold = (graduation.total_supply as u128 * bps as u128 / BPS as u128) as u64;
+        let checkpoint = path(sold)?;
+        println!(
+            "{:>7}%  {:>20}  {:>16}  {:>16}  {:>16}",
+            format_units(bps, 2),
+            checkpoint.tokens_sold / units,
+            checkpoint.price,
+            sol_string(checkpoint.sol_raised),
+            sol_string(checkpoint.market_cap),
+        );
+    }
+
+    Ok(())
+}
+
+fn print_linear(curve: &LinearCurve) {
+    println!("family:             native (linear)");
+    println!("initial_price:      {} lamports per token", curve.initial_price);
+    println!("price_increment:    {} (10^-{} lamports per token sold)", curve.price_increment, curve.decimals);
+    println!("total_supply:       {} base units, {} decimals", curve.total_supply, curve.decimals);
+    if curve.validate(&FeeStructure::default()).is_err() {
+        println!("warning:            buying the whole supply overflows; CreateToken would reject this curve");
+    }
+}
+
+fn print_constant_product(curve: &ConstantProductCurve, sold: u64) -> Result<()> {
+    let graduated = solver::constant_product_after(curve, sold)?;
+    println!("family:             anchor (constant product)");
+    println!("initial_virtual_sol_reserves:   {} lamports", curve.virtual_sol_reserves);
+    println!("initial_virtual_token_reserves: {} base units", curve.virtual_token_reserves);
+    println!("total_supply:       {} base units, {} decimals", curve.total_supply, curve.decimals);
+    // The program compares its own market cap formula against the threshold
+    println!("graduation_threshold: {} lamports", graduated.market_cap()?);
+    Ok(())
+}
+
+/// Parse a decimal SOL amount into lamports
+fn parse_sol(sol: &str) -> Result<u64> {
+    let (whole, fraction) = sol.trim().split_once('.').unwrap_or((sol.trim(), ""));
+    if whole.is_empty() && fraction.is_empty() || fraction.len() > 9 {
+        bail!("expected SOL with at most 9 decimals, e.g. 85 or 0.5");
+    }
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().context("invalid SOL amount")? };
+    let fraction: u64 = format!("{:0<9}", fraction).parse().context("invalid SOL amount")?;
+    whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(fraction))
+        .context("SOL amount too large")
+}
+
+fn sol_string(lamports: u64) -> String {
+    format_units(lamports / 100_000, 4)
+}
+
+/// `amount` with the last `decimals` digits after the point
+fn format_units(amount: u64, decimals: u32) -> String {
+    let scale = 10u64.pow(decimals);
+    format!("{}.{:0width$}", amount / scale, amount % scale, width = decimals as usize)
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Solve(args) => solve(args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sol() {
+        assert_eq!(parse_sol("85").unwrap(), 85 * LAMPORTS_PER_SOL);
+        assert_eq!(parse_sol("0.5").unwrap(), LAMPORTS_PER_SOL / 2);
+        assert_eq!(parse_sol(".000000001").unwrap(), 1);
+        assert!(parse_sol("0.0000000001").is_err());
+        assert!(parse_sol("").is_err());
+        assert!(parse_sol("-1").is_err());
+
+        assert_eq!(sol_string(85 * LAMPORTS_PER_SOL + 123_456_789), "85.1234");
+        assert_eq!(format_units(7930, 2), "79.30");
+    }
+}
@@ -0,0 +1,341 @@
+/*
+ * AsterLaunch Quoting Engine for the Web
+ *
+ * wasm-bindgen bindings to `asterlaunch-curve`, so the frontends quote
+ * trades with the programs' own integer math instead of floats:
+ * - `NativeCurve`: the native program's linear curve
+ * - `AnchorCurve`: the Anchor program's constant-product curve
+ * - Fee splits of both programs
+ *
+ * Amounts are `bigint` lamports and base units; prices are lamports per
+ * whole token. Build with `wasm-pack build --target web`.
+ */
+
+use asterlaunch_curve::{fees, ConstantProductCurve, CurveError, FeeStructure, LinearCurve, PlatformFee};
+use wasm_bindgen::prelude::*;
+
+fn js_error(error: CurveError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+/// Fees charged on a trade
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeFees {
+    /// Kept by the curve as liquidity
+    #[wasm_bindgen(readonly, js_name = liquidityFee)]
+    pub liquidity_fee: u64,
+
+    /// Paid to the platform, including any referrer's cut
+    #[wasm_bindgen(readonly, js_name = platformFee)]
+    pub platform_fee: u64,
+
+    /// Paid into the token creator's vault
+    #[wasm_bindgen(readonly, js_name = creatorFee)]
+    pub creator_fee: u64,
+}
+
+#[wasm_bindgen]
+impl TradeFees {
+    /// Sum of all fees
+    #[wasm_bindgen(getter, js_name = totalFee)]
+    pub fn total_fee(&self) -> u64 {
+        self.liquidity_fee + self.platform_fee + self.creator_fee
+    }
+}
+
+impl From<asterlaunch_curve::TradeFees> for TradeFees {
+    fn from(fees: asterlaunch_curve::TradeFees) -> Self {
+        Self {
+            liquidity_fee: fees.liquidity_fee,
+            platform_fee: fees.platform_fee,
+            creator_fee: fees.creator_fee,
+        }
+    }
+}
+
+/// Price of buying tokens
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuyQuote {
+    #[wasm_bindgen(readonly, js_name = tokenAmount)]
+    pub token_amount: u64,
+
+    /// Curve price of the tokens, before fees
+    #[wasm_bindgen(readonly)]
+    pub cost: u64,
+
+    #[wasm_bindgen(readonly)]
+    pub fees: TradeFees,
+
+    /// SOL the buyer pays
+    #[wasm_bindgen(readonly, js_name = totalCost)]
+    pub total_cost: u64,
+
+    #[wasm_bindgen(readonly, js_name = priceBefore)]
+    pub price_before: u64,
+
+    #[wasm_bindgen(readonly, js_name = priceAfter)]
+    pub price_after: u64,
+
+    #[wasm_bindgen(readonly, js_name = priceImpactBps)]
+    pub price_impact_bps: u64,
+
+    #[wasm_bindgen(readonly, js_name = graduationProgressBps)]
+    pub graduation_progress_bps: u16,
+}
+
+impl From<asterlaunch_curve::BuyQuote> for BuyQuote {
+    fn from(quote: asterlaunch_curve::BuyQuote) -> Self {
+        Self {
+            token_amount: quote.token_amount,
+            cost: quote.cost,
+            fees: quote.fees.into(),
+            total_cost: quote.total_cost,
+            price_before: quote.price_before,
+            price_after: quote.price_after,
+            price_impact_bps: quote.price_impact_bps,
+            graduation_progress_bps: quote.graduation_progress_bps,
+        }
+    }
+}
+
+/// Proceeds of selling tokens
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SellQuote {
+    #[wasm_bindgen(readonly, js_name = tokenAmount)]
+    pub token_amount: u64,
+
+    /// Curve value of the tokens
+    #[wasm_bindgen(readonly)]
+    pub proceeds: u64,
+
+    /// Share of the value paid out of the curve's reserves
+    #[wasm_bindgen(readonly)]
+    pub redeemed: u64,
+
+    #[wasm_bindgen(readonly)]
+    pub fees: TradeFees,
+
+    /// SOL the seller receives
+    #[wasm_bindgen(readonly, js_name = solOut)]
+    pub sol_out: u64,
+
+    #[wasm_bindgen(readonly, js_name = priceBefore)]
+    pub price_before: u64,
+
+    #[wasm_bindgen(readonly, js_name = priceAfter)]
+    pub price_after: u64,
+
+    #[wasm_bindgen(readonly, js_name = priceImpactBps)]
+    pub price_impact_bps: u64,
+
+    #[wasm_bindgen(readonly, js_name = graduationProgressBps)]
+    pub graduation_progress_bps: u16,
+}
+
+impl From<asterlaunch_curve::SellQuote> for SellQuote {
+    fn from(quote: asterlaunch_curve::SellQuote) -> Self {
+        Self {
+            token_amount: quote.token_amount,
+            proceeds: quote.proceeds,
+            redeemed: quote.redeemed,
+            fees: quote.fees.into(),
+            sol_out: quote.sol_out,
+            price_before: quote.price_before,
+            price_after: quote.price_after,
+            price_impact_bps: quote.price_impact_bps,
+            graduation_progress_bps: quote.graduation_progress_bps,
+        }
+    }
+}
+
+/// A native program bonding curve
+#[wasm_bindgen]
+pub struct NativeCurve {
+    curve: LinearCurve,
+    fees: FeeStructure,
+}
+
+#[wasm_bindgen]
+impl NativeCurve {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        initial_price: u64,
+        price_increment: u64,
+        total_supply: u64,
+        tokens_sold: u64,
+        decimals: u8,
+        creator_fee_bps: u16,
+    ) -> Result<NativeCurve, JsError> {
+        Ok(Self {
+            curve: LinearCurve {
+                initial_price,
+                price_increment,
+                total_supply,
+                tokens_sold,
+                decimals,
+            },
+            fees: FeeStructure::with_creator_fee(creator_fee_bps).map_err(js_error)?,
+        })
+    }
+
+    #[wasm_bindgen(js_name = spotPrice)]
+    pub fn spot_price(&self) -> Result<u64, JsError> {
+        self.curve.spot_price().map_err(js_error)
+    }
+
+    /// Cost of buying `token_amount` tokens, before fees
+    #[wasm_bindgen(js_name = calculateBuyPrice)]
+    pub fn calculate_buy_price(&self, token_amount: u64) -> Result<u64, JsError> {
+        self.curve.buy_cost(token_amount).map_err(js_error)
+    }
+
+    /// Value of selling `token_amount` tokens, before fees
+    #[wasm_bindgen(js_name = calculateSellPrice)]
+    pub fn calculate_sell_price(&self, token_amount: u64) -> Result<u64, JsError> {
+        self.curve.sell_value(token_amount).map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = quoteBuy)]
+    pub fn quote_buy(&self, token_amount: u64) -> Result<BuyQuote, JsError> {
+        Ok(self.curve.quote_buy(&self.fees, token_amount).map_err(js_error)?.into())
+    }
+
+    #[wasm_bindgen(js_name = quoteSell)]
+    pub fn quote_sell(&self, token_amount: u64) -> Result<SellQuote, JsError> {
+        Ok(self.curve.quote_sell(&self.fees, token_amount).map_err(js_error)?.into())
+    }
+
+    /// Most tokens `budget` lamports buys, fees included
+    #[wasm_bindgen(js_name = maxBuy)]
+    pub fn max_buy(&self, budget: u64) -> Result<u64, JsError> {
+        self.curve.max_buy(&self.fees, budget).map_err(js_error)
+    }
+
+    /// Share of the supply sold (basis points)
+    #[wasm_bindgen(js_name = graduationProgressBps)]
+    pub fn graduation_progress_bps(&self) -> u16 {
+        self.curve.progress_bps()
+    }
+}
+
+/// An Anchor program bonding curve
+#[wasm_bindgen]
+pub struct AnchorCurve {
+    curve: ConstantProductCurve,
+    fees: PlatformFee,
+}
+
+#[wasm_bindgen]
+impl AnchorCurve {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        virtual_sol_reserves: u64,
+        virtual_token_reserves: u64,
+        real_sol_reserves: u64,
+        real_token_reserves: u64,
+        total_supply: u64,
+        decimals: u8,
+        creator_fee_bps: u16,
+    ) -> AnchorCurve {
+        Self {
+            curve: ConstantProductCurve {
+                virtual_sol_reserves,
+                virtual_token_reserves,
+                real_sol_reserves,
+                real_token_reserves,
+                total_supply,
+                decimals,
+            },
+            fees: PlatformFee::with_creator_fee(creator_fee_bps),
+        }
+    }
+
+    #[wasm_bindgen(js_name = spotPrice)]
+    pub fn spot_price(&self) -> Result<u64, JsError> {
+        self.curve.spot_price().map_err(js_error)
+    }
+
+    /// Tokens bought with `sol_amount`, after fees
+    #[wasm_bindgen(js_name = tokensOut)]
+    pub fn tokens_out(&self, sol_amount: u64) -> Result<u64, JsError> {
+        self.curve.tokens_out(sol_amount).map_err(js_error)
+    }
+
+    /// SOL returned for `token_amount`, before fees
+    #[wasm_bindgen(js_name = solOut)]
+    pub fn sol_out(&self, token_amount: u64) -> Result<u64, JsError> {
+        self.curve.sol_out(token_amount).map_err(js_error)
+    }
+
+    /// Quote buying with `sol_amount`, fees included
+    #[wasm_bindgen(js_name = quoteBuy)]
+    pub fn quote_buy(&self, sol_amount: u64, graduation_threshold: u64) -> Result<BuyQuote, JsError> {
+        Ok(self.curve.quote_buy(&self.fees, sol_amount, graduation_threshold).map_err(js_error)?.into())
+    }
+
+    #[wasm_bindgen(js_name = quoteSell)]
+    pub fn quote_sell(&self, token_amount: u64, graduation_threshold: u64) -> Result<SellQuote, JsError> {
+        Ok(self.curve.quote_sell(&self.fees, token_amount, graduation_threshold).map_err(js_error)?.into())
+    }
+
+    #[wasm_bindgen(js_name = marketCap)]
+    pub fn market_cap(&self) -> Result<u64, JsError> {
+        self.curve.market_cap().map_err(js_error)
+    }
+
+    /// Market cap as a share of the graduation threshold (basis points)
+    #[wasm_bindgen(js_name = graduationProgressBps)]
+    pub fn graduation_progress_bps(&self, graduation_threshold: u64) -> Result<u16, JsError> {
+        self.curve.progress_bps(graduation_threshold).map_err(js_error)
+    }
+}
+
+/// Fee split of a native program trade valued at `amount`
+#[wasm_bindgen(js_name = nativeFeeSplit)]
+pub fn native_fee_split(amount: u64, creator_fee_bps: u16) -> Result<TradeFees, JsError> {
+    let fees = FeeStructure::with_creator_fee(creator_fee_bps).map_err(js_error)?;
+    Ok(fees.calculate_fee_breakdown(amount).map_err(js_error)?.trade_fees().into())
+}
+
+/// Fee split of an Anchor program trade of `amount`
+#[wasm_bindgen(js_name = anchorFeeSplit)]
+pub fn anchor_fee_split(amount: u64, creator_fee_bps: u16) -> Result<TradeFees, JsError> {
+    Ok(PlatformFee::with_creator_fee(creator_fee_bps).trade_fees(amount).map_err(js_error)?.into())
+}
+
+/// Referrer's share of an Anchor program platform fee
+#[wasm_bindgen(js_name = referralFee)]
+pub fn referral_fee(platform_fee: u64, referral_fee_bps: u16) -> Result<u64, JsError> {
+    fees::referral_fee(platform_fee, referral_fee_bps).map_err(js_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only success paths: building a `JsError` needs a JavaScript host
+
+    #[test]
+    fn test_native_curve() {
+        let curve = NativeCurve::new(100_000, 10, 1_000_000, 0, 0, 20).unwrap();
+        let quote = curve.quote_buy(1_000).unwrap();
+        assert_eq!(quote.cost, 104_995_000);
+        assert_eq!(quote.fees.total_fee(), 1_049_950);
+        assert_eq!(quote.fees, native_fee_split(quote.cost, 20).unwrap());
+        assert_eq!(curve.max_buy(quote.total_cost).unwrap(), 1_000);
+        assert_eq!(curve.calculate_buy_price(1_000).unwrap(), quote.cost);
+    }
+
+    #[test]
+    fn test_anchor_curve() {
+        let curve = AnchorCurve::new(30_000_000_000, 1_073_000_000_000_000, 0, 0, 1_000_000_000_000_000, 6, 20);
+        let quote = curve.quote_buy(1_000_000_000, 85_000_000_000).unwrap();
+        assert_eq!(quote.fees, anchor_fee_split(1_000_000_000, 20).unwrap());
+        assert_eq!(quote.cost + quote.fees.total_fee(), quote.total_cost);
+        assert_eq!(quote.token_amount, curve.tokens_out(quote.cost).unwrap());
+        assert!(quote.price_after > quote.price_before);
+    }
+}
@@ -0,0 +1,38 @@
+//! The fee invariants over arbitrary amounts and fee splits
+//!
+//! ```text
+//! cargo +nightly fuzz run fees
+//! ```
+
+#![no_main]
+
+#[path = "../../tests/invariants/mod.rs"]
+mod invariants;
+
+use arbitrary::Arbitrary;
+use asterlaunch_curve::{FeeStructure, PlatformFee};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    sol_amount: u64,
+    total_fee_bps: u16,
+    liquidity_fee_bps: u16,
+    platform_fee_bps: u16,
+    creator_fee_bps: u16,
+}
+
+fuzz_target!(|input: Input| {
+    let arbitrary = FeeStructure {
+        total_fee_bps: input.total_fee_bps,
+        liquidity_fee_bps: input.liquidity_fee_bps,
+        platform_fee_bps: input.platform_fee_bps,
+        creator_fee_bps: input.creator_fee_bps,
+    };
+    let _ = arbitrary.calculate_fee_breakdown(input.sol_amount);
+    let _ = PlatformFee { fee_bps: input.total_fee_bps, creator_fee_bps: input.creator_fee_bps }.trade_fees(input.sol_amount);
+
+    let Ok(fees) = FeeStructure::with_creator_fee(input.creator_fee_bps) else { return };
+    let platform_fee = PlatformFee::with_creator_fee(input.creator_fee_bps % 101);
+    invariants::fees_within_input(&fees, &platform_fee, input.sol_amount);
+});
@@ -0,0 +1,53 @@
+//! The linear curve invariants over arbitrary curves and trades
+//!
+//! ```text
+//! cargo +nightly fuzz run linear
+//! ```
+
+#![no_main]
+
+#[path = "../../tests/invariants/mod.rs"]
+mod invariants;
+
+use arbitrary::Arbitrary;
+use asterlaunch_curve::{FeeStructure, LinearCurve};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    initial_price: u64,
+    price_increment: u64,
+    total_supply: u64,
+    tokens_sold: u64,
+    decimals: u8,
+    creator_fee_bps: u16,
+    first: u64,
+    second: u64,
+}
+
+fuzz_target!(|input: Input| {
+    let Ok(fees) = FeeStructure::with_creator_fee(input.creator_fee_bps) else { return };
+    let curve = LinearCurve {
+        initial_price: input.initial_price,
+        price_increment: input.price_increment,
+        total_supply: input.total_supply,
+        tokens_sold: input.tokens_sold,
+        decimals: input.decimals,
+    };
+    // Trades on a curve `CreateToken` rejects may overflow; they must only
+    // fail, not panic
+    if curve.validate(&fees).is_err() || curve.tokens_sold > curve.total_supply {
+        let _ = curve.quote_buy(&fees, input.first);
+        let _ = curve.quote_sell(&fees, input.first);
+        let _ = curve.max_buy(&fees, input.second);
+        let _ = curve.price_at(input.second);
+        return;
+    }
+
+    // A curve of zero prices can hold every u64
+    let remaining = curve.tokens_remaining();
+    let first = input.first % remaining.saturating_add(1);
+    invariants::linear_round_trip(&curve, &fees, first);
+    invariants::linear_monotonic(&curve, first, input.second % remaining.saturating_add(1));
+    invariants::linear_split(&curve, &fees, first, input.second % (remaining - first).saturating_add(1));
+});
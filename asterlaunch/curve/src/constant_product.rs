@@ -0,0 +1,203 @@
+/*
+ * Constant-Product Curve
+ *
+ * The Anchor program's curve: SOL and token reserves, each offset by a
+ * virtual amount, keep their product constant.
+ *
+ * k = (virtual_sol + real_sol) * (virtual_token - real_token)
+ *
+ * `real_token_reserves` counts tokens sold out of the curve. Trades round
+ * in the curve's favour, so k never decreases.
+ */
+
+use crate::{
+    error::CurveError,
+    fees::PlatformFee,
+    quote::{self, BuyQuote, SellQuote},
+};
+
+/// Pricing state of a constant-product curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantProductCurve {
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+
+    /// SOL paid into the curve, after fees
+    pub real_sol_reserves: u64,
+
+    /// Tokens sold out of the curve
+    pub real_token_reserves: u64,
+
+    pub total_supply: u64,
+
+    /// Decimals of the token, to price whole tokens
+    pub decimals: u8,
+}
+
+impl ConstantProductCurve {
+    /// Current (sol, token) reserves priced by the curve
+    pub fn reserves(&self) -> Result<(u128, u128), CurveError> {
+        let sol_reserves = self.virtual_sol_reserves
+            .checked_add(self.real_sol_reserves)
+            .ok_or(CurveError::MathOverflow)?;
+        let token_reserves = self.virtual_token_reserves
+            .checked_sub(self.real_token_reserves)
+            .ok_or(CurveError::MathOverflow)?;
+        Ok((sol_reserves as u128, token_reserves as u128))
+    }
+
+    /// Spot price in lamports per whole token
+    pub fn spot_price(&self) -> Result<u64, CurveError> {
+        let (sol_reserves, token_reserves) = self.reserves()?;
+        let price = 10u128
+            .checked_pow(self.decimals as u32)
+            .and_then(|scale| sol_reserves.checked_mul(scale))
+            .and_then(|scaled| scaled.checked_div(token_reserves))
+            .ok_or(CurveError::MathOverflow)?;
+        u64::try_from(price).map_err(|_| CurveError::MathOverflow)
+    }
+
+    /// Tokens bought with `sol_amount` (after fees)
+    pub fn tokens_out(&self, sol_amount: u64) -> Result<u64, CurveError> {
+        let (sol_reserves, token_reserves) = self.reserves()?;
+        let k = sol_reserves * token_reserves;
+        let new_sol_reserves = sol_reserves + sol_amount as u128;
+        let new_token_reserves = div_ceil(k, new_sol_reserves)?;
+        u64::try_from(token_reserves - new_token_reserves).map_err(|_| CurveError::MathOverflow)
+    }
+
+    /// SOL returned for `token_amount` (before fees)
+    pub fn sol_out(&self, token_amount: u64) -> Result<u64, CurveError> {
+        let (sol_reserves, token_reserves) = self.reserves()?;
+        let k = sol_reserves * token_reserves;
+        let new_token_reserves = token_reserves + token_amount as u128;
+        let new_sol_reserves = div_ceil(k, new_token_reserves)?;
+        u64::try_from(sol_reserves - new_sol_reserves).map_err(|_| CurveError::MathOverflow)
+    }
+
+    /// Market cap in lamports, which the program compares against its
+    /// graduation threshold
+    ///
+    /// Total supply times the reserve ratio, rounded down once; the ratio is
+    /// lamports per base unit, so it is not rounded on its own
+    pub fn market_cap(&self) -> Result<u64, CurveError> {
+        let (sol_reserves, token_reserves) = self.reserves()?;
+        if token_reserves == 0 {
+            return Ok(0);
+        }
+        let cap = (self.total_supply as u128)
+            .checked_mul(sol_reserves)
+            .ok_or(CurveError::MathOverflow)?
+            / token_reserves;
+        u64::try_from(cap).map_err(|_| CurveError::MathOverflow)
+    }
+
+    /// Market cap as a share of the graduation threshold (basis points)
+    pub fn progress_bps(&self, graduation_threshold: u64) -> Result<u16, CurveError> {
+        Ok(quote::progress_bps(self.market_cap()?, graduation_threshold))
+    }
+
+    /// Quote buying with `sol_amount`, fees included
+    pub fn quote_buy(&self, fees: &PlatformFee, sol_amount: u64, graduation_threshold: u64) -> Result<BuyQuote, CurveError> {
+        if sol_amount == 0 {
+            return Err(CurveError::InvalidTradeAmount);
+        }
+
+        // Fees come out of the SOL paid; the rest goes into the curve
+        let fees = fees.trade_fees(sol_amount)?;
+        let cost = sol_amount - fees.total_fee();
+        let token_amount = self.tokens_out(cost)?;
+
+        let price_before = self.spot_price()?;
+        let after = Self {
+            real_sol_reserves: self.real_sol_reserves.checked_add(cost).ok_or(CurveError::MathOverflow)?,
+            real_token_reserves: self.real_token_reserves + token_amount,
+            ..*self
+        };
+
+        Ok(BuyQuote {
+            token_amount,
+            cost,
+            fees,
+            total_cost: sol_amount,
+            price_before,
+            price_after: after.spot_price()?,
+            price_impact_bps: quote::price_impact_bps(cost, token_amount, price_before, self.decimals)?,
+            graduation_progress_bps: after.progress_bps(graduation_threshold)?,
+        })
+    }
+
+    /// Quote selling `token_amount` tokens
+    ///
+    /// The whole curve value leaves the reserves: the fees are paid out of it
+    /// and the seller receives the rest
+    pub fn quote_sell(&self, fees: &PlatformFee, token_amount: u64, graduation_threshold: u64) -> Result<SellQuote, CurveError> {
+        if token_amount == 0 {
+            return Err(CurveError::InvalidTradeAmount);
+        }
+        if token_amount > self.real_token_reserves {
+            return Err(CurveError::InsufficientLiquidity);
+        }
+
+        let proceeds = self.sol_out(token_amount)?;
+        if proceeds > self.real_sol_reserves {
+            return Err(CurveError::InsufficientLiquidity);
+        }
+        let fees = fees.trade_fees(proceeds)?;
+        let sol_out = proceeds - fees.total_fee();
+
+        let price_before = self.spot_price()?;
+        let after = Self {
+            real_sol_reserves: self.real_sol_reserves - proceeds,
+            real_token_reserves: self.real_token_reserves - token_amount,
+            ..*self
+        };
+
+        Ok(SellQuote {
+            token_amount,
+            proceeds,
+            redeemed: proceeds,
+            fees,
+            sol_out,
+            price_before,
+            price_after: after.spot_price()?,
+            price_impact_bps: quote::price_impact_bps(proceeds, token_amount, price_before, self.decimals)?,
+            graduation_progress_bps: after.progress_bps(graduation_threshold)?,
+        })
+    }
+}
+
+/// `n / d` rounded up, in the curve's favour
+fn div_ceil(n: u128, d: u128) -> Result<u128, CurveError> {
+    if d == 0 {
+        return Err(CurveError::MathOverflow);
+    }
+    Ok(n.div_ceil(d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+    const TOKEN: u64 = 1_000_000;
+
+    #[test]
+    fn test_market_cap() {
+        // 110 SOL against 30% of a billion 6-decimal tokens
+        let curve = ConstantProductCurve {
+            virtual_sol_reserves: 30 * LAMPORTS_PER_SOL,
+            virtual_token_reserves: 1_000_000_000 * TOKEN,
+            real_sol_reserves: 80 * LAMPORTS_PER_SOL,
+            real_token_reserves: 700_000_000 * TOKEN,
+            total_supply: 1_000_000_000 * TOKEN,
+            decimals: 6,
+        };
+        assert_eq!(curve.market_cap().unwrap(), 366_666_666_666);
+        assert_eq!(curve.spot_price().unwrap(), 366);
+        assert_eq!(curve.progress_bps(400 * LAMPORTS_PER_SOL).unwrap(), 9_166);
+
+        let empty = ConstantProductCurve { real_sol_reserves: 0, real_token_reserves: 0, ..curve };
+        assert_eq!(empty.market_cap().unwrap(), 30 * LAMPORTS_PER_SOL);
+    }
+}
@@ -0,0 +1,35 @@
+use core::fmt;
+
+/// Errors from curve and fee math
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveError {
+    /// A result does not fit its type
+    MathOverflow,
+
+    /// Zero, or more than the curve can fill
+    InvalidTradeAmount,
+
+    /// The curve's reserves cannot pay for the trade
+    InsufficientLiquidity,
+
+    /// Fee shares exceed the fee they are carved from
+    InvalidFeeStructure,
+
+    /// The curve cannot price selling out its supply
+    InvalidCurveParameters,
+}
+
+impl fmt::Display for CurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CurveError::MathOverflow => "Math Overflow",
+            CurveError::InvalidTradeAmount => "Invalid Trade Amount",
+            CurveError::InsufficientLiquidity => "Insufficient Liquidity",
+            CurveError::InvalidFeeStructure => "Invalid Fee Structure",
+            CurveError::InvalidCurveParameters => "Invalid Curve Parameters",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CurveError {}
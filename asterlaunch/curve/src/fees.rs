@@ -0,0 +1,244 @@
+/*
+ * Fees
+ *
+ * Native program (`FeeStructure`):
+ * - 1% fee on the curve value (0.5% to liquidity, 0.5% to the platform)
+ * - The token creator's share, if any, is carved out of the platform half
+ * - SOL paid into the curve is split between a redeemable reserve that
+ *   backs sells and a liquidity reserve locked until graduation
+ *
+ * Anchor program (`PlatformFee`):
+ * - 1% fee on the SOL traded, all to the platform
+ * - The creator's share is carved out of it, and the referrer's share out
+ *   of what is left
+ *
+ * Fees round up and their shares round down, so splitting a trade never
+ * pays less in fees than trading at once.
+ */
+
+use crate::{error::CurveError, quote::TradeFees, BPS};
+
+/// Share of SOL paid into the curve that is locked as liquidity (basis points)
+pub const LIQUIDITY_SHARE_BPS: u16 = 5000;
+
+/// `bps` basis points of `amount`, rounded down
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64, CurveError> {
+    let share = amount as u128 * bps as u128 / BPS as u128;
+    u64::try_from(share).map_err(|_| CurveError::MathOverflow)
+}
+
+/// `bps` basis points of `amount`, rounded up
+pub fn bps_of_ceil(amount: u64, bps: u16) -> Result<u64, CurveError> {
+    let share = (amount as u128 * bps as u128).div_ceil(BPS as u128);
+    u64::try_from(share).map_err(|_| CurveError::MathOverflow)
+}
+
+/// Fee structure of the native program's trades
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeStructure {
+    /// Total fee in basis points (100 = 1%)
+    pub total_fee_bps: u16,
+
+    /// Portion of fee that goes to liquidity (basis points)
+    pub liquidity_fee_bps: u16,
+
+    /// Portion of fee that goes to platform treasury (basis points)
+    pub platform_fee_bps: u16,
+
+    /// Part of the platform portion paid to the token creator (basis points)
+    pub creator_fee_bps: u16,
+}
+
+/// Fee amounts charged on a single trade (in lamports)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    /// Trade amount after fees
+    pub net_amount: u64,
+
+    /// Fee kept by the curve as liquidity
+    pub liquidity_fee: u64,
+
+    /// Fee paid into the platform treasury
+    pub platform_fee: u64,
+
+    /// Fee paid into the token creator's vault
+    pub creator_fee: u64,
+}
+
+impl FeeBreakdown {
+    /// Sum of all fees
+    pub fn total_fee(&self) -> u64 {
+        self.liquidity_fee + self.platform_fee + self.creator_fee
+    }
+
+    /// The fee components alone
+    pub fn trade_fees(&self) -> TradeFees {
+        TradeFees {
+            liquidity_fee: self.liquidity_fee,
+            platform_fee: self.platform_fee,
+            creator_fee: self.creator_fee,
+        }
+    }
+}
+
+impl Default for FeeStructure {
+    /// 1% total (0.5% liquidity, 0.5% platform)
+    fn default() -> Self {
+        Self {
+            total_fee_bps: 100,      // 1%
+            liquidity_fee_bps: 50,   // 0.5%
+            platform_fee_bps: 50,    // 0.5%
+            creator_fee_bps: 0,
+        }
+    }
+}
+
+impl FeeStructure {
+    /// Default fee structure with part of the platform portion paid to the creator
+    pub fn with_creator_fee(creator_fee_bps: u16) -> Result<Self, CurveError> {
+        let fees = Self::default();
+        if creator_fee_bps > fees.platform_fee_bps {
+            return Err(CurveError::InvalidFeeStructure);
+        }
+
+        Ok(Self { creator_fee_bps, ..fees })
+    }
+
+    /// Calculate fee amounts from a SOL amount
+    ///
+    /// Returns `(net_amount, liquidity_fee, platform_fee)`; see
+    /// `calculate_fee_breakdown` for the creator's share
+    pub fn calculate_fees(&self, sol_amount: u64) -> Result<(u64, u64, u64), CurveError> {
+        let fees = self.calculate_fee_breakdown(sol_amount)?;
+
+        Ok((fees.net_amount, fees.liquidity_fee, fees.platform_fee))
+    }
+
+    /// Calculate every fee component from a SOL amount
+    ///
+    /// The total fee is rounded up, so splitting a trade never saves fees;
+    /// its liquidity and creator shares are rounded down
+    pub fn calculate_fee_breakdown(&self, sol_amount: u64) -> Result<FeeBreakdown, CurveError> {
+        let total_fee = bps_of_ceil(sol_amount, self.total_fee_bps)?;
+
+        let liquidity_fee = total_fee
+            .checked_mul(self.liquidity_fee_bps as u64)
+            .ok_or(CurveError::MathOverflow)?
+            .checked_div(self.total_fee_bps as u64)
+            .ok_or(CurveError::MathOverflow)?;
+
+        let creator_fee = total_fee
+            .checked_mul(self.creator_fee_bps as u64)
+            .ok_or(CurveError::MathOverflow)?
+            .checked_div(self.total_fee_bps as u64)
+            .ok_or(CurveError::MathOverflow)?;
+
+        let platform_fee = total_fee
+            .checked_sub(liquidity_fee)
+            .and_then(|fee| fee.checked_sub(creator_fee))
+            .ok_or(CurveError::MathOverflow)?;
+
+        let net_amount = sol_amount
+            .checked_sub(total_fee)
+            .ok_or(CurveError::MathOverflow)?;
+
+        Ok(FeeBreakdown {
+            net_amount,
+            liquidity_fee,
+            platform_fee,
+            creator_fee,
+        })
+    }
+}
+
+/// Split SOL paid into the curve between the redeemable and liquidity reserves
+///
+/// Returns: (redeemable, liquidity). The liquidity share is rounded down.
+pub fn split_liquidity(amount: u64) -> Result<(u64, u64), CurveError> {
+    let liquidity = bps_of(amount, LIQUIDITY_SHARE_BPS)?;
+
+    Ok((amount - liquidity, liquidity))
+}
+
+/// Portion of a curve value paid out of the redeemable reserve on a sell
+///
+/// Rounded down, so a sell never redeems more than the matching buy deposited
+pub fn calculate_redeemable(curve_value: u64) -> Result<u64, CurveError> {
+    bps_of(curve_value, 10000 - LIQUIDITY_SHARE_BPS)
+}
+
+/// Fee of the Anchor program's trades
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlatformFee {
+    /// Fee on the SOL traded (basis points of the trade)
+    pub fee_bps: u16,
+
+    /// Creator's share (basis points of the trade, carved out of the fee)
+    pub creator_fee_bps: u16,
+}
+
+impl PlatformFee {
+    /// Fee the Anchor program charges (1%)
+    pub const FEE_BPS: u16 = 100;
+
+    pub fn with_creator_fee(creator_fee_bps: u16) -> Self {
+        Self {
+            fee_bps: Self::FEE_BPS,
+            creator_fee_bps,
+        }
+    }
+
+    /// Fees on a trade of `sol_amount`
+    ///
+    /// The fee is rounded up, so splitting a trade never saves fees; the
+    /// creator's share is rounded down. `platform_fee` still includes the
+    /// referrer's share, which depends on the trader; see `referral_fee`
+    pub fn trade_fees(&self, sol_amount: u64) -> Result<TradeFees, CurveError> {
+        if self.fee_bps as u64 > BPS {
+            return Err(CurveError::InvalidFeeStructure);
+        }
+        let fee = bps_of_ceil(sol_amount, self.fee_bps)?;
+        let creator_fee = bps_of(sol_amount, self.creator_fee_bps)?;
+        let platform_fee = fee
+            .checked_sub(creator_fee)
+            .ok_or(CurveError::InvalidFeeStructure)?;
+
+        Ok(TradeFees {
+            liquidity_fee: 0,
+            platform_fee,
+            creator_fee,
+        })
+    }
+}
+
+/// Referrer's share of the platform fee (after the creator's share)
+pub fn referral_fee(platform_fee: u64, referral_fee_bps: u16) -> Result<u64, CurveError> {
+    bps_of(platform_fee, referral_fee_bps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_structures() {
+        let breakdown = FeeStructure::with_creator_fee(20).unwrap().calculate_fee_breakdown(1_000_000_000).unwrap();
+        assert_eq!(breakdown.net_amount, 990_000_000);
+        assert_eq!(breakdown.trade_fees().total_fee(), breakdown.total_fee());
+        assert_eq!(FeeStructure::with_creator_fee(51), Err(CurveError::InvalidFeeStructure));
+
+        let fees = PlatformFee::with_creator_fee(20).trade_fees(1_000_000_000).unwrap();
+        assert_eq!(fees.platform_fee, 8_000_000);
+        assert_eq!(fees.creator_fee, 2_000_000);
+        assert_eq!(fees.total_fee(), 10_000_000);
+        assert_eq!(referral_fee(fees.platform_fee, 2500).unwrap(), 2_000_000);
+        assert_eq!(PlatformFee::with_creator_fee(101).trade_fees(10_000), Err(CurveError::InvalidFeeStructure));
+
+        // Fees round up, their shares down
+        assert_eq!(FeeStructure::default().calculate_fees(150).unwrap(), (148, 1, 1));
+        let fees = PlatformFee::with_creator_fee(20).trade_fees(150).unwrap();
+        assert_eq!((fees.platform_fee, fees.creator_fee), (2, 0));
+        let fees = PlatformFee { fee_bps: 10_001, creator_fee_bps: 0 };
+        assert_eq!(fees.trade_fees(150), Err(CurveError::InvalidFeeStructure));
+    }
+}
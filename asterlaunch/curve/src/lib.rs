@@ -0,0 +1,35 @@
+/*
+ * AsterLaunch Curve Math
+ * 
+ * Pricing shared by both programs and the off-chain tools, so every quote
+ * is computed by the same integer code:
+ * - `linear`: the native program's linear curve
+ * - `constant_product`: the Anchor program's virtual-reserve curve
+ * - `fees`: fee splits of both programs and the native reserve split
+ * - `quote`: buy/sell quotes and trade results
+ * - `solver`: curve parameters for a graduation target
+ * 
+ * `no_std`, with no allocation; enable `std` for `std::error::Error` and
+ * `borsh` to encode the quote structs.
+ */
+
+#![no_std]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod constant_product;
+pub mod error;
+pub mod fees;
+pub mod linear;
+pub mod quote;
+pub mod solver;
+
+pub use constant_product::ConstantProductCurve;
+pub use error::CurveError;
+pub use fees::{FeeBreakdown, FeeStructure, PlatformFee};
+pub use linear::{LinearCurve, MAX_DECIMALS};
+pub use quote::{BuyQuote, SellQuote, TradeFees, TradeResult};
+
+/// Basis points in one whole
+pub const BPS: u64 = 10000;
@@ -0,0 +1,325 @@
+/*
+ * Linear Curve
+ *
+ * The native program's curve: the price rises by a fixed increment per
+ * whole token sold.
+ *
+ * price(n) = initial_price + n * price_increment / 10^(2 * decimals)
+ *
+ * Prices are lamports per whole token (10^decimals base units) and `n`
+ * counts base units sold, so each base unit costs price(n) / 10^decimals.
+ * The increment is fixed point at the mint's scale: each whole token sold
+ * raises the price by price_increment / 10^decimals lamports, so curves
+ * over large supplies can still rise gently.
+ *
+ * The sum over a trade is computed exactly and rounded once: up for buys,
+ * down for sells. Buying in several steps never costs less than buying at
+ * once, and selling back is never valued above what was paid; with 0
+ * decimals both are exact.
+ */
+
+use crate::{
+    error::CurveError,
+    fees::{self, FeeStructure},
+    quote::{self, BuyQuote, SellQuote},
+};
+
+/// Most decimals a linear curve prices; keeps the price scale cubed in u128
+pub const MAX_DECIMALS: u8 = 9;
+
+/// Pricing state of a linear curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinearCurve {
+    /// Price of the first token (lamports per whole token)
+    pub initial_price: u64,
+
+    /// Price increase per whole token sold (10^-decimals lamports per whole
+    /// token)
+    pub price_increment: u64,
+
+    /// Supply and sales in base units
+    pub total_supply: u64,
+    pub tokens_sold: u64,
+
+    /// Decimals of the token, to price whole tokens
+    pub decimals: u8,
+}
+
+impl LinearCurve {
+    /// Base units per whole token
+    fn scale(&self) -> Result<u128, CurveError> {
+        10u128.checked_pow(self.decimals as u32).ok_or(CurveError::MathOverflow)
+    }
+
+    /// Price at base unit `n` (counting from zero), in lamports per whole token
+    pub fn price_at(&self, n: u64) -> Result<u64, CurveError> {
+        let scale = self.scale()?;
+        let scale_squared = scale.checked_mul(scale).ok_or(CurveError::MathOverflow)?;
+        let price = (n as u128 * self.price_increment as u128 / scale_squared)
+            .checked_add(self.initial_price as u128)
+            .ok_or(CurveError::MathOverflow)?;
+        u64::try_from(price).map_err(|_| CurveError::MathOverflow)
+    }
+
+    /// Price of the next base unit sold, in lamports per whole token
+    pub fn spot_price(&self) -> Result<u64, CurveError> {
+        self.price_at(self.tokens_sold)
+    }
+
+    /// Tokens left to sell
+    pub fn tokens_remaining(&self) -> u64 {
+        self.total_supply.saturating_sub(self.tokens_sold)
+    }
+
+    /// Value of base units `start..start + amount` in lamports, rounded up
+    /// or down
+    fn sum_prices(&self, start: u64, amount: u64, round_up: bool) -> Result<u64, CurveError> {
+        if amount == 0 {
+            return Ok(0);
+        }
+        let scale = self.scale()?;
+        let scale_squared = scale.checked_mul(scale).ok_or(CurveError::MathOverflow)?;
+        let scale_cubed = scale_squared.checked_mul(scale).ok_or(CurveError::MathOverflow)?;
+        let amount = amount as u128;
+        let increment = self.price_increment as u128;
+
+        // amount * (first + last) is always even, so the halving is exact
+        let indices = (start as u128 * 2)
+            .checked_add(amount - 1)
+            .and_then(|sum| sum.checked_mul(amount))
+            .ok_or(CurveError::MathOverflow)?
+            / 2;
+
+        // value = (amount * initial_price * scale^2 + increment * indices) / scale^3
+        //
+        // The numerator can exceed u128, so each term is divided separately
+        // and the remainders, all below scale^3 * 2^64, are summed exactly
+        let base = amount * self.initial_price as u128;
+        let (indices_whole, indices_rest) = (indices / scale_cubed, indices % scale_cubed);
+        let rest_high = increment
+            .checked_mul(indices_rest / scale)
+            .ok_or(CurveError::MathOverflow)?;
+        let whole = (base / scale)
+            .checked_add(increment.checked_mul(indices_whole).ok_or(CurveError::MathOverflow)?)
+            .and_then(|sum| sum.checked_add(rest_high / scale_squared))
+            .ok_or(CurveError::MathOverflow)?;
+        let fraction = (base % scale) * scale_squared
+            + (rest_high % scale_squared) * scale
+            + increment * (indices_rest % scale);
+
+        let rounding = if round_up { fraction.div_ceil(scale_cubed) } else { fraction / scale_cubed };
+        let value = whole.checked_add(rounding).ok_or(CurveError::MathOverflow)?;
+        u64::try_from(value).map_err(|_| CurveError::MathOverflow)
+    }
+
+    /// Cost of buying the next `token_amount` base units, before fees
+    pub fn buy_cost(&self, token_amount: u64) -> Result<u64, CurveError> {
+        self.sum_prices(self.tokens_sold, token_amount, true)
+    }
+
+    /// Value of selling back the last `token_amount` base units sold, before fees
+    pub fn sell_value(&self, token_amount: u64) -> Result<u64, CurveError> {
+        if token_amount > self.tokens_sold {
+            return Err(CurveError::InvalidTradeAmount);
+        }
+        self.sum_prices(self.tokens_sold - token_amount, token_amount, false)
+    }
+
+    /// Check the curve can price selling out its whole supply
+    ///
+    /// Every trade on the curve prices a part of the full supply, so a
+    /// full-supply buy quoting without overflow bounds them all
+    pub fn validate(&self, fees: &FeeStructure) -> Result<(), CurveError> {
+        if self.total_supply == 0 || self.decimals > MAX_DECIMALS {
+            return Err(CurveError::InvalidCurveParameters);
+        }
+
+        let empty = Self { tokens_sold: 0, ..*self };
+        match empty.quote_buy(fees, self.total_supply) {
+            Ok(_) => Ok(()),
+            Err(CurveError::MathOverflow) => Err(CurveError::InvalidCurveParameters),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Share of the supply sold (basis points)
+    pub fn progress_bps(&self) -> u16 {
+        quote::progress_bps(self.tokens_sold, self.total_supply)
+    }
+
+    /// Quote buying `token_amount` base units, with fees charged on top
+    pub fn quote_buy(&self, fees: &FeeStructure, token_amount: u64) -> Result<BuyQuote, CurveError> {
+        if token_amount == 0 || token_amount > self.tokens_remaining() {
+            return Err(CurveError::InvalidTradeAmount);
+        }
+
+        let cost = self.buy_cost(token_amount)?;
+        let fees = fees.calculate_fee_breakdown(cost)?.trade_fees();
+        let total_cost = cost
+            .checked_add(fees.total_fee())
+            .ok_or(CurveError::MathOverflow)?;
+
+        let price_before = self.spot_price()?;
+        let after = Self {
+            tokens_sold: self.tokens_sold + token_amount,
+            ..*self
+        };
+
+        Ok(BuyQuote {
+            token_amount,
+            cost,
+            fees,
+            total_cost,
+            price_before,
+            price_after: after.spot_price()?,
+            price_impact_bps: quote::price_impact_bps(cost, token_amount, price_before, self.decimals)?,
+            graduation_progress_bps: after.progress_bps(),
+        })
+    }
+
+    /// Most base units `budget` lamports buys, fees included
+    pub fn max_buy(&self, fees: &FeeStructure, budget: u64) -> Result<u64, CurveError> {
+        // The total cost grows with the amount, so search for the largest
+        // amount that fits
+        let (mut low, mut high) = (0, self.tokens_remaining());
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            match self.quote_buy(fees, mid) {
+                Ok(quote) if quote.total_cost <= budget => low = mid,
+                Ok(_) | Err(CurveError::MathOverflow) => high = mid - 1,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(low)
+    }
+
+    /// Quote selling `token_amount` base units
+    ///
+    /// The seller redeems half of the curve value, and the fees on the full
+    /// value come out of that half. The fees are charged on the half
+    /// doubled, not on the value itself: rounding up a fee on the odd
+    /// lamport the half drops would pay a larger sell less.
+    pub fn quote_sell(&self, fees: &FeeStructure, token_amount: u64) -> Result<SellQuote, CurveError> {
+        if token_amount == 0 {
+            return Err(CurveError::InvalidTradeAmount);
+        }
+
+        let proceeds = self.sell_value(token_amount)?;
+        let redeemed = fees::calculate_redeemable(proceeds)?;
+        let fees = fees.calculate_fee_breakdown(redeemed * 2)?.trade_fees();
+        let sol_out = redeemed
+            .checked_sub(fees.total_fee())
+            .ok_or(CurveError::InvalidTradeAmount)?;
+
+        let price_before = self.spot_price()?;
+        let after = Self {
+            tokens_sold: self.tokens_sold - token_amount,
+            ..*self
+        };
+
+        Ok(SellQuote {
+            token_amount,
+            proceeds,
+            redeemed,
+            fees,
+            sol_out,
+            price_before,
+            price_after: after.spot_price()?,
+            price_impact_bps: quote::price_impact_bps(proceeds, token_amount, price_before, self.decimals)?,
+            graduation_progress_bps: after.progress_bps(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_cost_the_same() {
+        let mut curve = LinearCurve {
+            initial_price: 100_000,
+            price_increment: 7,
+            total_supply: 1_000_000,
+            tokens_sold: 0,
+            decimals: 0,
+        };
+        let at_once = curve.buy_cost(32).unwrap();
+
+        let mut paid = 0;
+        for amount in [1, 2, 3, 5, 8, 13] {
+            paid += curve.buy_cost(amount).unwrap();
+            curve.tokens_sold += amount;
+        }
+        assert_eq!(paid, at_once);
+        assert_eq!(curve.sell_value(32).unwrap(), paid);
+        assert_eq!(curve.sell_value(33), Err(CurveError::InvalidTradeAmount));
+        assert_eq!(curve.buy_cost(0).unwrap(), 0);
+
+        let fees = FeeStructure::default();
+        let budget = curve.quote_buy(&fees, 100).unwrap().total_cost;
+        assert_eq!(curve.max_buy(&fees, budget).unwrap(), 100);
+        assert_eq!(curve.max_buy(&fees, budget - 1).unwrap(), 99);
+    }
+
+    #[test]
+    fn test_prices_whole_tokens() {
+        let mut curve = LinearCurve {
+            initial_price: 100_000,
+            price_increment: 10_000_000,
+            total_supply: 1_000_000_000_000,
+            tokens_sold: 0,
+            decimals: 6,
+        };
+
+        // One whole token from 100_000 to 100_010 lamports, rounded in the
+        // curve's favour both ways
+        assert_eq!(curve.buy_cost(1_000_000).unwrap(), 100_005);
+        curve.tokens_sold = 1_000_000;
+        assert_eq!(curve.spot_price().unwrap(), 100_010);
+        assert_eq!(curve.sell_value(1_000_000).unwrap(), 100_004);
+
+        // Half a token past the first is priced half an increment higher
+        curve.tokens_sold = 1_500_000;
+        assert_eq!(curve.spot_price().unwrap(), 100_015);
+
+        // Steps never cost less than buying at once
+        curve.tokens_sold = 0;
+        let at_once = curve.buy_cost(3_000_000).unwrap();
+        let mut paid = 0;
+        for amount in [1, 999_999, 1_500_000, 500_000] {
+            paid += curve.buy_cost(amount).unwrap();
+            curve.tokens_sold += amount;
+        }
+        assert!(paid >= at_once);
+        assert!(curve.sell_value(3_000_000).unwrap() <= at_once);
+
+        let quote = LinearCurve { tokens_sold: 0, ..curve }
+            .quote_buy(&FeeStructure::default(), 1_000_000)
+            .unwrap();
+        assert_eq!(quote.price_impact_bps, 0);
+    }
+
+    #[test]
+    fn test_validate() {
+        let fees = FeeStructure::default();
+        let curve = LinearCurve {
+            initial_price: 100_000,
+            price_increment: 10,
+            total_supply: 1_000_000_000_000_000,
+            tokens_sold: 0,
+            decimals: 9,
+        };
+        assert_eq!(curve.validate(&fees), Ok(()));
+
+        let invalid = [
+            LinearCurve { total_supply: 0, ..curve },
+            LinearCurve { decimals: MAX_DECIMALS + 1, ..curve },
+            LinearCurve { initial_price: u64::MAX, ..curve },
+            LinearCurve { decimals: 0, ..curve },
+        ];
+        for curve in invalid {
+            assert_eq!(curve.validate(&fees), Err(CurveError::InvalidCurveParameters));
+        }
+    }
+}
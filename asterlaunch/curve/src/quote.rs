@@ -0,0 +1,137 @@
+/*
+ * Quotes
+ *
+ * What a buy or sell would charge or pay, and what a trade actually moved.
+ * The native program returns these borsh-encoded (`borsh` feature); all
+ * amounts are lamports and base units, prices are lamports per whole token.
+ */
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{error::CurveError, BPS};
+
+/// Fees charged on a trade
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TradeFees {
+    /// Kept by the curve as liquidity
+    pub liquidity_fee: u64,
+
+    /// Paid to the platform, including any referrer's cut
+    pub platform_fee: u64,
+
+    /// Paid into the token creator's vault
+    pub creator_fee: u64,
+}
+
+impl TradeFees {
+    /// Sum of all fees
+    pub fn total_fee(&self) -> u64 {
+        self.liquidity_fee + self.platform_fee + self.creator_fee
+    }
+}
+
+/// Price of buying tokens
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuyQuote {
+    pub token_amount: u64,
+
+    /// Curve price of the tokens, before fees
+    pub cost: u64,
+
+    /// Fees charged on top of the cost
+    pub fees: TradeFees,
+
+    /// SOL the buyer pays
+    pub total_cost: u64,
+
+    /// Spot price before and after the trade
+    pub price_before: u64,
+    pub price_after: u64,
+
+    /// Average price paid above the spot price before the trade (basis points)
+    pub price_impact_bps: u64,
+
+    /// Graduation progress after the trade (basis points)
+    pub graduation_progress_bps: u16,
+}
+
+/// Proceeds of selling tokens
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SellQuote {
+    pub token_amount: u64,
+
+    /// Curve value of the tokens
+    pub proceeds: u64,
+
+    /// Share of the value paid out of the curve's reserves
+    pub redeemed: u64,
+
+    /// Fees on the value, taken out of the redeemed SOL
+    pub fees: TradeFees,
+
+    /// SOL the seller receives
+    pub sol_out: u64,
+
+    /// Spot price before and after the trade
+    pub price_before: u64,
+    pub price_after: u64,
+
+    /// Average curve value below the spot price before the trade (basis points)
+    pub price_impact_bps: u64,
+
+    /// Graduation progress after the trade (basis points)
+    pub graduation_progress_bps: u16,
+}
+
+/// Fill of a trade
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeResult {
+    /// Tokens bought or sold (in base units)
+    pub tokens: u64,
+
+    /// SOL paid by the buyer including fees, or received by the seller after fees
+    pub sol: u64,
+
+    /// All fees charged on the trade
+    pub fees: u64,
+
+    /// Spot price after the trade
+    pub new_price: u64,
+}
+
+/// Distance between the average price of a trade and the spot price (basis points)
+///
+/// `value` is what `token_amount` base units traded for; `spot_price` is per
+/// whole token of `decimals` decimals
+pub fn price_impact_bps(value: u64, token_amount: u64, spot_price: u64, decimals: u8) -> Result<u64, CurveError> {
+    let spot_value = (spot_price as u128)
+        .checked_mul(token_amount as u128)
+        .ok_or(CurveError::MathOverflow)?;
+    if spot_value == 0 {
+        return Ok(0);
+    }
+
+    let value = 10u128
+        .checked_pow(decimals as u32)
+        .and_then(|scale| scale.checked_mul(value as u128))
+        .ok_or(CurveError::MathOverflow)?;
+    let impact = value
+        .abs_diff(spot_value)
+        .checked_mul(BPS as u128)
+        .ok_or(CurveError::MathOverflow)?
+        / spot_value;
+    u64::try_from(impact).map_err(|_| CurveError::MathOverflow)
+}
+
+/// `part` of `whole` in basis points, capped at 10000
+pub fn progress_bps(part: u64, whole: u64) -> u16 {
+    if whole == 0 {
+        return BPS as u16;
+    }
+    (part.min(whole) as u128 * BPS as u128 / whole as u128) as u16
+}
@@ -0,0 +1,253 @@
+/*
+ * Curve Parameter Solver
+ *
+ * Finds curve parameters that hit a graduation target: the SOL the curve
+ * has raised, or the market cap it has reached, once a given share of the
+ * supply is sold.
+ *
+ * Each family has two parameters and a target fixes one degree of freedom,
+ * so the caller fixes the other (the linear curve's initial price, the
+ * constant-product curve's virtual token reserves) and the solver searches
+ * the remaining one. The search runs on the curves' own integer math, so a
+ * solution prices exactly as the programs would.
+ *
+ * SOL raised is the curve value of the tokens sold, excluding fees. The
+ * constant-product market cap is `ConstantProductCurve::market_cap`, the
+ * figure the Anchor program graduates on; the native program graduates once
+ * sold out, so a linear curve's market cap is its spot price times the total
+ * supply in whole tokens.
+ */
+
+use crate::{
+    constant_product::ConstantProductCurve, error::CurveError, linear::LinearCurve, BPS,
+};
+
+/// What the curve should reach at graduation (lamports)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    SolRaised(u64),
+    MarketCap(u64),
+}
+
+/// Supply and graduation point to solve a curve for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Graduation {
+    /// Supply in base units
+    pub total_supply: u64,
+    pub decimals: u8,
+
+    /// Share of the supply sold at graduation (basis points)
+    pub sold_bps: u16,
+
+    pub target: Target,
+}
+
+/// State of a curve after some of its supply is sold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Base units sold
+    pub tokens_sold: u64,
+
+    /// Spot price (lamports per whole token)
+    pub price: u64,
+
+    /// SOL the curve has raised, excluding fees
+    pub sol_raised: u64,
+
+    /// Market cap as the curve's program computes it (see the module docs)
+    pub market_cap: u64,
+}
+
+impl Graduation {
+    /// Base units sold at graduation
+    pub fn tokens_sold(&self) -> u64 {
+        (self.total_supply as u128 * self.sold_bps.min(BPS as u16) as u128 / BPS as u128) as u64
+    }
+
+    /// Smallest `price_increment` that reaches the target, starting from
+    /// `initial_price`
+    pub fn solve_linear(&self, initial_price: u64) -> Result<LinearCurve, CurveError> {
+        let curve = |price_increment| LinearCurve {
+            initial_price,
+            price_increment,
+            total_supply: self.total_supply,
+            tokens_sold: 0,
+            decimals: self.decimals,
+        };
+        let sold = self.tokens_sold();
+        let price_increment = self.search(|price_increment| linear_checkpoint(&curve(price_increment), sold))?;
+        Ok(curve(price_increment))
+    }
+
+    /// Smallest `virtual_sol_reserves` that reaches the target, with
+    /// `virtual_token_reserves` fixed
+    pub fn solve_constant_product(&self, virtual_token_reserves: u64) -> Result<ConstantProductCurve, CurveError> {
+        if virtual_token_reserves <= self.tokens_sold() {
+            return Err(CurveError::InvalidCurveParameters);
+        }
+        let curve = |virtual_sol_reserves| ConstantProductCurve {
+            virtual_sol_reserves,
+            virtual_token_reserves,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+            total_supply: self.total_supply,
+            decimals: self.decimals,
+        };
+        let sold = self.tokens_sold();
+        let virtual_sol_reserves = self.search(|virtual_sol_reserves| {
+            constant_product_checkpoint(&curve(virtual_sol_reserves), sold)
+        })?;
+        Ok(curve(virtual_sol_reserves.max(1)))
+    }
+
+    /// Smallest parameter whose graduation checkpoint reaches the target
+    ///
+    /// Both targets grow with the parameter; a parameter the math cannot
+    /// price overshoots
+    fn search(&self, checkpoint: impl Fn(u64) -> Result<Checkpoint, CurveError>) -> Result<u64, CurveError> {
+        let reaches = |parameter| match checkpoint(parameter) {
+            Ok(checkpoint) => Ok(match self.target {
+                Target::SolRaised(target) => checkpoint.sol_raised >= target,
+                Target::MarketCap(target) => checkpoint.market_cap >= target,
+            }),
+            Err(CurveError::MathOverflow) => Ok(true),
+            Err(err) => Err(err),
+        };
+
+        let (mut low, mut high) = (0, u64::MAX);
+        if !reaches(high)? {
+            return Err(CurveError::InvalidCurveParameters);
+        }
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if reaches(mid)? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        // The smallest reaching parameter may still be unpriceable
+        checkpoint(low).map_err(|_| CurveError::InvalidCurveParameters)?;
+        Ok(low)
+    }
+}
+
+/// State of a linear curve once `tokens_sold` base units are sold
+pub fn linear_checkpoint(curve: &LinearCurve, tokens_sold: u64) -> Result<Checkpoint, CurveError> {
+    let empty = LinearCurve { tokens_sold: 0, ..*curve };
+    let sold = LinearCurve { tokens_sold, ..*curve };
+    let price = sold.spot_price()?;
+
+    Ok(Checkpoint {
+        tokens_sold,
+        price,
+        sol_raised: empty.buy_cost(tokens_sold)?,
+        market_cap: linear_market_cap(price, curve.total_supply, curve.decimals)?,
+    })
+}
+
+/// State of a constant-product curve once `tokens_sold` base units are sold
+///
+/// Assumes every token was bought from the curve's initial state
+pub fn constant_product_checkpoint(curve: &ConstantProductCurve, tokens_sold: u64) -> Result<Checkpoint, CurveError> {
+    let sold = constant_product_after(curve, tokens_sold)?;
+    let price = sold.spot_price()?;
+
+    Ok(Checkpoint {
+        tokens_sold,
+        price,
+        sol_raised: sold.real_sol_reserves,
+        market_cap: sold.market_cap()?,
+    })
+}
+
+/// A constant-product curve after buying `tokens_sold` from its initial state
+///
+/// The SOL paid in is the least that buys at least `tokens_sold`
+pub fn constant_product_after(curve: &ConstantProductCurve, tokens_sold: u64) -> Result<ConstantProductCurve, CurveError> {
+    let sol_reserves = curve.virtual_sol_reserves as u128;
+    let token_reserves = curve.virtual_token_reserves as u128;
+    let remaining = token_reserves
+        .checked_sub(tokens_sold as u128)
+        .filter(|remaining| *remaining > 0)
+        .ok_or(CurveError::InvalidTradeAmount)?;
+
+    let new_sol_reserves = sol_reserves
+        .checked_mul(token_reserves)
+        .ok_or(CurveError::MathOverflow)?
+        .div_ceil(remaining);
+    let sol_in = u64::try_from(new_sol_reserves - sol_reserves).map_err(|_| CurveError::MathOverflow)?;
+
+    Ok(ConstantProductCurve {
+        real_sol_reserves: sol_in,
+        real_token_reserves: tokens_sold,
+        ..*curve
+    })
+}
+
+/// `price` per whole token times `supply` base units
+fn linear_market_cap(price: u64, supply: u64, decimals: u8) -> Result<u64, CurveError> {
+    let cap = price as u128 * supply as u128 / 10u128.pow(decimals as u32);
+    u64::try_from(cap).map_err(|_| CurveError::MathOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_solve_linear() {
+        let graduation = Graduation {
+            total_supply: 1_000_000_000_000,
+            decimals: 6,
+            sold_bps: 8000,
+            target: Target::SolRaised(85 * LAMPORTS_PER_SOL),
+        };
+        let curve = graduation.solve_linear(50_000).unwrap();
+        assert_eq!(curve.price_increment, 140_625);
+        assert_eq!(curve.validate(&Default::default()), Ok(()));
+
+        // The solution is the smallest increment reaching the target
+        let sold = graduation.tokens_sold();
+        assert_eq!(sold, 800_000_000_000);
+        assert!(linear_checkpoint(&curve, sold).unwrap().sol_raised >= 85 * LAMPORTS_PER_SOL);
+        let below = LinearCurve { price_increment: curve.price_increment - 1, ..curve };
+        assert!(linear_checkpoint(&below, sold).unwrap().sol_raised < 85 * LAMPORTS_PER_SOL);
+
+        // A starting price already past the target needs no increment
+        let market_cap = Graduation { target: Target::MarketCap(1_000), ..graduation };
+        assert_eq!(market_cap.solve_linear(50_000).unwrap().price_increment, 0);
+    }
+
+    #[test]
+    fn test_solve_constant_product() {
+        let graduation = Graduation {
+            total_supply: 1_000_000_000_000_000,
+            decimals: 6,
+            sold_bps: 7930,
+            target: Target::MarketCap(400 * LAMPORTS_PER_SOL),
+        };
+        let curve = graduation.solve_constant_product(1_073_000_000_000_000).unwrap();
+
+        let sold = graduation.tokens_sold();
+        let checkpoint = constant_product_checkpoint(&curve, sold).unwrap();
+        assert!(checkpoint.market_cap >= 400 * LAMPORTS_PER_SOL);
+        // The checkpoint reports the market cap the program graduates on
+        let graduated = constant_product_after(&curve, sold).unwrap();
+        assert_eq!(checkpoint.market_cap, graduated.market_cap().unwrap());
+        let below = ConstantProductCurve { virtual_sol_reserves: curve.virtual_sol_reserves - 1, ..curve };
+        assert!(constant_product_checkpoint(&below, sold).unwrap().market_cap < 400 * LAMPORTS_PER_SOL);
+
+        // Buying the SOL raised from the initial state fills the checkpoint
+        assert!(curve.tokens_out(checkpoint.sol_raised).unwrap() >= sold);
+        assert!(curve.tokens_out(checkpoint.sol_raised - 1).unwrap() < sold);
+
+        // Selling past the virtual token reserves has no price
+        assert_eq!(
+            graduation.solve_constant_product(sold),
+            Err(CurveError::InvalidCurveParameters)
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Differential tests: the shared math against the native program's quote
+//! path and against independent reference implementations of both curves.
+
+use asterlaunch_curve::{ConstantProductCurve, CurveError, FeeStructure, LinearCurve, PlatformFee};
+use asterlaunch_program::{quote, state::BondingCurveState};
+
+/// Deterministic xorshift, so failures reproduce
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+/// Price of every base unit summed one by one, in lamports scaled by
+/// 10^(3 * decimals): returns (rounded up, rounded down)
+fn reference_linear_cost(curve: &LinearCurve, start: u64, amount: u64) -> (u64, u64) {
+    let scale = 10u128.pow(curve.decimals as u32);
+    let total: u128 = (start..start + amount)
+        .map(|n| curve.initial_price as u128 * scale * scale + n as u128 * curve.price_increment as u128)
+        .sum();
+    let divisor = scale * scale * scale;
+    (total.div_ceil(divisor) as u64, (total / divisor) as u64)
+}
+
+/// The Anchor program's buy, as written before it used the shared crate
+/// but with the fee rounded up: returns (tokens_out, platform_fee, creator_fee)
+fn reference_anchor_buy(curve: &ConstantProductCurve, creator_fee_bps: u16, sol_amount: u64) -> (u64, u64, u64) {
+    let platform_fee = (sol_amount * 100).div_ceil(10000);
+    let creator_fee = sol_amount * creator_fee_bps as u64 / 10000;
+    let sol_after_fee = sol_amount - platform_fee;
+
+    let sol_reserves = (curve.virtual_sol_reserves + curve.real_sol_reserves) as u128;
+    let token_reserves = (curve.virtual_token_reserves - curve.real_token_reserves) as u128;
+    let k = sol_reserves * token_reserves;
+    let new_token_reserves = k.div_ceil(sol_reserves + sol_after_fee as u128);
+    ((token_reserves - new_token_reserves) as u64, platform_fee, creator_fee)
+}
+
+/// The Anchor program's sell, as written before it used the shared crate
+/// but with the fee rounded up: returns (sol_out_before_fee, platform_fee, creator_fee)
+fn reference_anchor_sell(curve: &ConstantProductCurve, creator_fee_bps: u16, token_amount: u64) -> (u64, u64, u64) {
+    let sol_reserves = (curve.virtual_sol_reserves + curve.real_sol_reserves) as u128;
+    let token_reserves = (curve.virtual_token_reserves - curve.real_token_reserves) as u128;
+    let k = sol_reserves * token_reserves;
+    let new_sol_reserves = k.div_ceil(token_reserves + token_amount as u128);
+    let sol_out_before_fee = (sol_reserves - new_sol_reserves) as u64;
+
+    let platform_fee = (sol_out_before_fee * 100).div_ceil(10000);
+    let creator_fee = sol_out_before_fee * creator_fee_bps as u64 / 10000;
+    (sol_out_before_fee, platform_fee, creator_fee)
+}
+
+#[test]
+fn linear_matches_native_program_and_reference() {
+    let mut rng = Rng(0x5eed_1234_abcd_0001);
+    for _ in 0..2000 {
+        let total_supply = 1 + rng.below(1_000_000);
+        let curve = LinearCurve {
+            initial_price: 1 + rng.below(1_000_000),
+            price_increment: rng.below(1_000),
+            total_supply,
+            tokens_sold: rng.below(total_supply + 1),
+            decimals: rng.below(10) as u8,
+        };
+        let creator_fee_bps = rng.below(51) as u16;
+        let native = BondingCurveState::from_curve(&curve, creator_fee_bps);
+        let fees = FeeStructure::with_creator_fee(creator_fee_bps).unwrap();
+
+        let buy_amount = rng.below(curve.tokens_remaining().min(2_000) + 1);
+        assert_eq!(
+            curve.buy_cost(buy_amount).unwrap(),
+            reference_linear_cost(&curve, curve.tokens_sold, buy_amount).0
+        );
+        match (curve.quote_buy(&fees, buy_amount), quote::quote_buy(&native, buy_amount)) {
+            (Ok(shared), Ok(program)) => assert_eq!(shared, program),
+            (Err(CurveError::InvalidTradeAmount), Err(_)) => assert_eq!(buy_amount, 0),
+            (shared, program) => panic!("buy {} on {:?}: {:?} vs {:?}", buy_amount, curve, shared, program),
+        }
+
+        let sell_amount = rng.below(curve.tokens_sold.min(2_000) + 1);
+        assert_eq!(
+            curve.sell_value(sell_amount).unwrap(),
+            reference_linear_cost(&curve, curve.tokens_sold - sell_amount, sell_amount).1
+        );
+        match (curve.quote_sell(&fees, sell_amount), quote::quote_sell(&native, sell_amount)) {
+            (Ok(shared), Ok(program)) => assert_eq!(shared, program),
+            (Err(_), Err(_)) => {}
+            (shared, program) => panic!("sell {} on {:?}: {:?} vs {:?}", sell_amount, curve, shared, program),
+        }
+    }
+}
+
+#[test]
+fn linear_sums_large_trades_exactly() {
+    let mut rng = Rng(0x5eed_1234_abcd_0004);
+    let mut checked = 0;
+    for _ in 0..5000 {
+        let curve = LinearCurve {
+            initial_price: rng.below(1_000_000_000),
+            price_increment: rng.below(1_000_000_000_000),
+            total_supply: u64::MAX,
+            tokens_sold: rng.below(1_000_000_000_000_000),
+            decimals: rng.below(10) as u8,
+        };
+        let amount = 1 + rng.below(1_000_000_000_000);
+
+        // The closed form, wherever its numerator fits in u128
+        let scale = 10u128.pow(curve.decimals as u32);
+        let (start, amount_wide) = (curve.tokens_sold as u128, amount as u128);
+        let indices = (2 * start + amount_wide - 1) * amount_wide / 2;
+        let numerator = (amount_wide * curve.initial_price as u128)
+            .checked_mul(scale * scale)
+            .and_then(|base| indices.checked_mul(curve.price_increment as u128)?.checked_add(base));
+        let Some(numerator) = numerator else { continue };
+
+        let divisor = scale * scale * scale;
+        match (curve.buy_cost(amount), u64::try_from(numerator.div_ceil(divisor))) {
+            (Ok(cost), Ok(expected)) => assert_eq!(cost, expected),
+            (Err(CurveError::MathOverflow), Err(_)) => {}
+            (cost, expected) => panic!("buy {} on {:?}: {:?} vs {:?}", amount, curve, cost, expected),
+        }
+        checked += 1;
+    }
+    assert!(checked > 2500, "only {} trades checked", checked);
+}
+
+#[test]
+fn constant_product_matches_anchor_reference() {
+    let mut rng = Rng(0x5eed_1234_abcd_0002);
+    for _ in 0..2000 {
+        let virtual_token_reserves = 1_000_000_000_000 + rng.below(1_000_000_000_000_000);
+        let curve = ConstantProductCurve {
+            virtual_sol_reserves: 1_000_000_000 + rng.below(100_000_000_000),
+            virtual_token_reserves,
+            real_sol_reserves: rng.below(100_000_000_000),
+            real_token_reserves: rng.below(virtual_token_reserves / 2),
+            total_supply: virtual_token_reserves,
+            decimals: 6,
+        };
+        let creator_fee_bps = rng.below(101) as u16;
+        let fees = PlatformFee::with_creator_fee(creator_fee_bps);
+
+        let sol_amount = 1 + rng.below(50_000_000_000);
+        let buy = curve.quote_buy(&fees, sol_amount, u64::MAX).unwrap();
+        let (tokens_out, platform_fee, creator_fee) = reference_anchor_buy(&curve, creator_fee_bps, sol_amount);
+        assert_eq!(buy.token_amount, tokens_out);
+        assert_eq!(buy.fees.total_fee(), platform_fee);
+        assert_eq!(buy.fees.creator_fee, creator_fee);
+        assert_eq!(buy.cost, sol_amount - platform_fee);
+
+        let token_amount = 1 + rng.below(curve.real_token_reserves.max(1));
+        let (sol_out_before_fee, platform_fee, creator_fee) = reference_anchor_sell(&curve, creator_fee_bps, token_amount);
+        match curve.quote_sell(&fees, token_amount, u64::MAX) {
+            Ok(sell) => {
+                assert_eq!(sell.proceeds, sol_out_before_fee);
+                assert_eq!(sell.fees.total_fee(), platform_fee);
+                assert_eq!(sell.fees.creator_fee, creator_fee);
+                assert_eq!(sell.sol_out, sol_out_before_fee - platform_fee);
+            }
+            Err(CurveError::InsufficientLiquidity) => {
+                assert!(token_amount > curve.real_token_reserves || sol_out_before_fee > curve.real_sol_reserves)
+            }
+            Err(err) => panic!("sell {} on {:?}: {:?}", token_amount, curve, err),
+        }
+    }
+}
+
+#[test]
+fn round_trips_never_pay_out_more_than_paid_in() {
+    let mut rng = Rng(0x5eed_1234_abcd_0003);
+    for _ in 0..1000 {
+        let linear = LinearCurve {
+            initial_price: 1 + rng.below(1_000_000),
+            price_increment: rng.below(1_000),
+            total_supply: 1_000_000,
+            tokens_sold: rng.below(500_000),
+            decimals: rng.below(10) as u8,
+        };
+        let fees = FeeStructure::default();
+        let amount = 1 + rng.below(10_000);
+        let buy = linear.quote_buy(&fees, amount).unwrap();
+        let after = LinearCurve { tokens_sold: linear.tokens_sold + amount, ..linear };
+        // A sell worth less than its fee is refused
+        if let Ok(sell) = after.quote_sell(&fees, amount) {
+            assert!(sell.sol_out < buy.total_cost);
+        }
+
+        let product = ConstantProductCurve {
+            virtual_sol_reserves: 30_000_000_000,
+            virtual_token_reserves: 1_073_000_000_000_000,
+            real_sol_reserves: rng.below(50_000_000_000),
+            real_token_reserves: rng.below(500_000_000_000_000),
+            total_supply: 1_000_000_000_000_000,
+            decimals: 6,
+        };
+        let fees = PlatformFee::with_creator_fee(20);
+        let buy = product.quote_buy(&fees, 1 + rng.below(10_000_000_000), u64::MAX).unwrap();
+        let after = ConstantProductCurve {
+            real_sol_reserves: product.real_sol_reserves + buy.cost,
+            real_token_reserves: product.real_token_reserves + buy.token_amount,
+            ..product
+        };
+        if buy.token_amount > 0 {
+            assert!(after.quote_sell(&fees, buy.token_amount, u64::MAX).unwrap().proceeds <= buy.cost);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Curve and fee invariants, shared by the property tests and the fuzz
+//! targets
+//!
+//! Each check takes arbitrary inputs, skips those the math rejects with an
+//! error, and panics if an accepted trade breaks an invariant.
+
+#![allow(dead_code)]
+
+use asterlaunch_curve::{ConstantProductCurve, FeeStructure, LinearCurve, PlatformFee};
+
+/// Fees split `sol_amount` exactly and never exceed it
+pub fn fees_within_input(fees: &FeeStructure, platform_fee: &PlatformFee, sol_amount: u64) {
+    if let Ok((net, liquidity_fee, platform_fee)) = fees.calculate_fees(sol_amount) {
+        let breakdown = fees.calculate_fee_breakdown(sol_amount).unwrap();
+        assert_eq!(net, breakdown.net_amount);
+        assert_eq!((liquidity_fee, platform_fee), (breakdown.liquidity_fee, breakdown.platform_fee));
+        assert_eq!(net as u128 + breakdown.total_fee() as u128, sol_amount as u128);
+        assert!(breakdown.creator_fee <= breakdown.total_fee() - breakdown.liquidity_fee);
+    }
+
+    if let Ok(trade_fees) = platform_fee.trade_fees(sol_amount) {
+        assert!(trade_fees.total_fee() <= sol_amount);
+        assert_eq!(trade_fees.liquidity_fee, 0);
+    }
+}
+
+/// Buying `amount` base units and selling them straight back never returns
+/// more than was paid, before or after fees
+pub fn linear_round_trip(curve: &LinearCurve, fees: &FeeStructure, amount: u64) {
+    let Ok(buy) = curve.quote_buy(fees, amount) else { return };
+    let after = LinearCurve { tokens_sold: curve.tokens_sold + amount, ..*curve };
+    let value = after.sell_value(amount).unwrap();
+    assert!(value <= buy.cost, "{:?}: bought {} for {}, sold for {}", curve, amount, buy.cost, value);
+
+    if let Ok(sell) = after.quote_sell(fees, amount) {
+        assert!(sell.sol_out <= buy.total_cost);
+        assert_eq!(sell.price_after, buy.price_before);
+    }
+}
+
+/// Buying more costs more, and so does buying later on the curve
+pub fn linear_monotonic(curve: &LinearCurve, amount: u64, extra: u64) {
+    let Ok(cost) = curve.buy_cost(amount) else { return };
+    if let Ok(more) = curve.buy_cost(amount.saturating_add(extra)) {
+        assert!(more >= cost, "{:?}: {} costs {}, {} more costs {}", curve, amount, cost, extra, more);
+    }
+    let later = LinearCurve { tokens_sold: curve.tokens_sold.saturating_add(extra), ..*curve };
+    if let Ok(later_cost) = later.buy_cost(amount) {
+        assert!(later_cost >= cost, "{:?}: {} costs {}, {} later {}", curve, amount, cost, extra, later_cost);
+    }
+    if let (Ok(price), Ok(later_price)) = (curve.spot_price(), later.spot_price()) {
+        assert!(later_price >= price);
+    }
+}
+
+/// Buying `first` then `second` never costs less than buying both at once,
+/// and selling them in two parts never pays more than selling at once
+pub fn linear_split(curve: &LinearCurve, fees: &FeeStructure, first: u64, second: u64) {
+    let Some(total) = first.checked_add(second) else { return };
+    let Ok(at_once) = curve.quote_buy(fees, total) else { return };
+    let middle = LinearCurve { tokens_sold: curve.tokens_sold + first, ..*curve };
+    if let (Ok(a), Ok(b)) = (curve.quote_buy(fees, first), middle.quote_buy(fees, second)) {
+        assert!(a.cost + b.cost >= at_once.cost);
+        assert!(a.total_cost + b.total_cost >= at_once.total_cost, "{:?}: buy {} + {}", curve, first, second);
+    }
+
+    let top = LinearCurve { tokens_sold: curve.tokens_sold + total, ..*curve };
+    let Ok(at_once) = top.quote_sell(fees, total) else { return };
+    let middle = LinearCurve { tokens_sold: curve.tokens_sold + second, ..*curve };
+    if let (Ok(a), Ok(b)) = (top.quote_sell(fees, first), middle.quote_sell(fees, second)) {
+        assert!(a.proceeds + b.proceeds <= at_once.proceeds);
+        assert!(a.sol_out + b.sol_out <= at_once.sol_out, "{:?}: sell {} + {}", curve, first, second);
+    }
+}
+
+/// `curve` after a buy with `sol_amount`, or None if the buy fails
+fn constant_product_buy(curve: &ConstantProductCurve, fees: &PlatformFee, sol_amount: u64) -> Option<(ConstantProductCurve, u64)> {
+    let buy = curve.quote_buy(fees, sol_amount, u64::MAX).ok()?;
+    let after = ConstantProductCurve {
+        real_sol_reserves: curve.real_sol_reserves.checked_add(buy.cost)?,
+        real_token_reserves: curve.real_token_reserves + buy.token_amount,
+        ..*curve
+    };
+    Some((after, buy.token_amount))
+}
+
+/// The reserve product, which rounding keeps from ever decreasing
+fn product(curve: &ConstantProductCurve) -> Option<u128> {
+    let (sol_reserves, token_reserves) = curve.reserves().ok()?;
+    sol_reserves.checked_mul(token_reserves)
+}
+
+/// A buy sold straight back never returns more than was paid, and neither
+/// trade lowers the reserve product
+pub fn constant_product_round_trip(curve: &ConstantProductCurve, fees: &PlatformFee, sol_amount: u64) {
+    let Some((after, tokens)) = constant_product_buy(curve, fees, sol_amount) else { return };
+    let k = product(curve).unwrap();
+    assert!(product(&after).unwrap() >= k);
+    if tokens == 0 {
+        return;
+    }
+
+    let sell = after.quote_sell(fees, tokens, u64::MAX).unwrap();
+    assert!(sell.proceeds <= sol_amount - fees.trade_fees(sol_amount).unwrap().total_fee());
+    assert!(sell.sol_out <= sol_amount, "{:?}: paid {}, got back {}", curve, sol_amount, sell.sol_out);
+
+    let sold = ConstantProductCurve {
+        real_sol_reserves: after.real_sol_reserves - sell.proceeds,
+        real_token_reserves: after.real_token_reserves - tokens,
+        ..after
+    };
+    assert!(product(&sold).unwrap() >= k);
+}
+
+/// More SOL buys at least as many tokens, more tokens sell for at least as
+/// much SOL, and the price only rises as tokens leave the curve
+pub fn constant_product_monotonic(curve: &ConstantProductCurve, amount: u64, extra: u64) {
+    let more = amount.saturating_add(extra);
+    if let (Ok(tokens), Ok(more_tokens)) = (curve.tokens_out(amount), curve.tokens_out(more)) {
+        assert!(more_tokens >= tokens, "{:?}: {} buys {}, {} buys {}", curve, amount, tokens, more, more_tokens);
+    }
+    if let (Ok(sol), Ok(more_sol)) = (curve.sol_out(amount), curve.sol_out(more)) {
+        assert!(more_sol >= sol);
+    }
+
+    let Some((after, _)) = constant_product_buy(curve, &PlatformFee::with_creator_fee(0), amount) else { return };
+    if let (Ok(price), Ok(after_price)) = (curve.spot_price(), after.spot_price()) {
+        assert!(after_price >= price);
+    }
+}
+
+/// Two buys never get more tokens than one with the same SOL, and two
+/// sells never pay more than one of the same tokens
+pub fn constant_product_split(curve: &ConstantProductCurve, fees: &PlatformFee, first: u64, second: u64) {
+    let Some(total) = first.checked_add(second) else { return };
+    let Some((_, at_once)) = constant_product_buy(curve, fees, total) else { return };
+    if let Some((middle, a)) = constant_product_buy(curve, fees, first) {
+        if let Some((_, b)) = constant_product_buy(&middle, fees, second) {
+            assert!(a + b <= at_once, "{:?}: buy {} + {}: {} + {} vs {}", curve, first, second, a, b, at_once);
+        }
+    }
+
+    let Some(tokens) = first.checked_add(second).filter(|tokens| *tokens <= curve.real_token_reserves) else { return };
+    let Ok(at_once) = curve.quote_sell(fees, tokens, u64::MAX) else { return };
+    if let Ok(a) = curve.quote_sell(fees, first, u64::MAX) {
+        let middle = ConstantProductCurve {
+            real_sol_reserves: curve.real_sol_reserves - a.proceeds,
+            real_token_reserves: curve.real_token_reserves - first,
+            ..*curve
+        };
+        if let Ok(b) = middle.quote_sell(fees, second, u64::MAX) {
+            assert!(a.proceeds + b.proceeds <= at_once.proceeds);
+            assert!(a.sol_out + b.sol_out <= at_once.sol_out, "{:?}: sell {} + {}", curve, first, second);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Property tests of the curve and fee invariants over random curves and
+//! trades; `fuzz/` runs the same checks under libFuzzer.
+//!
+//! The native program prices through `BondingCurveState`, which is checked
+//! to agree with the shared curve for any state before the invariants run
+//! on the shared curve alone.
+
+mod invariants;
+
+use asterlaunch_curve::{ConstantProductCurve, FeeStructure, LinearCurve, PlatformFee, MAX_DECIMALS};
+use asterlaunch_program::state::BondingCurveState;
+use proptest::prelude::*;
+
+/// A u64 spread over every magnitude, not just the largest
+fn magnitude() -> impl Strategy<Value = u64> {
+    (any::<u64>(), 0..64u32).prop_map(|(value, shift)| value >> shift)
+}
+
+/// A linear curve `CreateToken` accepts, partly sold
+fn linear_curve() -> impl Strategy<Value = (LinearCurve, FeeStructure)> {
+    (magnitude(), magnitude(), 1..=u64::MAX >> 4, 0..=MAX_DECIMALS, any::<u64>(), 0..=50u16)
+        .prop_map(|(initial_price, price_increment, total_supply, decimals, sold, creator_fee_bps)| {
+            let curve = LinearCurve {
+                initial_price: initial_price >> 24,
+                price_increment,
+                total_supply,
+                tokens_sold: sold % (total_supply + 1),
+                decimals,
+            };
+            (curve, FeeStructure::with_creator_fee(creator_fee_bps).unwrap())
+        })
+        .prop_filter("CreateToken rejects curves it cannot price", |(curve, fees)| curve.validate(fees).is_ok())
+}
+
+/// An Anchor curve with non-zero virtual reserves, partly sold
+fn constant_product_curve() -> impl Strategy<Value = (ConstantProductCurve, PlatformFee)> {
+    (1..=u64::MAX >> 8, 1..=u64::MAX >> 4, any::<u64>(), magnitude(), 0..=100u16).prop_map(
+        |(virtual_sol_reserves, virtual_token_reserves, sold, real_sol_reserves, creator_fee_bps)| {
+            let curve = ConstantProductCurve {
+                virtual_sol_reserves,
+                virtual_token_reserves,
+                real_sol_reserves: real_sol_reserves >> 8,
+                real_token_reserves: sold % virtual_token_reserves,
+                total_supply: virtual_token_reserves,
+                decimals: 6,
+            };
+            (curve, PlatformFee::with_creator_fee(creator_fee_bps))
+        },
+    )
+}
+
+proptest! {
+    // Most random linear curves overflow before selling out, so allow the
+    // curve strategy to reject many of them
+    #![proptest_config(ProptestConfig { cases: 2000, max_local_rejects: 1 << 24, ..ProptestConfig::default() })]
+
+    #[test]
+    fn native_state_prices_like_the_shared_curve(
+        initial_price in any::<u64>(),
+        price_increment in any::<u64>(),
+        total_supply in any::<u64>(),
+        tokens_sold in any::<u64>(),
+        decimals in any::<u8>(),
+        amount in magnitude(),
+    ) {
+        let curve = LinearCurve { initial_price, price_increment, total_supply, tokens_sold, decimals };
+        let state = BondingCurveState::from_curve(&curve, 0);
+        prop_assert_eq!(state.calculate_buy_price(amount).ok(), curve.buy_cost(amount).ok());
+        prop_assert_eq!(state.calculate_sell_price(amount).ok(), curve.sell_value(amount).ok());
+    }
+
+    #[test]
+    fn fees_never_exceed_input(sol_amount in magnitude(), creator_fee_bps in 0..=50u16, anchor_creator_fee_bps in 0..=100u16) {
+        let fees = FeeStructure::with_creator_fee(creator_fee_bps).unwrap();
+        invariants::fees_within_input(&fees, &PlatformFee::with_creator_fee(anchor_creator_fee_bps), sol_amount);
+    }
+
+    #[test]
+    fn linear_buy_then_sell_never_profits((curve, fees) in linear_curve(), amount in magnitude()) {
+        invariants::linear_round_trip(&curve, &fees, amount % (curve.tokens_remaining() + 1));
+    }
+
+    #[test]
+    fn linear_cost_is_monotonic((curve, _) in linear_curve(), amount in magnitude(), extra in magnitude()) {
+        let remaining = curve.tokens_remaining();
+        invariants::linear_monotonic(&curve, amount % (remaining + 1), extra % (remaining + 1));
+    }
+
+    #[test]
+    fn linear_split_never_beats_one_trade((curve, fees) in linear_curve(), first in magnitude(), second in magnitude()) {
+        let remaining = curve.tokens_remaining();
+        let first = first % (remaining + 1);
+        invariants::linear_split(&curve, &fees, first, second % (remaining - first + 1));
+    }
+
+    #[test]
+    fn constant_product_buy_then_sell_never_profits((curve, fees) in constant_product_curve(), sol_amount in magnitude()) {
+        invariants::constant_product_round_trip(&curve, &fees, sol_amount);
+    }
+
+    #[test]
+    fn constant_product_is_monotonic((curve, _) in constant_product_curve(), amount in magnitude(), extra in magnitude()) {
+        invariants::constant_product_monotonic(&curve, amount, extra);
+    }
+
+    #[test]
+    fn constant_product_split_never_beats_one_trade(
+        (curve, fees) in constant_product_curve(),
+        first in magnitude(),
+        second in magnitude(),
+    ) {
+        invariants::constant_product_split(&curve, &fees, first, second);
+    }
+
+    #[test]
+    fn arbitrary_inputs_never_panic(
+        values in proptest::array::uniform8(any::<u64>()),
+        decimals in any::<u8>(),
+        bps in proptest::array::uniform2(any::<u16>()),
+    ) {
+        let [a, b, c, d, e, f, g, h] = values;
+        let linear = LinearCurve { initial_price: a, price_increment: b, total_supply: c, tokens_sold: d, decimals };
+        let fees = FeeStructure { total_fee_bps: bps[0], liquidity_fee_bps: bps[1], platform_fee_bps: bps[1], creator_fee_bps: bps[0] };
+        let _ = linear.validate(&fees);
+        let _ = linear.quote_buy(&fees, e);
+        let _ = linear.quote_sell(&fees, e);
+        let _ = linear.max_buy(&fees, f);
+        let _ = linear.price_at(g);
+        let _ = fees.calculate_fees(h);
+
+        let product = ConstantProductCurve {
+            virtual_sol_reserves: a,
+            virtual_token_reserves: b,
+            real_sol_reserves: c,
+            real_token_reserves: d,
+            total_supply: h,
+            decimals,
+        };
+        let platform_fee = PlatformFee { fee_bps: bps[0], creator_fee_bps: bps[1] };
+        let _ = product.quote_buy(&platform_fee, e, f);
+        let _ = product.quote_sell(&platform_fee, e, f);
+        let _ = product.market_cap();
+        let _ = product.spot_price();
+    }
+}
@@ -0,0 +1,558 @@
+//! Decoding transactions into the store
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use asterlaunch_program::{events::AsterLaunchEvent, instruction::AsterLaunchInstruction};
+use asterlaunch_trade_decoder::{
+    model::Side, DecodedTransaction, Decoder, ProgramKind, Programs, TransactionRecord,
+};
+use borsh::BorshDeserialize;
+use serde_json::json;
+use solana_sdk::{hash::hashv, pubkey::Pubkey};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+
+use crate::store::{feed_kind, HolderRow, NewToken, Store, TradeRow};
+
+/// Indexes transactions of the AsterLaunch programs
+pub struct Indexer {
+    store: Store,
+    decoder: Decoder,
+    programs: Programs,
+}
+
+impl Indexer {
+    pub fn new(store: Store, programs: Programs) -> Result<Self> {
+        let mut decoder = Decoder::new(programs);
+        for (mint, decimals) in store.token_decimals()? {
+            if let Ok(mint) = mint.parse() {
+                decoder.set_decimals(mint, decimals);
+            }
+        }
+
+        Ok(Self {
+            store,
+            decoder,
+            programs,
+        })
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    pub fn programs(&self) -> &Programs {
+        &self.programs
+    }
+
+    /// Index a transaction fetched with `getTransaction`
+    pub fn index_encoded(
+        &mut self,
+        tx: &EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<Option<DecodedTransaction>> {
+        self.index(&TransactionRecord::from_encoded(tx)?)
+    }
+
+    /// Index a transaction, returning what was decoded from it, or `None`
+    /// if it was already indexed
+    pub fn index(&mut self, tx: &TransactionRecord) -> Result<Option<DecodedTransaction>> {
+        if self.store.has_transaction(&tx.signature)? {
+            return Ok(None);
+        }
+
+        let decoded = self.decoder.decode(tx);
+        let batch = self.store.batch()?;
+        batch.insert_transaction(&tx.signature, tx.slot, tx.block_time, tx.failed)?;
+
+        // Descriptions are only in the native `CreateToken` instruction, which
+        // emits one `TokenCreated` event
+        let mut descriptions = native_instructions(self.programs.native, tx).filter_map(|instruction| {
+            match instruction {
+                AsterLaunchInstruction::CreateToken { description, .. } => Some(description),
+                _ => None,
+            }
+        });
+
+        // Likewise the Anchor `create_token` instruction holds the curve's
+        // virtual reserves
+        let mut virtual_reserves = Vec::new();
+        for instruction in anchor_instructions(self.programs.anchor, tx) {
+            match instruction {
+                AnchorInstruction::InitializePlatform { graduation_threshold } => {
+                    batch.set_graduation_threshold(
+                        program_name(ProgramKind::Anchor),
+                        graduation_threshold,
+                        tx.slot,
+                    )?;
+                }
+                AnchorInstruction::CreateToken(args) => virtual_reserves.push(args),
+            }
+        }
+        let mut virtual_reserves = virtual_reserves.into_iter();
+
+        let mut mints = HashSet::new();
+        let mut trades = decoded.trades.iter().zip(&decoded.snapshots);
+        for (index, event) in decoded.events.iter().enumerate() {
+            match &event.event {
+                AsterLaunchEvent::TokenCreated(created) => {
+                    let (description, reserves) = match event.program {
+                        ProgramKind::Native => (descriptions.next().unwrap_or_default(), None),
+                        ProgramKind::Anchor => (String::new(), virtual_reserves.next()),
+                    };
+                    let token = NewToken {
+                        mint: created.mint.to_string(),
+                        program: program_name(event.program).to_string(),
+                        creator: created.creator.to_string(),
+                        bonding_curve: created.bonding_curve.to_string(),
+                        name: created.name.clone(),
+                        symbol: created.symbol.clone(),
+                        uri: created.uri.clone(),
+                        description,
+                        total_supply: created.total_supply,
+                        decimals: created.decimals,
+                        created_at: created.timestamp.saturating_mul(1000),
+                        slot: tx.slot,
+                        signature: tx.signature.clone(),
+                        virtual_sol_reserves: reserves.as_ref().map_or(0, |args| args.initial_virtual_sol_reserves),
+                        virtual_token_reserves: reserves.as_ref().map_or(0, |args| args.initial_virtual_token_reserves),
+                    };
+                    batch.insert_token(&token)?;
+                    batch.push_feed(
+                        feed_kind::TOKEN_LAUNCHED,
+                        &token.mint,
+                        Some(&token.creator),
+                        tx.slot,
+                        &json!({
+                            "mint": token.mint,
+                            "program": token.program,
+                            "creator": token.creator,
+                            "bondingCurve": token.bonding_curve,
+                            "name": token.name,
+                            "ticker": token.symbol,
+                            "uri": token.uri,
+                            "description": token.description,
+                            "totalSupply": token.total_supply,
+                            "decimals": token.decimals,
+                            "createdAt": token.created_at,
+                        }),
+                    )?;
+                    mints.insert(created.mint);
+                }
+                AsterLaunchEvent::Trade(event) => {
+                    // The decoder converts every trade event, in order
+                    let Some((trade, snapshot)) = trades.next() else {
+                        continue;
+                    };
+                    batch.insert_trade(&TradeRow {
+                        id: trade.id.clone(),
+                        signature: tx.signature.clone(),
+                        event_index: index as u32,
+                        slot: tx.slot,
+                        timestamp: trade.timestamp,
+                        mint: trade.mint.clone(),
+                        wallet: trade.wallet.clone(),
+                        side: match trade.side {
+                            Side::Buy => "buy",
+                            Side::Sell => "sell",
+                        }
+                        .to_string(),
+                        token_amount: trade.token_amount,
+                        sol_amount: trade.sol_amount,
+                        decimals: trade.decimals,
+                        liquidity_fee: event.liquidity_fee,
+                        platform_fee: event.platform_fee,
+                        creator_fee: event.creator_fee,
+                        referral_fee: event.referral_fee,
+                        trade_seq: trade.trade_seq,
+                        price_lamports: snapshot.price_lamports,
+                        sol_reserves: snapshot.sol_reserves,
+                        token_reserves: snapshot.token_reserves,
+                    })?;
+                    batch.push_feed(
+                        feed_kind::TRADE,
+                        &trade.mint,
+                        Some(&trade.wallet),
+                        tx.slot,
+                        &serde_json::to_value(trade)?,
+                    )?;
+                    batch.push_feed(
+                        feed_kind::CURVE,
+                        &trade.mint,
+                        None,
+                        tx.slot,
+                        &json!({
+                            "mint": snapshot.mint,
+                            "slot": snapshot.slot,
+                            "timestamp": snapshot.timestamp,
+                            "price": snapshot.price,
+                            "priceLamports": snapshot.price_lamports,
+                            "solReserves": snapshot.sol_reserves,
+                            "tokenReserves": snapshot.token_reserves,
+                            "isGraduated": false,
+                        }),
+                    )?;
+                    mints.insert(event.mint);
+                }
+                AsterLaunchEvent::Graduated(graduated) => {
+                    let mint = graduated.mint.to_string();
+                    let timestamp = graduated.timestamp.saturating_mul(1000);
+                    batch.set_graduated(&mint, timestamp, &tx.signature)?;
+                    batch.push_feed(
+                        feed_kind::CURVE,
+                        &mint,
+                        None,
+                        tx.slot,
+                        &json!({
+                            "mint": mint,
+                            "slot": tx.slot,
+                            "timestamp": timestamp,
+                            "solLiquidity": graduated.sol_liquidity,
+                            "tokenLiquidity": graduated.token_liquidity,
+                            "isGraduated": true,
+                        }),
+                    )?;
+                    mints.insert(graduated.mint);
+                }
+            }
+        }
+
+        // Holders of the tokens the transaction traded
+        for balance in tx.token_balances.iter().filter(|balance| mints.contains(&balance.mint)) {
+            batch.upsert_holder(&HolderRow {
+                account: balance.account.to_string(),
+                mint: balance.mint.to_string(),
+                owner: balance.owner.map(|owner| owner.to_string()),
+                amount: balance.amount,
+                slot: tx.slot,
+            })?;
+        }
+
+        batch.commit()?;
+        Ok(Some(decoded))
+    }
+}
+
+/// Instruction data of a program in a successful transaction, top-level and CPI
+fn instruction_data(program: Option<Pubkey>, tx: &TransactionRecord) -> impl Iterator<Item = &[u8]> + '_ {
+    let program = program.filter(|_| !tx.failed);
+    let top_level = tx
+        .instructions
+        .iter()
+        .map(|instruction| (instruction.program_id, &instruction.data));
+    let inner = tx
+        .inner_instructions
+        .iter()
+        .map(|instruction| (instruction.program_id, &instruction.data));
+
+    top_level
+        .chain(inner)
+        .filter(move |(program_id, _)| Some(*program_id) == program)
+        .map(|(_, data)| data.as_slice())
+}
+
+/// Native program instructions of a successful transaction, top-level and CPI
+fn native_instructions(
+    native: Option<Pubkey>,
+    tx: &TransactionRecord,
+) -> impl Iterator<Item = AsterLaunchInstruction> + '_ {
+    instruction_data(native, tx).filter_map(|data| AsterLaunchInstruction::try_from_slice(data).ok())
+}
+
+/// Arguments of the Anchor `create_token` instruction
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize)]
+pub struct AnchorCreateToken {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_virtual_token_reserves: u64,
+}
+
+/// Anchor program instructions the indexer reads arguments from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnchorInstruction {
+    InitializePlatform { graduation_threshold: u64 },
+    CreateToken(AnchorCreateToken),
+}
+
+impl AnchorInstruction {
+    /// Decode instruction data: `sha256("global:<name>")[..8]` then borsh arguments
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        let (discriminator, mut args) = data.split_at(8);
+        if discriminator == anchor_discriminator("initialize_platform") {
+            let graduation_threshold = u64::deserialize(&mut args).ok()?;
+            Some(AnchorInstruction::InitializePlatform { graduation_threshold })
+        } else if discriminator == anchor_discriminator("create_token") {
+            AnchorCreateToken::deserialize(&mut args).ok().map(AnchorInstruction::CreateToken)
+        } else {
+            None
+        }
+    }
+}
+
+/// Anchor instruction discriminator
+fn anchor_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hashv(&[b"global:", name.as_bytes()]).to_bytes()[..8]);
+    discriminator
+}
+
+/// Anchor program instructions of a successful transaction, top-level and CPI
+fn anchor_instructions(
+    anchor: Option<Pubkey>,
+    tx: &TransactionRecord,
+) -> impl Iterator<Item = AnchorInstruction> + '_ {
+    instruction_data(anchor, tx).filter_map(AnchorInstruction::decode)
+}
+
+/// Stored name of a program kind
+pub fn program_name(program: ProgramKind) -> &'static str {
+    match program {
+        ProgramKind::Native => "native",
+        ProgramKind::Anchor => "anchor",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::TradeGap;
+    use asterlaunch_program::{
+        events::{Event, Graduated, TokenCreated, Trade, TradeSide, EVENT_IX_TAG_LE},
+        state::TradeStats,
+    };
+    use asterlaunch_trade_decoder::{InnerInstruction, Instruction, TokenBalance};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use borsh::BorshSerialize;
+
+    fn data_log(event: &impl Event) -> String {
+        format!("Program data: {}", STANDARD.encode(event.data()))
+    }
+
+    #[test]
+    fn test_index_native_transactions() {
+        let native = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let trader = Pubkey::new_unique();
+        let trader_account = Pubkey::new_unique();
+        let mut indexer = Indexer::new(
+            Store::open_in_memory().unwrap(),
+            Programs { native: Some(native), anchor: None },
+        )
+        .unwrap();
+
+        let create = AsterLaunchInstruction::CreateToken {
+            name: "Aster".to_string(),
+            ticker: "AST".to_string(),
+            description: "To the stars".to_string(),
+            metadata_uri: "https://example.com/ast.json".to_string(),
+            total_supply: 1_000_000,
+            decimals: 0,
+        };
+        let created = TokenCreated {
+            mint,
+            creator: trader,
+            bonding_curve: Pubkey::new_unique(),
+            name: "Aster".to_string(),
+            symbol: "AST".to_string(),
+            uri: "https://example.com/ast.json".to_string(),
+            total_supply: 1_000_000,
+            decimals: 0,
+            timestamp: 1_700_000_000,
+        };
+        let trade = Trade {
+            mint,
+            trader,
+            side: TradeSide::Buy,
+            token_amount: 100,
+            sol_amount: 150_000,
+            liquidity_fee: 1_000,
+            platform_fee: 400,
+            creator_fee: 100,
+            referral_fee: 0,
+            sol_reserves: 149_000,
+            token_reserves: 999_900,
+            price: 1_300,
+            timestamp: 1_700_000_010,
+            // The mint's first trade was never seen
+            stats: TradeStats {
+                trade_seq: 2,
+                buy_count: 2,
+                ..TradeStats::default()
+            },
+        };
+        let graduated = Graduated {
+            mint,
+            bonding_curve: created.bonding_curve,
+            sol_liquidity: 149_000,
+            token_liquidity: 999_900,
+            timestamp: 1_700_000_020,
+        };
+        let tx = TransactionRecord {
+            signature: "sig".to_string(),
+            slot: 10,
+            log_messages: vec![
+                format!("Program {} invoke [1]", native),
+                data_log(&created),
+                data_log(&trade),
+                data_log(&graduated),
+                format!("Program {} success", native),
+            ],
+            instructions: vec![Instruction {
+                program_id: native,
+                data: create.try_to_vec().unwrap(),
+            }],
+            token_balances: vec![
+                TokenBalance { account: trader_account, mint, owner: Some(trader), amount: 100 },
+                TokenBalance { account: Pubkey::new_unique(), mint: Pubkey::new_unique(), owner: None, amount: 5 },
+            ],
+            ..TransactionRecord::default()
+        };
+
+        let decoded = indexer.index(&tx).unwrap().unwrap();
+        assert_eq!(decoded.trades.len(), 1);
+        // Already indexed
+        assert!(indexer.index(&tx).unwrap().is_none());
+
+        let conn = indexer.store().connection();
+        let (description, price, graduated_at): (String, i64, Option<i64>) = conn
+            .query_row(
+                "SELECT description, price_lamports, graduated_at FROM tokens WHERE mint = ?1",
+                [mint.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(description, "To the stars");
+        assert_eq!(price, 1_300);
+        assert_eq!(graduated_at, Some(1_700_000_020_000));
+
+        let fees: i64 = conn
+            .query_row("SELECT liquidity_fee + platform_fee + creator_fee FROM trades", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(fees, 1_500);
+        assert_eq!(
+            indexer.store().trade_gaps().unwrap(),
+            vec![TradeGap { mint: mint.to_string(), last_seq: 2, missing: 1 }]
+        );
+
+        let feed: Vec<String> = conn
+            .prepare("SELECT kind FROM feed ORDER BY seq")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(feed, ["tokenLaunched", "trade", "curve", "curve"]);
+
+        // Only balances of AsterLaunch mints are kept
+        let holders: Vec<(String, i64)> = conn
+            .prepare("SELECT owner, amount FROM holders")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(holders, vec![(trader.to_string(), 100)]);
+
+        // An older balance does not overwrite a newer one
+        let stale = TransactionRecord {
+            signature: "stale".to_string(),
+            slot: 5,
+            log_messages: vec![
+                format!("Program {} invoke [1]", native),
+                data_log(&trade),
+                format!("Program {} success", native),
+            ],
+            token_balances: vec![TokenBalance { account: trader_account, mint, owner: Some(trader), amount: 1 }],
+            ..TransactionRecord::default()
+        };
+        indexer.index(&stale).unwrap();
+        let amount: i64 = indexer
+            .store()
+            .connection()
+            .query_row("SELECT amount FROM holders WHERE account = ?1", [trader_account.to_string()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(amount, 100);
+    }
+
+    #[test]
+    fn test_index_anchor_curve_parameters() {
+        let anchor = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut indexer = Indexer::new(
+            Store::open_in_memory().unwrap(),
+            Programs { native: None, anchor: Some(anchor) },
+        )
+        .unwrap();
+
+        let mut initialize = anchor_discriminator("initialize_platform").to_vec();
+        initialize.extend_from_slice(&400_000_000_000u64.to_le_bytes());
+        let mut create = anchor_discriminator("create_token").to_vec();
+        create.extend_from_slice(
+            &("Aster", "AST", "https://example.com/ast.json", 30_000_000_000u64, 1_073_000_000_000_000u64)
+                .try_to_vec()
+                .unwrap(),
+        );
+        let created = TokenCreated {
+            mint,
+            creator: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            name: "Aster".to_string(),
+            symbol: "AST".to_string(),
+            uri: "https://example.com/ast.json".to_string(),
+            total_supply: 1_000_000_000_000_000,
+            decimals: 6,
+            timestamp: 1_700_000_000,
+        };
+        let mut event = EVENT_IX_TAG_LE.to_vec();
+        event.extend_from_slice(&created.data());
+
+        let tx = TransactionRecord {
+            signature: "sig".to_string(),
+            slot: 10,
+            instructions: vec![
+                Instruction { program_id: anchor, data: initialize },
+                Instruction { program_id: anchor, data: create },
+            ],
+            inner_instructions: vec![InnerInstruction { program_id: anchor, data: event, stack_height: Some(2) }],
+            ..TransactionRecord::default()
+        };
+        indexer.index(&tx).unwrap();
+
+        let conn = indexer.store().connection();
+        let threshold: i64 = conn
+            .query_row("SELECT graduation_threshold FROM platforms WHERE program = 'anchor'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(threshold, 400_000_000_000);
+        let reserves: (i64, i64) = conn
+            .query_row(
+                "SELECT virtual_sol_reserves, virtual_token_reserves FROM tokens WHERE mint = ?1",
+                [mint.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(reserves, (30_000_000_000, 1_073_000_000_000_000));
+    }
+
+    #[test]
+    fn test_failed_transactions_are_recorded_without_effects() {
+        let native = Pubkey::new_unique();
+        let mut indexer = Indexer::new(
+            Store::open_in_memory().unwrap(),
+            Programs { native: Some(native), anchor: None },
+        )
+        .unwrap();
+
+        let tx = TransactionRecord {
+            signature: "failed".to_string(),
+            failed: true,
+            ..TransactionRecord::default()
+        };
+        assert!(indexer.index(&tx).unwrap().unwrap().trades.is_empty());
+        assert!(indexer.store().has_transaction("failed").unwrap());
+        assert_eq!(indexer.store().trade_count().unwrap(), 0);
+    }
+}
@@ -0,0 +1,19 @@
+/*
+ * AsterLaunch Indexer
+ * 
+ * Stores what the AsterLaunch programs do in SQLite:
+ * - Tokens with their latest curve state and graduation
+ * - Trades with fees, and the curve snapshot after each
+ * - Holder balances of AsterLaunch tokens
+ * 
+ * Transactions come from a validator (log subscriptions, opened before the
+ * backfill so nothing lands between the two) or from a dump of
+ * `getTransaction` results.
+ */
+
+pub mod indexer;
+pub mod source;
+pub mod store;
+
+pub use indexer::Indexer;
+pub use store::Store;
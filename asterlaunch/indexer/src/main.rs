@@ -0,0 +1,117 @@
+//! AsterLaunch indexer service
+//!
+//! ```text
+//! asterlaunch-indexer --db asterlaunch.db --native-program <ID> follow --rpc-url http://127.0.0.1:8899 --dump ledger.jsonl
+//! asterlaunch-indexer --db asterlaunch.db --native-program <ID> replay ledger.jsonl
+//! ```
+
+use std::{path::PathBuf, process::ExitCode};
+
+use anyhow::{bail, Result};
+use asterlaunch_indexer::{source::{self, RpcSource}, Indexer, Store};
+use asterlaunch_trade_decoder::Programs;
+use clap::{Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Parser, Debug)]
+#[command(about = "Index AsterLaunch tokens, trades and holders into SQLite")]
+struct Args {
+    /// SQLite database, created if missing
+    #[arg(long, default_value = "asterlaunch.db")]
+    db: PathBuf,
+
+    /// Native program ID
+    #[arg(long)]
+    native_program: Option<Pubkey>,
+
+    /// Anchor program ID
+    #[arg(long)]
+    anchor_program: Option<Pubkey>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Backfill from a validator, then index new transactions as they land
+    Follow {
+        /// RPC node, e.g. a local validator
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        rpc_url: String,
+
+        /// Websocket endpoint (default: derived from the RPC URL)
+        #[arg(long)]
+        ws_url: Option<String>,
+
+        /// Append every fetched transaction to this dump file
+        #[arg(long)]
+        dump: Option<PathBuf>,
+
+        /// Only index new transactions
+        #[arg(long)]
+        no_backfill: bool,
+    },
+
+    /// Index transaction dumps
+    Replay {
+        /// JSON Lines files of `getTransaction` results
+        #[arg(required = true)]
+        dumps: Vec<PathBuf>,
+    },
+}
+
+fn run(args: Args) -> Result<()> {
+    let programs = Programs {
+        native: args.native_program,
+        anchor: args.anchor_program,
+    };
+    if programs.ids().is_empty() {
+        bail!("pass --native-program and/or --anchor-program");
+    }
+
+    let mut indexer = Indexer::new(Store::open(&args.db)?, programs)?;
+    match args.command {
+        Command::Follow { rpc_url, ws_url, dump, no_backfill } => {
+            let mut source = RpcSource::new(&rpc_url, ws_url.as_deref(), dump.as_deref())?;
+            // Transactions that land during the backfill wait in the subscription
+            let notifications = source.subscribe(&programs)?;
+            if !no_backfill {
+                for program in programs.ids() {
+                    let indexed = source.backfill(&mut indexer, &program)?;
+                    eprintln!("backfilled {} transactions of {}", indexed, program);
+                }
+                report_gaps(&indexer)?;
+            }
+            source.follow(&mut indexer, notifications, |signature| eprintln!("indexed {}", signature))
+        }
+        Command::Replay { dumps } => {
+            for dump in dumps {
+                let indexed = source::replay(&mut indexer, &dump)?;
+                eprintln!("indexed {} transactions from {}", indexed, dump.display());
+            }
+            report_gaps(&indexer)
+        }
+    }
+}
+
+/// Warn about mints whose trade numbers skip trades
+fn report_gaps(indexer: &Indexer) -> Result<()> {
+    for gap in indexer.store().trade_gaps()? {
+        eprintln!(
+            "warning: {} is missing {} of its {} trades",
+            gap.mint, gap.missing, gap.last_seq
+        );
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Transaction sources
+//!
+//! A dump is a JSON Lines file with one `getTransaction` result per line,
+//! which is what `follow --dump` writes, so any run can be replayed later.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    str::FromStr,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use asterlaunch_trade_decoder::Programs;
+use solana_pubsub_client::pubsub_client::PubsubClient;
+use solana_rpc_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_rpc_client_api::config::{
+    RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+
+use crate::indexer::Indexer;
+
+/// Maximum signatures per `getSignaturesForAddress` request
+const SIGNATURES_PAGE: usize = 1000;
+
+/// Attempts to fetch a transaction a notification announced
+const FETCH_ATTEMPTS: usize = 10;
+
+/// Read transactions from a dump
+pub fn read_dump(path: &Path) -> Result<impl Iterator<Item = Result<EncodedConfirmedTransactionWithStatusMeta>>> {
+    let file = File::open(path).with_context(|| format!("cannot open dump {}", path.display()))?;
+    let name = path.display().to_string();
+
+    Ok(BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(move |(number, line)| {
+            let line = line.with_context(|| format!("cannot read {}", name))?;
+            serde_json::from_str(&line).with_context(|| format!("{}:{}: invalid transaction", name, number + 1))
+        }))
+}
+
+/// Index every transaction of a dump, returning how many were new
+pub fn replay(indexer: &mut Indexer, path: &Path) -> Result<usize> {
+    let mut indexed = 0;
+    for tx in read_dump(path)? {
+        if indexer.index_encoded(&tx?)?.is_some() {
+            indexed += 1;
+        }
+    }
+    Ok(indexed)
+}
+
+/// Signatures announced by log subscriptions, queued until they are indexed
+pub struct Notifications(mpsc::Receiver<String>);
+
+/// Follows a validator over RPC
+pub struct RpcSource {
+    rpc: RpcClient,
+    ws_url: String,
+    dump: Option<File>,
+}
+
+impl RpcSource {
+    pub fn new(rpc_url: &str, ws_url: Option<&str>, dump: Option<&Path>) -> Result<Self> {
+        let dump = dump
+            .map(|path| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("cannot open dump {}", path.display()))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed()),
+            ws_url: ws_url.map_or_else(|| websocket_url(rpc_url), str::to_string),
+            dump,
+        })
+    }
+
+    /// Index a program's transactions newer than the newest one already indexed
+    pub fn backfill(&mut self, indexer: &mut Indexer, program: &Pubkey) -> Result<usize> {
+        let mut pending = Vec::new();
+        let mut before = None;
+        'pages: loop {
+            let page = self.rpc.get_signatures_for_address_with_config(
+                program,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(SIGNATURES_PAGE),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )?;
+            for status in &page {
+                if indexer.store().has_transaction(&status.signature)? {
+                    break 'pages;
+                }
+                pending.push(Signature::from_str(&status.signature)?);
+            }
+            match page.last() {
+                Some(last) if page.len() == SIGNATURES_PAGE => before = Some(Signature::from_str(&last.signature)?),
+                _ => break,
+            }
+        }
+
+        // Signatures come newest first
+        let mut indexed = 0;
+        for signature in pending.iter().rev() {
+            let tx = self.fetch(signature)?;
+            if indexer.index_encoded(&tx)?.is_some() {
+                indexed += 1;
+            }
+        }
+        Ok(indexed)
+    }
+
+    /// Subscribe to the programs' logs
+    ///
+    /// Notifications queue up until [`RpcSource::follow`] reads them, so
+    /// subscribing before a backfill leaves no gap between the two
+    pub fn subscribe(&self, programs: &Programs) -> Result<Notifications> {
+        let (sender, receiver) = mpsc::channel();
+        for program in programs.ids() {
+            let (subscription, notifications) = PubsubClient::logs_subscribe(
+                &self.ws_url,
+                RpcTransactionLogsFilter::Mentions(vec![program.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .with_context(|| format!("cannot subscribe to {}", self.ws_url))?;
+
+            let sender = sender.clone();
+            thread::spawn(move || {
+                // Keep the subscription alive for as long as it delivers
+                let _subscription = subscription;
+                for notification in notifications {
+                    if sender.send(notification.value.signature).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(Notifications(receiver))
+    }
+
+    /// Index transactions as notifications announce them, until the subscriptions end
+    pub fn follow(
+        &mut self,
+        indexer: &mut Indexer,
+        notifications: Notifications,
+        mut on_indexed: impl FnMut(&str),
+    ) -> Result<()> {
+        for signature in notifications.0 {
+            if indexer.store().has_transaction(&signature)? {
+                continue;
+            }
+            let tx = self.fetch(&Signature::from_str(&signature)?)?;
+            if indexer.index_encoded(&tx)?.is_some() {
+                on_indexed(&signature);
+            }
+        }
+
+        bail!("log subscription to {} closed", self.ws_url)
+    }
+
+    /// Fetch a confirmed transaction, appending it to the dump
+    fn fetch(&mut self, signature: &Signature) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+
+        // A log notification can arrive before the transaction is queryable
+        let mut attempt = 0;
+        let tx = loop {
+            match self.rpc.get_transaction_with_config(signature, config) {
+                Ok(tx) => break tx,
+                Err(_) if attempt + 1 < FETCH_ATTEMPTS => {
+                    attempt += 1;
+                    thread::sleep(Duration::from_millis(200 * attempt as u64));
+                }
+                Err(err) => return Err(err).with_context(|| format!("cannot fetch transaction {}", signature)),
+            }
+        };
+
+        if let Some(dump) = &mut self.dump {
+            serde_json::to_writer(&mut *dump, &tx)?;
+            dump.write_all(b"\n")?;
+        }
+        Ok(tx)
+    }
+}
+
+/// Websocket URL of an RPC node, following the validator's port convention
+pub fn websocket_url(rpc_url: &str) -> String {
+    let url = rpc_url
+        .replacen("https://", "wss://", 1)
+        .replacen("http://", "ws://", 1);
+
+    // A local validator serves websockets on the RPC port plus one
+    let local = url.rsplit_once(':').and_then(|(host, port)| {
+        let port = port.parse::<u16>().ok()?.checked_add(1)?;
+        Some(format!("{}:{}", host, port))
+    });
+    local.unwrap_or(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(websocket_url("http://127.0.0.1:8899"), "ws://127.0.0.1:8900");
+        assert_eq!(websocket_url("https://api.devnet.solana.com"), "wss://api.devnet.solana.com");
+    }
+}
@@ -0,0 +1,506 @@
+//! SQLite storage
+//!
+//! Every write is idempotent, so replaying a transaction (or a whole dump)
+//! changes nothing. Amounts are stored as SQLite integers in base units and
+//! lamports; timestamps are milliseconds since the epoch.
+//!
+//! The `feed` table is an append-only log of what each newly indexed
+//! transaction changed; its `seq` is the sequence number streaming clients
+//! resume from.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    failed INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tokens (
+    mint TEXT PRIMARY KEY,
+    program TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    bonding_curve TEXT NOT NULL,
+    name TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    total_supply INTEGER NOT NULL,
+    decimals INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    created_slot INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    price_lamports INTEGER NOT NULL DEFAULT 0,
+    sol_reserves INTEGER NOT NULL DEFAULT 0,
+    token_reserves INTEGER NOT NULL,
+    virtual_sol_reserves INTEGER NOT NULL DEFAULT 0,
+    virtual_token_reserves INTEGER NOT NULL DEFAULT 0,
+    state_slot INTEGER NOT NULL,
+    state_seq INTEGER NOT NULL DEFAULT 0,
+    graduated_at INTEGER,
+    graduation_signature TEXT
+);
+
+CREATE TABLE IF NOT EXISTS platforms (
+    program TEXT PRIMARY KEY,
+    graduation_threshold INTEGER NOT NULL,
+    slot INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS trades (
+    id TEXT PRIMARY KEY,
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    mint TEXT NOT NULL,
+    wallet TEXT NOT NULL,
+    side TEXT NOT NULL,
+    token_amount INTEGER NOT NULL,
+    sol_amount INTEGER NOT NULL,
+    decimals INTEGER NOT NULL,
+    liquidity_fee INTEGER NOT NULL,
+    platform_fee INTEGER NOT NULL,
+    creator_fee INTEGER NOT NULL,
+    referral_fee INTEGER NOT NULL,
+    trade_seq INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS trades_mint ON trades (mint, slot);
+CREATE INDEX IF NOT EXISTS trades_wallet ON trades (wallet, slot);
+
+CREATE TABLE IF NOT EXISTS snapshots (
+    trade_id TEXT PRIMARY KEY REFERENCES trades (id),
+    mint TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    price_lamports INTEGER NOT NULL,
+    sol_reserves INTEGER NOT NULL,
+    token_reserves INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS snapshots_mint ON snapshots (mint, slot);
+
+CREATE TABLE IF NOT EXISTS holders (
+    account TEXT PRIMARY KEY,
+    mint TEXT NOT NULL,
+    owner TEXT,
+    amount INTEGER NOT NULL,
+    slot INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS holders_mint ON holders (mint);
+CREATE INDEX IF NOT EXISTS holders_owner ON holders (owner);
+
+CREATE TABLE IF NOT EXISTS feed (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    wallet TEXT,
+    slot INTEGER NOT NULL,
+    payload TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS feed_mint ON feed (mint, seq);
+CREATE INDEX IF NOT EXISTS feed_wallet ON feed (wallet, seq);
+";
+
+/// Kinds of feed entries
+pub mod feed_kind {
+    /// A token was launched; the payload is the token
+    pub const TOKEN_LAUNCHED: &str = "tokenLaunched";
+
+    /// A trade; the payload is the frontend `Trade`
+    pub const TRADE: &str = "trade";
+
+    /// Curve state after a trade or graduation
+    pub const CURVE: &str = "curve";
+}
+
+/// A newly created token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewToken {
+    pub mint: String,
+    pub program: String,
+    pub creator: String,
+    pub bonding_curve: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub description: String,
+    pub total_supply: u64,
+    pub decimals: u8,
+    pub created_at: i64,
+    pub slot: u64,
+    pub signature: String,
+
+    /// Virtual reserves of an Anchor curve, 0 for native tokens
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+}
+
+/// A trade with its fees and the curve state after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeRow {
+    pub id: String,
+    pub signature: String,
+    pub event_index: u32,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub mint: String,
+    pub wallet: String,
+    pub side: String,
+    pub token_amount: u64,
+    pub sol_amount: u64,
+    pub decimals: u8,
+    pub liquidity_fee: u64,
+    pub platform_fee: u64,
+    pub creator_fee: u64,
+    pub referral_fee: u64,
+
+    /// The mint's trade number from the program, 0 if not reported
+    pub trade_seq: u64,
+    pub price_lamports: u64,
+    pub sol_reserves: u64,
+    pub token_reserves: u64,
+}
+
+/// Trades of a mint the store is missing, going by the program's trade numbers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeGap {
+    pub mint: String,
+
+    /// Highest trade number seen
+    pub last_seq: u64,
+
+    /// Trade numbers up to `last_seq` that were never indexed
+    pub missing: u64,
+}
+
+/// Balance of a token account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HolderRow {
+    pub account: String,
+    pub mint: String,
+    pub owner: Option<String>,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+/// The indexer database
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Open or create a database file
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("cannot open database {}", path.display()))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::init(conn)
+    }
+
+    /// In-memory database, for tests and dry runs
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA).context("cannot create schema")?;
+        Ok(Self { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Whether a transaction has already been indexed
+    pub fn has_transaction(&self, signature: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT 1 FROM transactions WHERE signature = ?1",
+                [signature],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+    }
+
+    /// Decimals of every indexed token
+    pub fn token_decimals(&self) -> Result<Vec<(String, u8)>> {
+        let mut statement = self.conn.prepare("SELECT mint, decimals FROM tokens")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Number of indexed trades
+    pub fn trade_count(&self) -> Result<u64> {
+        Ok(self
+            .conn
+            .query_row("SELECT COUNT(*) FROM trades", [], |row| row.get::<_, i64>(0))? as u64)
+    }
+
+    /// Mints with missed trades
+    pub fn trade_gaps(&self) -> Result<Vec<TradeGap>> {
+        let mut statement = self.conn.prepare(
+            "SELECT mint, MAX(trade_seq), MAX(trade_seq) - COUNT(DISTINCT trade_seq) FROM trades
+             WHERE trade_seq > 0 GROUP BY mint HAVING MAX(trade_seq) > COUNT(DISTINCT trade_seq)
+             ORDER BY mint",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok(TradeGap {
+                mint: row.get(0)?,
+                last_seq: row.get::<_, i64>(1)? as u64,
+                missing: row.get::<_, i64>(2)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Start a write batch; nothing is stored until [`Batch::commit`]
+    pub fn batch(&mut self) -> Result<Batch<'_>> {
+        Ok(Batch {
+            tx: self.conn.transaction()?,
+        })
+    }
+}
+
+/// Writes of one indexed transaction, applied atomically
+pub struct Batch<'a> {
+    tx: Transaction<'a>,
+}
+
+impl Batch<'_> {
+    pub fn insert_transaction(&self, signature: &str, slot: u64, block_time: Option<i64>, failed: bool) -> Result<()> {
+        self.tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time, failed) VALUES (?1, ?2, ?3, ?4)",
+            params![signature, slot as i64, block_time, failed],
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_token(&self, token: &NewToken) -> Result<()> {
+        self.tx.execute(
+            "INSERT OR IGNORE INTO tokens (
+                mint, program, creator, bonding_curve, name, symbol, uri, description, total_supply,
+                decimals, created_at, created_slot, signature, token_reserves, state_slot,
+                virtual_sol_reserves, virtual_token_reserves
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?9, ?12, ?14, ?15)",
+            params![
+                token.mint,
+                token.program,
+                token.creator,
+                token.bonding_curve,
+                token.name,
+                token.symbol,
+                token.uri,
+                token.description,
+                token.total_supply as i64,
+                token.decimals,
+                token.created_at,
+                token.slot as i64,
+                token.signature,
+                token.virtual_sol_reserves as i64,
+                token.virtual_token_reserves as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Store a trade and move the token's curve state forward to it
+    pub fn insert_trade(&self, trade: &TradeRow) -> Result<()> {
+        self.tx.execute(
+            "INSERT OR IGNORE INTO trades (
+                id, signature, event_index, slot, timestamp, mint, wallet, side, token_amount,
+                sol_amount, decimals, liquidity_fee, platform_fee, creator_fee, referral_fee, trade_seq
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                trade.id,
+                trade.signature,
+                trade.event_index,
+                trade.slot as i64,
+                trade.timestamp,
+                trade.mint,
+                trade.wallet,
+                trade.side,
+                trade.token_amount as i64,
+                trade.sol_amount as i64,
+                trade.decimals,
+                trade.liquidity_fee as i64,
+                trade.platform_fee as i64,
+                trade.creator_fee as i64,
+                trade.referral_fee as i64,
+                trade.trade_seq as i64,
+            ],
+        )?;
+        self.tx.execute(
+            "INSERT OR IGNORE INTO snapshots (
+                trade_id, mint, slot, timestamp, price_lamports, sol_reserves, token_reserves
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                trade.id,
+                trade.mint,
+                trade.slot as i64,
+                trade.timestamp,
+                trade.price_lamports as i64,
+                trade.sol_reserves as i64,
+                trade.token_reserves as i64,
+            ],
+        )?;
+
+        // Trade numbers order trades within a slot too; trades without one
+        // fall back to the slot, and are applied in the order they are indexed
+        self.tx.execute(
+            "UPDATE tokens SET
+                 price_lamports = ?2, sol_reserves = ?3, token_reserves = ?4, state_slot = ?5, state_seq = ?6
+             WHERE mint = ?1 AND CASE
+                 WHEN ?6 > 0 AND state_seq > 0 THEN state_seq < ?6
+                 ELSE state_slot <= ?5
+             END",
+            params![
+                trade.mint,
+                trade.price_lamports as i64,
+                trade.sol_reserves as i64,
+                trade.token_reserves as i64,
+                trade.slot as i64,
+                trade.trade_seq as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record a program's graduation threshold; a platform is initialized once
+    pub fn set_graduation_threshold(&self, program: &str, graduation_threshold: u64, slot: u64) -> Result<()> {
+        self.tx.execute(
+            "INSERT OR IGNORE INTO platforms (program, graduation_threshold, slot) VALUES (?1, ?2, ?3)",
+            params![program, graduation_threshold as i64, slot as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_graduated(&self, mint: &str, timestamp: i64, signature: &str) -> Result<()> {
+        self.tx.execute(
+            "UPDATE tokens SET graduated_at = ?2, graduation_signature = ?3
+             WHERE mint = ?1 AND graduated_at IS NULL",
+            params![mint, timestamp, signature],
+        )?;
+        Ok(())
+    }
+
+    /// Record a token account balance unless a later one is already known
+    pub fn upsert_holder(&self, holder: &HolderRow) -> Result<()> {
+        self.tx.execute(
+            "INSERT INTO holders (account, mint, owner, amount, slot) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (account) DO UPDATE SET
+                 mint = excluded.mint, owner = excluded.owner, amount = excluded.amount, slot = excluded.slot
+             WHERE excluded.slot >= holders.slot",
+            params![
+                holder.account,
+                holder.mint,
+                holder.owner,
+                holder.amount as i64,
+                holder.slot as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Append an entry to the feed
+    pub fn push_feed(
+        &self,
+        kind: &str,
+        mint: &str,
+        wallet: Option<&str>,
+        slot: u64,
+        payload: &serde_json::Value,
+    ) -> Result<()> {
+        self.tx.execute(
+            "INSERT INTO feed (kind, mint, wallet, slot, payload) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![kind, mint, wallet, slot as i64, payload.to_string()],
+        )?;
+        Ok(())
+    }
+
+    pub fn commit(self) -> Result<()> {
+        self.tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(seq: u64, slot: u64, price_lamports: u64) -> TradeRow {
+        TradeRow {
+            id: format!("sig{}:0", seq),
+            signature: format!("sig{}", seq),
+            event_index: 0,
+            slot,
+            timestamp: 0,
+            mint: "mint".to_string(),
+            wallet: "wallet".to_string(),
+            side: "buy".to_string(),
+            token_amount: 1,
+            sol_amount: 1,
+            decimals: 0,
+            liquidity_fee: 0,
+            platform_fee: 0,
+            creator_fee: 0,
+            referral_fee: 0,
+            trade_seq: seq,
+            price_lamports,
+            sol_reserves: 0,
+            token_reserves: 0,
+        }
+    }
+
+    fn state(store: &Store) -> (i64, i64) {
+        store
+            .connection()
+            .query_row("SELECT price_lamports, state_seq FROM tokens", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_older_trade_in_a_slot_does_not_overwrite_state() {
+        let mut store = Store::open_in_memory().unwrap();
+        let batch = store.batch().unwrap();
+        batch
+            .insert_token(&NewToken {
+                mint: "mint".to_string(),
+                program: "native".to_string(),
+                creator: "creator".to_string(),
+                bonding_curve: "curve".to_string(),
+                name: "Aster".to_string(),
+                symbol: "AST".to_string(),
+                uri: String::new(),
+                description: String::new(),
+                total_supply: 1_000,
+                decimals: 0,
+                created_at: 0,
+                slot: 1,
+                signature: "create".to_string(),
+                virtual_sol_reserves: 0,
+                virtual_token_reserves: 0,
+            })
+            .unwrap();
+        batch.commit().unwrap();
+
+        // Both trades land in slot 10, the second is indexed first
+        for trade in [trade(2, 10, 300), trade(1, 10, 200)] {
+            let batch = store.batch().unwrap();
+            batch.insert_trade(&trade).unwrap();
+            batch.commit().unwrap();
+        }
+        assert_eq!(state(&store), (300, 2));
+
+        let batch = store.batch().unwrap();
+        batch.insert_trade(&trade(3, 10, 400)).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(state(&store), (400, 3));
+    }
+}
@@ -0,0 +1,82 @@
+//! Layout of the Anchor program's `BondingCurve` account
+//!
+//! Mirrored here because the Anchor program is built separately from the
+//! native crate; keep in sync with `astra-wallet/contracts/programs/asterlaunch`.
+//! Pricing comes from `asterlaunch-curve`, which the program uses too.
+
+use asterlaunch_curve::ConstantProductCurve;
+use borsh::BorshDeserialize;
+use solana_program::{hash::hash, pubkey::Pubkey};
+
+/// Associated token account program
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Decimals of the Anchor program's mints
+pub const TOKEN_DECIMALS: u8 = 6;
+
+/// Anchor `BondingCurve` account data (after the 8-byte discriminator)
+#[derive(BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AnchorBondingCurve {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub total_supply: u64,
+    pub created_at: i64,
+    pub graduated: bool,
+    pub bump: u8,
+    pub paused: bool,
+    pub creator_fee_bps: u16,
+    pub creator_fees_accrued: u64,
+}
+
+impl AnchorBondingCurve {
+    /// Anchor account discriminator: `sha256("account:BondingCurve")[..8]`
+    pub fn discriminator() -> [u8; 8] {
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash(b"account:BondingCurve").to_bytes()[..8]);
+        discriminator
+    }
+
+    /// Decode account data, or `None` if it is not a `BondingCurve`
+    pub fn try_from_account_data(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return None;
+        }
+
+        // Accounts are allocated at their maximum size, so trailing bytes are expected
+        Self::deserialize(&mut &data[8..]).ok()
+    }
+
+    /// Curve's associated token account
+    pub fn token_account(&self, curve: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[curve.as_ref(), spl_token::id().as_ref(), self.mint.as_ref()],
+            &ASSOCIATED_TOKEN_PROGRAM_ID,
+        )
+        .0
+    }
+
+    /// Pricing state of the curve
+    pub fn curve(&self) -> ConstantProductCurve {
+        ConstantProductCurve {
+            virtual_sol_reserves: self.virtual_sol_reserves,
+            virtual_token_reserves: self.virtual_token_reserves,
+            real_sol_reserves: self.real_sol_reserves,
+            real_token_reserves: self.real_token_reserves,
+            total_supply: self.total_supply,
+            decimals: TOKEN_DECIMALS,
+        }
+    }
+
+    /// SOL returned for `token_amount` before fees, as the program prices it
+    pub fn sol_out(&self, token_amount: u64) -> Option<u64> {
+        self.curve().sol_out(token_amount).ok()
+    }
+}
@@ -0,0 +1,413 @@
+//! Reserve checks for native and Anchor bonding curves
+//!
+//! Every curve's expected reserves are recomputed from the curve math and its
+//! trade counters, then compared with the balances actually held on chain.
+
+use asterlaunch_program::state::BondingCurveState;
+use borsh::BorshDeserialize;
+use solana_program::{program_pack::Pack, pubkey::Pubkey, rent::Rent};
+use solana_sdk::account::Account;
+
+use crate::{
+    anchor_curve::AnchorBondingCurve,
+    report::{CurveReport, Discrepancy, ProgramKind, Report},
+    snapshot::Snapshot,
+};
+
+/// Deployed programs whose curves are audited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Programs {
+    /// Native `asterlaunch-program` deployment
+    pub native: Option<Pubkey>,
+
+    /// Anchor `asterlaunch` deployment
+    pub anchor: Option<Pubkey>,
+}
+
+impl Programs {
+    /// IDs of the configured programs
+    pub fn ids(&self) -> Vec<Pubkey> {
+        self.native.into_iter().chain(self.anchor).collect()
+    }
+}
+
+/// A bonding curve account found in a snapshot
+enum Curve {
+    Native(BondingCurveState),
+    Anchor(AnchorBondingCurve),
+    Undecodable(ProgramKind),
+}
+
+/// Find every bonding curve owned by the audited programs
+fn find_curves(snapshot: &Snapshot, programs: &Programs) -> Vec<(Pubkey, Curve)> {
+    let mut curves = Vec::new();
+
+    if let Some(program_id) = programs.native {
+        for (address, account) in snapshot.owned_by(&program_id) {
+            if account.data.len() != BondingCurveState::LEN {
+                continue;
+            }
+            // Other account types may share the length; the PDA tells them apart
+            match BondingCurveState::deserialize(&mut &account.data[..]) {
+                Ok(curve) if BondingCurveState::find_address(&program_id, &curve.token_mint).0 == *address => {
+                    curves.push((*address, Curve::Native(curve)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(program_id) = programs.anchor {
+        let discriminator = AnchorBondingCurve::discriminator();
+        for (address, account) in snapshot.owned_by(&program_id) {
+            if !account.data.starts_with(&discriminator) {
+                continue;
+            }
+            let curve = match AnchorBondingCurve::try_from_account_data(&account.data) {
+                Some(curve) => Curve::Anchor(curve),
+                None => Curve::Undecodable(ProgramKind::Anchor),
+            };
+            curves.push((*address, curve));
+        }
+    }
+
+    curves
+}
+
+/// Reserve accounts referenced by the curves in a snapshot
+///
+/// Token reserves are owned by the token program, so fetching a program's
+/// accounts alone does not capture them
+pub fn referenced_accounts(snapshot: &Snapshot, programs: &Programs) -> Vec<Pubkey> {
+    find_curves(snapshot, programs)
+        .into_iter()
+        .flat_map(|(address, curve)| match curve {
+            Curve::Native(curve) => vec![curve.sol_reserve, curve.token_reserve],
+            Curve::Anchor(curve) => vec![curve.token_account(&address)],
+            Curve::Undecodable(_) => vec![],
+        })
+        .collect()
+}
+
+/// Audit every bonding curve in a snapshot
+pub fn audit(snapshot: &Snapshot, programs: &Programs, rent: &Rent) -> Report {
+    let mut report = Report {
+        native_program: programs.native.map(|id| id.to_string()),
+        anchor_program: programs.anchor.map(|id| id.to_string()),
+        ..Report::default()
+    };
+
+    for (address, curve) in find_curves(snapshot, programs) {
+        let curve_report = match curve {
+            Curve::Native(curve) => audit_native_curve(snapshot, rent, &address, &curve),
+            Curve::Anchor(curve) => audit_anchor_curve(snapshot, rent, &address, &curve),
+            Curve::Undecodable(program) => CurveReport {
+                program,
+                curve: address.to_string(),
+                mint: String::new(),
+                graduated: false,
+                sol_balance: 0,
+                expected_sol_balance: 0,
+                buyback_value: 0,
+                token_balance: 0,
+                expected_token_balance: 0,
+                discrepancies: vec![Discrepancy::Undecodable { address: address.to_string() }],
+            },
+        };
+        report.push(curve_report);
+    }
+
+    report
+}
+
+/// Check a native curve's SOL reserve, redeemable reserve and token reserve
+fn audit_native_curve(
+    snapshot: &Snapshot,
+    rent: &Rent,
+    address: &Pubkey,
+    curve: &BondingCurveState,
+) -> CurveReport {
+    let mut discrepancies = Vec::new();
+
+    // An overflowing buyback can never be covered
+    let buyback_value = curve.calculate_buyback_value().unwrap_or(u64::MAX);
+    let expected_token_balance = curve.total_supply.saturating_sub(curve.tokens_sold);
+
+    let sol_reserve = snapshot.get(&curve.sol_reserve);
+    let sol_balance = sol_reserve.map_or(0, |account| account.lamports);
+    let expected_sol_balance = sol_reserve
+        .map_or(0, |account| rent.minimum_balance(account.data.len()))
+        .saturating_add(curve.redeemable_sol)
+        .saturating_add(curve.liquidity_sol);
+    let token_balance = token_balance(snapshot, &curve.token_reserve, &mut discrepancies);
+
+    if sol_reserve.is_none() {
+        discrepancies.push(Discrepancy::MissingAccount { address: curve.sol_reserve.to_string() });
+    }
+
+    // Graduated curves have handed their reserves to the DEX pool
+    if !curve.has_graduated {
+        if sol_reserve.is_some() && sol_balance != expected_sol_balance {
+            discrepancies.push(Discrepancy::SolBalanceMismatch {
+                expected: expected_sol_balance,
+                actual: sol_balance,
+            });
+        }
+        if curve.redeemable_sol < buyback_value {
+            discrepancies.push(Discrepancy::Undercollateralized {
+                buyback_value,
+                reserves: curve.redeemable_sol,
+            });
+        }
+        if let Some(token_balance) = token_balance {
+            if token_balance != expected_token_balance {
+                discrepancies.push(Discrepancy::TokenBalanceMismatch {
+                    expected: expected_token_balance,
+                    actual: token_balance,
+                });
+            }
+        }
+
+        // Pricing is path independent, but buys round up and sells round
+        // down, so the SOL collected covers at least the curve integral over
+        // every token sold, rounded down
+        let expected_value = match curve.tokens_sold {
+            0 => 0,
+            tokens_sold => curve.calculate_sell_price(tokens_sold).unwrap_or(u64::MAX),
+        };
+        if curve.sol_collected < expected_value {
+            discrepancies.push(Discrepancy::CurveValueMismatch {
+                expected: expected_value,
+                recorded: curve.sol_collected,
+            });
+        }
+    }
+
+    CurveReport {
+        program: ProgramKind::Native,
+        curve: address.to_string(),
+        mint: curve.token_mint.to_string(),
+        graduated: curve.has_graduated,
+        sol_balance,
+        expected_sol_balance,
+        buyback_value,
+        token_balance: token_balance.unwrap_or(0),
+        expected_token_balance,
+        discrepancies,
+    }
+}
+
+/// Check an Anchor curve's own lamports and its associated token account
+fn audit_anchor_curve(
+    snapshot: &Snapshot,
+    rent: &Rent,
+    address: &Pubkey,
+    curve: &AnchorBondingCurve,
+) -> CurveReport {
+    let mut discrepancies = Vec::new();
+
+    // The curve account itself holds the SOL reserves
+    let account = snapshot.get(address);
+    let sol_balance = account.map_or(0, |account| account.lamports);
+    let expected_sol_balance = account
+        .map_or(0, |account| rent.minimum_balance(account.data.len()))
+        .saturating_add(curve.real_sol_reserves);
+    let buyback_value = curve.sol_out(curve.real_token_reserves).unwrap_or(u64::MAX);
+    let expected_token_balance = curve.total_supply.saturating_sub(curve.real_token_reserves);
+    let token_balance = token_balance(snapshot, &curve.token_account(address), &mut discrepancies);
+
+    if !curve.graduated {
+        if sol_balance != expected_sol_balance {
+            discrepancies.push(Discrepancy::SolBalanceMismatch {
+                expected: expected_sol_balance,
+                actual: sol_balance,
+            });
+        }
+        if curve.real_sol_reserves < buyback_value {
+            discrepancies.push(Discrepancy::Undercollateralized {
+                buyback_value,
+                reserves: curve.real_sol_reserves,
+            });
+        }
+        if let Some(token_balance) = token_balance {
+            if token_balance != expected_token_balance {
+                discrepancies.push(Discrepancy::TokenBalanceMismatch {
+                    expected: expected_token_balance,
+                    actual: token_balance,
+                });
+            }
+        }
+    }
+
+    CurveReport {
+        program: ProgramKind::Anchor,
+        curve: address.to_string(),
+        mint: curve.mint.to_string(),
+        graduated: curve.graduated,
+        sol_balance,
+        expected_sol_balance,
+        buyback_value,
+        token_balance: token_balance.unwrap_or(0),
+        expected_token_balance,
+        discrepancies,
+    }
+}
+
+/// Balance of an SPL token account, recording a discrepancy if it is missing
+fn token_balance(
+    snapshot: &Snapshot,
+    address: &Pubkey,
+    discrepancies: &mut Vec<Discrepancy>,
+) -> Option<u64> {
+    let balance = snapshot
+        .get(address)
+        .and_then(|account: &Account| spl_token::state::Account::unpack(&account.data).ok())
+        .map(|token_account| token_account.amount);
+    if balance.is_none() {
+        discrepancies.push(Discrepancy::MissingAccount { address: address.to_string() });
+    }
+    balance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asterlaunch_curve::LinearCurve;
+    use borsh::BorshSerialize;
+    use solana_program::program_option::COption;
+
+    /// The curve `CreateToken` sets up, in whole tokens of `decimals`
+    fn native_curve(program_id: &Pubkey, mint: &Pubkey, decimals: u8) -> BondingCurveState {
+        let unit = 10u64.pow(decimals as u32);
+        let pricing = LinearCurve {
+            initial_price: 100_000,
+            price_increment: 10 * unit,
+            total_supply: 1_000_000 * unit,
+            tokens_sold: 0,
+            decimals,
+        };
+        BondingCurveState {
+            token_mint: *mint,
+            token_reserve: BondingCurveState::find_token_reserve_address(program_id, mint).0,
+            sol_reserve: BondingCurveState::find_sol_reserve_address(program_id, mint).0,
+            ..BondingCurveState::from_curve(&pricing, 20)
+        }
+    }
+
+    fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        Account { lamports: 2_039_280, data, owner: spl_token::id(), executable: false, rent_epoch: 0 }
+    }
+
+    /// Snapshot of a native curve after one buy of `tokens_sold` base units
+    fn native_snapshot(program_id: &Pubkey, decimals: u8, tokens_sold: u64) -> (Snapshot, Pubkey) {
+        let rent = Rent::default();
+        let mint = Pubkey::new_unique();
+        let (address, _) = BondingCurveState::find_address(program_id, &mint);
+
+        let mut curve = native_curve(program_id, &mint, decimals);
+        let cost = curve.calculate_buy_price(tokens_sold).unwrap();
+        let (redeemable, liquidity) = asterlaunch_program::bonding_curve::split_liquidity(cost).unwrap();
+        curve.tokens_sold = tokens_sold;
+        curve.sol_collected = cost;
+        curve.redeemable_sol = redeemable;
+        curve.liquidity_sol = liquidity;
+
+        let mut snapshot = Snapshot::default();
+        snapshot.insert(
+            curve.sol_reserve,
+            Account {
+                lamports: rent.minimum_balance(0) + redeemable + liquidity,
+                data: vec![],
+                owner: *program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        snapshot.insert(
+            curve.token_reserve,
+            token_account(&mint, &address, curve.total_supply - tokens_sold),
+        );
+        snapshot.insert(
+            address,
+            Account {
+                lamports: rent.minimum_balance(BondingCurveState::LEN),
+                data: curve.try_to_vec().unwrap(),
+                owner: *program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        (snapshot, address)
+    }
+
+    #[test]
+    fn test_clean_native_curve() {
+        let program_id = Pubkey::new_unique();
+        let (snapshot, _) = native_snapshot(&program_id, 0, 1_000);
+        let programs = Programs { native: Some(program_id), anchor: None };
+
+        let report = audit(&snapshot, &programs, &Rent::default());
+        assert_eq!(report.curves_audited, 1);
+        assert!(report.is_clean(), "{:?}", report.curves[0].discrepancies);
+        assert_eq!(referenced_accounts(&snapshot, &programs).len(), 2);
+    }
+
+    #[test]
+    fn test_rounded_up_buy_is_clean() {
+        let program_id = Pubkey::new_unique();
+        let (snapshot, address) = native_snapshot(&program_id, 6, 1_234_567);
+        let programs = Programs { native: Some(program_id), anchor: None };
+
+        // The buy rounds up a lamport above the sell value
+        let curve = BondingCurveState::deserialize(&mut &snapshot.get(&address).unwrap().data[..]).unwrap();
+        assert_eq!(curve.sol_collected, 123_465);
+        assert_eq!(curve.calculate_sell_price(curve.tokens_sold).unwrap(), 123_464);
+
+        let report = audit(&snapshot, &programs, &Rent::default());
+        assert!(report.is_clean(), "{:?}", report.curves[0].discrepancies);
+
+        // Recording less than the sell value is still flagged
+        let mut snapshot = snapshot;
+        let mut account = snapshot.get(&address).unwrap().clone();
+        let short = BondingCurveState { sol_collected: 123_463, ..curve };
+        account.data = short.try_to_vec().unwrap();
+        snapshot.insert(address, account);
+        let report = audit(&snapshot, &programs, &Rent::default());
+        assert_eq!(
+            report.curves[0].discrepancies,
+            vec![Discrepancy::CurveValueMismatch { expected: 123_464, recorded: 123_463 }]
+        );
+    }
+
+    #[test]
+    fn test_native_discrepancies() {
+        let program_id = Pubkey::new_unique();
+        let (mut snapshot, address) = native_snapshot(&program_id, 0, 1_000);
+        let programs = Programs { native: Some(program_id), anchor: None };
+
+        // Drain a lamport from the SOL reserve and drop the token reserve
+        let curve = BondingCurveState::deserialize(&mut &snapshot.get(&address).unwrap().data[..]).unwrap();
+        let mut sol_reserve = snapshot.get(&curve.sol_reserve).unwrap().clone();
+        sol_reserve.lamports -= 1;
+        snapshot.insert(curve.sol_reserve, sol_reserve);
+        snapshot.insert(curve.token_reserve, Account::default());
+
+        let report = audit(&snapshot, &programs, &Rent::default());
+        let discrepancies = &report.curves[0].discrepancies;
+        assert_eq!(report.curves_with_discrepancies, 1);
+        assert!(discrepancies.iter().any(|d| matches!(d, Discrepancy::SolBalanceMismatch { .. })));
+        assert!(discrepancies.iter().any(|d| matches!(d, Discrepancy::MissingAccount { .. })));
+    }
+}
@@ -0,0 +1,15 @@
+/*
+ * AsterLaunch Reserve Auditor
+ * 
+ * Proof-of-reserves checks for every bonding curve of the native and Anchor
+ * programs:
+ * - SOL reserves hold exactly rent plus the reserves the curve accounts for
+ * - Reserves cover selling back every circulating token
+ * - Token reserves hold exactly the unsold supply
+ * - Recorded curve value matches the curve integral (native program)
+ */
+
+pub mod anchor_curve;
+pub mod audit;
+pub mod report;
+pub mod snapshot;
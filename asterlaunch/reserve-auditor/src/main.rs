@@ -0,0 +1,86 @@
+//! Daily proof-of-reserves job
+//!
+//! Audits a snapshot file or a live RPC node and prints a JSON report. Exits
+//! with status 1 when any curve has a discrepancy.
+//!
+//! ```text
+//! asterlaunch-reserve-auditor --rpc-url http://127.0.0.1:8899 --native-program <ID> --save-snapshot snapshot.json
+//! asterlaunch-reserve-auditor --snapshot snapshot.json --native-program <ID> --output report.json
+//! ```
+
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use anyhow::{bail, Context, Result};
+use asterlaunch_reserve_auditor::{
+    audit::{audit, Programs},
+    snapshot::Snapshot,
+};
+use clap::Parser;
+use solana_program::{pubkey::Pubkey, rent::Rent};
+
+#[derive(Parser, Debug)]
+#[command(about = "Proof-of-reserves audit of AsterLaunch bonding curves")]
+struct Args {
+    /// Snapshot file of keyed accounts to audit
+    #[arg(long, conflicts_with = "rpc_url", required_unless_present = "rpc_url")]
+    snapshot: Option<PathBuf>,
+
+    /// RPC node to fetch accounts from, e.g. a local validator
+    #[arg(long)]
+    rpc_url: Option<String>,
+
+    /// Native program ID
+    #[arg(long)]
+    native_program: Option<Pubkey>,
+
+    /// Anchor program ID
+    #[arg(long)]
+    anchor_program: Option<Pubkey>,
+
+    /// Save the fetched accounts as a snapshot file
+    #[arg(long, requires = "rpc_url")]
+    save_snapshot: Option<PathBuf>,
+
+    /// Write the report here instead of stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+fn run(args: Args) -> Result<bool> {
+    let programs = Programs {
+        native: args.native_program,
+        anchor: args.anchor_program,
+    };
+    if programs.ids().is_empty() {
+        bail!("pass --native-program and/or --anchor-program");
+    }
+
+    let snapshot = match (&args.snapshot, &args.rpc_url) {
+        (Some(path), _) => Snapshot::load(path)?,
+        (None, Some(rpc_url)) => Snapshot::fetch(rpc_url, &programs)?,
+        (None, None) => unreachable!("clap requires a snapshot or an RPC URL"),
+    };
+    if let Some(path) = &args.save_snapshot {
+        snapshot.save(path)?;
+    }
+
+    let report = audit(&snapshot, &programs, &Rent::default());
+    let json = serde_json::to_string_pretty(&report)?;
+    match &args.output {
+        Some(path) => fs::write(path, json).with_context(|| format!("cannot write {}", path.display()))?,
+        None => println!("{}", json),
+    }
+
+    Ok(report.is_clean())
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::from(2)
+        }
+    }
+}
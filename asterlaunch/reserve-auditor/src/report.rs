@@ -0,0 +1,102 @@
+//! Machine-readable audit report
+
+use serde::Serialize;
+
+/// Program a curve belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgramKind {
+    Native,
+    Anchor,
+}
+
+/// A reserve that does not match what the curve accounts for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Discrepancy {
+    /// Curve account data could not be decoded
+    Undecodable { address: String },
+
+    /// An account the curve references is missing from the snapshot
+    MissingAccount { address: String },
+
+    /// SOL reserve balance differs from rent plus the ledgered reserves
+    SolBalanceMismatch { expected: u64, actual: u64 },
+
+    /// Reserves cannot buy back every circulating token
+    Undercollateralized { buyback_value: u64, reserves: u64 },
+
+    /// Token reserve balance differs from the unsold supply
+    TokenBalanceMismatch { expected: u64, actual: u64 },
+
+    /// Recorded curve value is below the curve integral over tokens sold
+    CurveValueMismatch { expected: u64, recorded: u64 },
+}
+
+/// Audit result of a single bonding curve
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CurveReport {
+    pub program: ProgramKind,
+    pub curve: String,
+    pub mint: String,
+    pub graduated: bool,
+
+    /// Lamports held by the SOL reserve
+    pub sol_balance: u64,
+
+    /// Lamports the SOL reserve should hold (rent plus ledgered reserves)
+    pub expected_sol_balance: u64,
+
+    /// SOL needed to buy back every circulating token
+    pub buyback_value: u64,
+
+    /// Tokens held by the token reserve
+    pub token_balance: u64,
+
+    /// Tokens the token reserve should hold
+    pub expected_token_balance: u64,
+
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl CurveReport {
+    /// Whether the curve's reserves check out
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Proof-of-reserves report over every curve in a snapshot
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub native_program: Option<String>,
+    pub anchor_program: Option<String>,
+    pub curves_audited: usize,
+    pub curves_with_discrepancies: usize,
+
+    /// Lamports held by all audited SOL reserves
+    pub total_sol_balance: u64,
+
+    /// Lamports needed to buy back every circulating token of every curve
+    pub total_buyback_value: u64,
+
+    pub curves: Vec<CurveReport>,
+}
+
+impl Report {
+    /// Add a curve and update the totals
+    pub fn push(&mut self, curve: CurveReport) {
+        self.curves_audited += 1;
+        if !curve.is_clean() {
+            self.curves_with_discrepancies += 1;
+        }
+        self.total_sol_balance = self.total_sol_balance.saturating_add(curve.sol_balance);
+        self.total_buyback_value = self.total_buyback_value.saturating_add(curve.buyback_value);
+        self.curves.push(curve);
+    }
+
+    /// Whether every curve's reserves check out
+    pub fn is_clean(&self) -> bool {
+        self.curves_with_discrepancies == 0
+    }
+}
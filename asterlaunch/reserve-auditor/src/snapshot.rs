@@ -0,0 +1,133 @@
+//! Account snapshots to audit
+//!
+//! A snapshot is a JSON array of keyed accounts, in the shape returned by
+//! `getProgramAccounts` and `solana account --output json`:
+//!
+//! ```json
+//! [{ "pubkey": "...", "account": { "lamports": 0, "data": ["...", "base64"], "owner": "...", ... } }]
+//! ```
+
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::response::RpcKeyedAccount;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::audit::{referenced_accounts, Programs};
+
+/// Maximum accounts per `getMultipleAccounts` request
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// A set of accounts captured at one point in time
+#[derive(Debug, Default, Clone)]
+pub struct Snapshot {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl Snapshot {
+    /// Build a snapshot from RPC keyed accounts
+    pub fn from_keyed_accounts(keyed_accounts: Vec<RpcKeyedAccount>) -> Result<Self> {
+        let mut snapshot = Self::default();
+        for keyed in keyed_accounts {
+            let pubkey = Pubkey::from_str(&keyed.pubkey)
+                .with_context(|| format!("invalid pubkey {}", keyed.pubkey))?;
+            let account = keyed
+                .account
+                .decode::<Account>()
+                .ok_or_else(|| anyhow!("cannot decode account {}", pubkey))?;
+            snapshot.insert(pubkey, account);
+        }
+        Ok(snapshot)
+    }
+
+    /// Load a snapshot from a JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("cannot read snapshot {}", path.display()))?;
+        let keyed_accounts: Vec<RpcKeyedAccount> = serde_json::from_str(&json)
+            .with_context(|| format!("invalid snapshot {}", path.display()))?;
+        Self::from_keyed_accounts(keyed_accounts)
+    }
+
+    /// Save the snapshot as a JSON file that [`Snapshot::load`] can read back
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut keyed_accounts: Vec<RpcKeyedAccount> = self
+            .accounts
+            .iter()
+            .map(|(pubkey, account)| RpcKeyedAccount {
+                pubkey: pubkey.to_string(),
+                account: UiAccount::encode(pubkey, account, UiAccountEncoding::Base64, None, None),
+            })
+            .collect();
+        keyed_accounts.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+
+        fs::write(path, serde_json::to_string_pretty(&keyed_accounts)?)
+            .with_context(|| format!("cannot write snapshot {}", path.display()))
+    }
+
+    /// Fetch every account owned by the audited programs from an RPC node,
+    /// plus the reserve accounts their curves reference
+    pub fn fetch(rpc_url: &str, programs: &Programs) -> Result<Self> {
+        let client = RpcClient::new(rpc_url.to_string());
+
+        let mut snapshot = Self::default();
+        for program_id in programs.ids() {
+            let accounts = client
+                .get_program_accounts(&program_id)
+                .with_context(|| format!("cannot fetch accounts of {}", program_id))?;
+            for (pubkey, account) in accounts {
+                snapshot.insert(pubkey, account);
+            }
+        }
+
+        let missing: Vec<Pubkey> = referenced_accounts(&snapshot, programs)
+            .into_iter()
+            .filter(|pubkey| snapshot.get(pubkey).is_none())
+            .collect();
+        for chunk in missing.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = client
+                .get_multiple_accounts(chunk)
+                .context("cannot fetch reserve accounts")?;
+            for (pubkey, account) in chunk.iter().zip(accounts) {
+                if let Some(account) = account {
+                    snapshot.insert(*pubkey, account);
+                }
+            }
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Add or replace an account
+    pub fn insert(&mut self, pubkey: Pubkey, account: Account) {
+        self.accounts.insert(pubkey, account);
+    }
+
+    /// Look up an account
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get(pubkey)
+    }
+
+    /// Accounts owned by a program, in address order
+    pub fn owned_by(&self, program_id: &Pubkey) -> Vec<(&Pubkey, &Account)> {
+        let mut accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.owner == *program_id)
+            .collect();
+        accounts.sort_by_key(|(pubkey, _)| **pubkey);
+        accounts
+    }
+
+    /// Number of accounts in the snapshot
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Whether the snapshot holds no accounts
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
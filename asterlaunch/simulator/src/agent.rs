@@ -0,0 +1,283 @@
+//! Trader populations
+//!
+//! Each agent spends a SOL budget on the curve and decides once per step
+//! whether to trade, from the spot price and an external fair price:
+//! - snipers buy their whole budget on the first step and take profit
+//! - retail buyers arrive over time and trade small random amounts
+//! - panic sellers buy early and dump everything on a drawdown
+//! - arbitrage bots trade the curve towards the fair price
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::market::Fill;
+
+const BPS: u64 = asterlaunch_curve::BPS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgentKind {
+    Sniper,
+    Retail,
+    PanicSeller,
+    Arbitrageur,
+}
+
+impl AgentKind {
+    /// Order agents land in within a step; snipers and bots pay for priority
+    pub fn priority(self) -> u8 {
+        match self {
+            AgentKind::Sniper => 0,
+            AgentKind::Arbitrageur => 1,
+            AgentKind::PanicSeller | AgentKind::Retail => 2,
+        }
+    }
+}
+
+/// A trade an agent wants to make
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// Spend at most this many lamports, fees included
+    Buy(u64),
+    /// Sell this many base units
+    Sell(u64),
+}
+
+/// What an agent sees when deciding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct View {
+    pub step: u64,
+
+    /// Spot price (lamports per whole token)
+    pub price: u64,
+
+    /// External value of the token (lamports per whole token)
+    pub fair_price: u64,
+
+    pub decimals: u8,
+}
+
+/// Tuning shared by every agent of a kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Behaviour {
+    /// Sniper: sell out once the price reaches this multiple of the entry
+    /// price (basis points)
+    pub take_profit_bps: u64,
+
+    /// Retail: chance of buying, and of selling, on a step (basis points)
+    pub retail_buy_bps: u64,
+    pub retail_sell_bps: u64,
+
+    /// Retail: largest share of the budget one buy spends (basis points)
+    pub retail_max_buy_bps: u64,
+
+    /// Panic seller: sell out once the price falls this far below its
+    /// peak since buying (basis points)
+    pub panic_drawdown_bps: u64,
+
+    /// Arbitrageur: gap between spot and fair price worth trading, wide
+    /// enough to cover fees both ways (basis points)
+    pub arbitrage_band_bps: u64,
+
+    /// Arbitrageur: share of the budget or holdings one trade uses (basis
+    /// points)
+    pub arbitrage_size_bps: u64,
+}
+
+impl Default for Behaviour {
+    fn default() -> Self {
+        Self {
+            take_profit_bps: 30000,
+            retail_buy_bps: 500,
+            retail_sell_bps: 150,
+            retail_max_buy_bps: 2500,
+            panic_drawdown_bps: 1500,
+            arbitrage_band_bps: 300,
+            arbitrage_size_bps: 1000,
+        }
+    }
+}
+
+/// A trader and its running account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Agent {
+    pub id: usize,
+    pub kind: AgentKind,
+
+    /// First step the agent trades on
+    pub entry_step: u64,
+
+    /// Lamports left to spend
+    pub budget: u64,
+
+    /// Base units held
+    pub tokens: u64,
+
+    pub sol_spent: u64,
+    pub sol_received: u64,
+    pub fees_paid: u64,
+    pub trades: u64,
+
+    /// Average price of the first buy (lamports per whole token)
+    entry_price: u64,
+
+    /// Highest spot price seen while holding
+    peak_price: u64,
+}
+
+impl Agent {
+    pub fn new(id: usize, kind: AgentKind, budget: u64, entry_step: u64) -> Self {
+        Self {
+            id,
+            kind,
+            entry_step,
+            budget,
+            tokens: 0,
+            sol_spent: 0,
+            sol_received: 0,
+            fees_paid: 0,
+            trades: 0,
+            entry_price: 0,
+            peak_price: 0,
+        }
+    }
+
+    /// Whether the agent has bought before
+    fn has_entered(&self) -> bool {
+        self.sol_spent > 0
+    }
+
+    /// The agent's order for this step, if any
+    pub fn decide(&mut self, view: &View, behaviour: &Behaviour, rng: &mut impl Rng) -> Option<Order> {
+        if view.step < self.entry_step {
+            return None;
+        }
+        let price = view.price as u128;
+
+        match self.kind {
+            AgentKind::Sniper => {
+                if !self.has_entered() {
+                    return (self.budget > 0).then_some(Order::Buy(self.budget));
+                }
+                let target = self.entry_price as u128 * behaviour.take_profit_bps as u128;
+                (self.tokens > 0 && price * BPS as u128 >= target).then_some(Order::Sell(self.tokens))
+            }
+            AgentKind::Retail => {
+                let roll = rng.gen_range(0..BPS);
+                if roll < behaviour.retail_buy_bps && self.budget > 0 {
+                    let largest = (self.budget as u128 * behaviour.retail_max_buy_bps as u128 / BPS as u128) as u64;
+                    Some(Order::Buy(rng.gen_range(1..=largest.max(1)).min(self.budget)))
+                } else if roll < behaviour.retail_buy_bps + behaviour.retail_sell_bps && self.tokens > 0 {
+                    Some(Order::Sell(rng.gen_range(1..=self.tokens)))
+                } else {
+                    None
+                }
+            }
+            AgentKind::PanicSeller => {
+                if !self.has_entered() {
+                    return (self.budget > 0).then_some(Order::Buy(self.budget));
+                }
+                if self.tokens == 0 {
+                    return None;
+                }
+                self.peak_price = self.peak_price.max(view.price);
+                let floor = self.peak_price as u128 * (BPS - behaviour.panic_drawdown_bps.min(BPS)) as u128;
+                (price * (BPS as u128) <= floor).then_some(Order::Sell(self.tokens))
+            }
+            AgentKind::Arbitrageur => {
+                let fair = view.fair_price as u128;
+                let band = behaviour.arbitrage_band_bps as u128;
+                let size = |amount: u64| ((amount as u128 * behaviour.arbitrage_size_bps as u128 / BPS as u128) as u64).max(1);
+                if price * (BPS as u128) < fair * (BPS as u128).saturating_sub(band) && self.budget > 0 {
+                    Some(Order::Buy(size(self.budget).min(self.budget)))
+                } else if price * (BPS as u128) > fair * (BPS as u128 + band) && self.tokens > 0 {
+                    Some(Order::Sell(size(self.tokens).min(self.tokens)))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Apply a filled order to the agent's account
+    pub fn record(&mut self, order: Order, fill: &Fill, decimals: u8) {
+        match order {
+            Order::Buy(_) => {
+                if !self.has_entered() && fill.token_amount > 0 {
+                    let price = fill.sol_amount as u128 * 10u128.pow(decimals as u32) / fill.token_amount as u128;
+                    self.entry_price = u64::try_from(price).unwrap_or(u64::MAX);
+                    self.peak_price = self.entry_price;
+                }
+                self.budget = self.budget.saturating_sub(fill.sol_amount);
+                self.sol_spent += fill.sol_amount;
+                self.tokens += fill.token_amount;
+            }
+            Order::Sell(_) => {
+                self.sol_received += fill.sol_amount;
+                self.tokens -= fill.token_amount;
+            }
+        }
+        self.fees_paid += fill.fees.total_fee();
+        self.trades += 1;
+    }
+
+    /// Tokens held, valued at `price` (lamports per whole token)
+    pub fn holdings_value(&self, price: u64, decimals: u8) -> u64 {
+        let value = self.tokens as u128 * price as u128 / 10u128.pow(decimals as u32);
+        u64::try_from(value).unwrap_or(u64::MAX)
+    }
+
+    /// SOL received plus holdings at `price`, less SOL spent
+    pub fn pnl(&self, price: u64, decimals: u8) -> i128 {
+        self.sol_received as i128 + self.holdings_value(price, decimals) as i128 - self.sol_spent as i128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asterlaunch_curve::TradeFees;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_sniper_takes_profit() {
+        let behaviour = Behaviour::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut sniper = Agent::new(0, AgentKind::Sniper, 1_000_000, 0);
+        let mut view = View { step: 0, price: 100, fair_price: 100, decimals: 0 };
+
+        let order = sniper.decide(&view, &behaviour, &mut rng).unwrap();
+        assert_eq!(order, Order::Buy(1_000_000));
+        let fill = Fill { token_amount: 9_000, sol_amount: 990_000, fees: TradeFees::default() };
+        sniper.record(order, &fill, 0);
+        assert_eq!(sniper.budget, 10_000);
+
+        // Bought at 110 lamports a token; 3x is 330
+        view.price = 329;
+        assert_eq!(sniper.decide(&view, &behaviour, &mut rng), None);
+        view.price = 330;
+        assert_eq!(sniper.decide(&view, &behaviour, &mut rng), Some(Order::Sell(9_000)));
+        assert_eq!(sniper.pnl(330, 0), 9_000 * 330 - 990_000);
+    }
+
+    #[test]
+    fn test_panic_seller_dumps_on_drawdown() {
+        let behaviour = Behaviour::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut seller = Agent::new(0, AgentKind::PanicSeller, 1_000, 5);
+        let mut view = View { step: 4, price: 100, fair_price: 100, decimals: 0 };
+        assert_eq!(seller.decide(&view, &behaviour, &mut rng), None);
+
+        view.step = 5;
+        let order = seller.decide(&view, &behaviour, &mut rng).unwrap();
+        seller.record(order, &Fill { token_amount: 10, sol_amount: 1_000, fees: TradeFees::default() }, 0);
+
+        // Peaks at 200, then sells out 15% below it
+        view.price = 200;
+        assert_eq!(seller.decide(&view, &behaviour, &mut rng), None);
+        view.price = 171;
+        assert_eq!(seller.decide(&view, &behaviour, &mut rng), None);
+        view.price = 170;
+        assert_eq!(seller.decide(&view, &behaviour, &mut rng), Some(Order::Sell(10)));
+    }
+}
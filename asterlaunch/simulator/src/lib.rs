@@ -0,0 +1,21 @@
+/*
+ * AsterLaunch Market Simulator
+ * 
+ * Tries curve parameters against trader populations before they ship:
+ * - `market`: the native and Anchor curves with their fee models, moved
+ *   the way the programs move them
+ * - `agent`: snipers, retail buyers, panic sellers and arbitrage bots
+ * - `sim`: seeded runs producing price, reserves, fees, graduation time
+ *   and per-agent PnL
+ * - `replay`: re-runs recorded trades to check them against the curve
+ */
+
+pub mod agent;
+pub mod market;
+pub mod replay;
+pub mod sim;
+
+pub use agent::{Agent, AgentKind, Behaviour, Order};
+pub use market::{ConstantProductMarket, Fill, LinearMarket, Market, MarketState, Side};
+pub use replay::{replay, Divergence, RecordedTrade, Report};
+pub use sim::{run, Cohort, Config, Outcome, Population};
@@ -0,0 +1,303 @@
+//! AsterLaunch market simulator
+//!
+//! ```text
+//! asterlaunch-sim simulate --family native --total-supply 1000000000000 --initial-price 50000 --price-increment 140625 --out sim
+//! asterlaunch-sim simulate --family anchor --virtual-sol-reserves 30 --graduation-threshold 400000000000 --retail 200
+//! asterlaunch-sim replay --family native --total-supply 1000000000000 --initial-price 50000 --price-increment 140625 sim/trades.csv
+//! ```
+
+use std::{fs, path::{Path, PathBuf}, process::ExitCode};
+
+use anyhow::{bail, Context, Result};
+use asterlaunch_curve::{ConstantProductCurve, LinearCurve};
+use asterlaunch_simulator::{
+    replay, run, Behaviour, Cohort, Config, ConstantProductMarket, LinearMarket, Market, Population, RecordedTrade,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+
+/// Mint decimals and supply of tokens launched by the Anchor program
+const ANCHOR_TOKEN_DECIMALS: u8 = 6;
+const ANCHOR_TOKEN_SUPPLY: u64 = 1_000_000_000_000_000;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+#[derive(Parser, Debug)]
+#[command(about = "Simulate and replay trading on AsterLaunch bonding curves")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run an agent population against a curve and write CSV results
+    Simulate(SimulateArgs),
+
+    /// Re-run recorded trades through a curve and report divergences
+    Replay(ReplayArgs),
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Family {
+    /// The native program's linear curve
+    Native,
+    /// The Anchor program's constant-product curve
+    Anchor,
+}
+
+/// The curve as created on-chain
+#[derive(Args, Debug)]
+struct CurveArgs {
+    #[arg(long, value_enum)]
+    family: Family,
+
+    /// Total supply in base units (Anchor: always 1B tokens)
+    #[arg(long)]
+    total_supply: Option<u64>,
+
+    /// Mint decimals (Anchor: always 6)
+    #[arg(long, default_value_t = ANCHOR_TOKEN_DECIMALS)]
+    decimals: u8,
+
+    /// Creator share of the trading fee (basis points of the trade)
+    #[arg(long, default_value_t = 20)]
+    creator_fee_bps: u16,
+
+    /// Native: price of the first token (lamports)
+    #[arg(long, default_value_t = 100_000)]
+    initial_price: u64,
+
+    /// Native: price increase per whole token sold (10^-decimals lamports)
+    #[arg(long)]
+    price_increment: Option<u64>,
+
+    /// Anchor: virtual SOL reserves (SOL)
+    #[arg(long, value_parser = parse_sol, default_value = "30")]
+    virtual_sol_reserves: u64,
+
+    /// Anchor: virtual token reserves in base units (default: the total supply)
+    #[arg(long)]
+    virtual_token_reserves: Option<u64>,
+
+    /// Anchor: market cap that graduates the curve (lamports)
+    #[arg(long)]
+    graduation_threshold: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+struct SimulateArgs {
+    #[command(flatten)]
+    curve: CurveArgs,
+
+    #[arg(long, default_value_t = 2000)]
+    steps: u64,
+
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+
+    #[arg(long, default_value_t = 3)]
+    snipers: usize,
+
+    /// SOL per sniper
+    #[arg(long, value_parser = parse_sol, default_value = "5")]
+    sniper_budget: u64,
+
+    #[arg(long, default_value_t = 100)]
+    retail: usize,
+
+    /// SOL per retail buyer
+    #[arg(long, value_parser = parse_sol, default_value = "1")]
+    retail_budget: u64,
+
+    #[arg(long, default_value_t = 20)]
+    panic_sellers: usize,
+
+    /// SOL per panic seller
+    #[arg(long, value_parser = parse_sol, default_value = "2")]
+    panic_budget: u64,
+
+    #[arg(long, default_value_t = 2)]
+    arbitrageurs: usize,
+
+    /// SOL per arbitrage bot
+    #[arg(long, value_parser = parse_sol, default_value = "20")]
+    arbitrage_budget: u64,
+
+    /// Starting fair price for the arbitrage bots, lamports per whole token
+    /// (default: the curve's starting price)
+    #[arg(long, default_value_t = 0)]
+    fair_price: u64,
+
+    /// Largest move of the fair price per step (basis points)
+    #[arg(long, default_value_t = 100)]
+    volatility_bps: u64,
+
+    /// Directory for steps.csv, trades.csv and agents.csv
+    #[arg(long, default_value = "simulation")]
+    out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ReplayArgs {
+    #[command(flatten)]
+    curve: CurveArgs,
+
+    /// CSV of trades in order, from the curve's creation
+    trades: PathBuf,
+}
+
+fn market(args: &CurveArgs) -> Result<Box<dyn Market>> {
+    match args.family {
+        Family::Native => {
+            let curve = LinearCurve {
+                initial_price: args.initial_price,
+                price_increment: args.price_increment.context("--price-increment is required for a native curve")?,
+                total_supply: args.total_supply.context("--total-supply is required for a native curve")?,
+                tokens_sold: 0,
+                decimals: args.decimals,
+            };
+            let market = LinearMarket::new(curve, args.creator_fee_bps)
+                .context("CreateToken would reject this curve")?;
+            Ok(Box::new(market))
+        }
+        Family::Anchor => {
+            if args.decimals != ANCHOR_TOKEN_DECIMALS
+                || args.total_supply.is_some_and(|supply| supply != ANCHOR_TOKEN_SUPPLY)
+            {
+                bail!(
+                    "the Anchor program mints {} base units with {} decimals",
+                    ANCHOR_TOKEN_SUPPLY,
+                    ANCHOR_TOKEN_DECIMALS
+                );
+            }
+            let curve = ConstantProductCurve {
+                virtual_sol_reserves: args.virtual_sol_reserves,
+                virtual_token_reserves: args.virtual_token_reserves.unwrap_or(ANCHOR_TOKEN_SUPPLY),
+                real_sol_reserves: 0,
+                real_token_reserves: 0,
+                total_supply: ANCHOR_TOKEN_SUPPLY,
+                decimals: ANCHOR_TOKEN_DECIMALS,
+            };
+            let threshold = args
+                .graduation_threshold
+                .context("--graduation-threshold is required for an Anchor curve")?;
+            let market = ConstantProductMarket::new(curve, args.creator_fee_bps, threshold)
+                .context("virtual reserves must be non-zero and the token reserves at most the supply")?;
+            Ok(Box::new(market))
+        }
+    }
+}
+
+fn simulate(args: SimulateArgs) -> Result<()> {
+    let mut market = market(&args.curve)?;
+    let config = Config {
+        steps: args.steps,
+        seed: args.seed,
+        population: Population {
+            snipers: Cohort { count: args.snipers, budget: args.sniper_budget },
+            retail: Cohort { count: args.retail, budget: args.retail_budget },
+            panic_sellers: Cohort { count: args.panic_sellers, budget: args.panic_budget },
+            arbitrageurs: Cohort { count: args.arbitrageurs, budget: args.arbitrage_budget },
+        },
+        behaviour: Behaviour::default(),
+        fair_price: args.fair_price,
+        volatility_bps: args.volatility_bps,
+    };
+    let outcome = run(market.as_mut(), &config)?;
+
+    fs::create_dir_all(&args.out).with_context(|| format!("creating {}", args.out.display()))?;
+    write_csv(&args.out.join("steps.csv"), &outcome.steps)?;
+    write_csv(&args.out.join("trades.csv"), &outcome.trades)?;
+    write_csv(&args.out.join("agents.csv"), &outcome.agents)?;
+
+    let last = outcome.steps.last().context("the simulation ran no steps")?;
+    println!("steps:        {}", outcome.steps.len());
+    println!("trades:       {}", outcome.trades.len());
+    match outcome.graduated_at {
+        Some(step) => println!("graduated:    step {}", step),
+        None => println!("graduated:    no"),
+    }
+    println!("price:        {} lamports per token", last.price);
+    println!("sol_reserves: {} SOL", sol_string(last.sol_reserves));
+    println!(
+        "fees:         {} liquidity, {} platform, {} creator (SOL)",
+        sol_string(last.liquidity_fees),
+        sol_string(last.platform_fees),
+        sol_string(last.creator_fees)
+    );
+    println!("results:      {}", args.out.display());
+    Ok(())
+}
+
+fn replay_trades(args: ReplayArgs) -> Result<bool> {
+    let mut market = market(&args.curve)?;
+    let mut reader = csv::Reader::from_path(&args.trades)
+        .with_context(|| format!("opening {}", args.trades.display()))?;
+    let trades = reader
+        .deserialize()
+        .collect::<Result<Vec<RecordedTrade>, _>>()
+        .with_context(|| format!("reading {}", args.trades.display()))?;
+
+    let report = replay(market.as_mut(), trades)?;
+    for divergence in &report.divergences {
+        println!(
+            "trade {}: {} recorded {}, replayed {}",
+            divergence.index, divergence.field, divergence.recorded, divergence.replayed
+        );
+    }
+    if let Some((index, err)) = report.rejected {
+        println!("trade {}: rejected by the curve ({})", index, err);
+    }
+    println!(
+        "replayed {} trades: price {}, {} lamports and {} base units in reserve{}",
+        report.trades,
+        report.state.price,
+        report.state.sol_reserves,
+        report.state.token_reserves,
+        if report.graduated { ", graduated" } else { "" }
+    );
+    Ok(report.is_consistent())
+}
+
+fn write_csv<T: Serialize>(path: &Path, rows: &[T]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path).with_context(|| format!("creating {}", path.display()))?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Parse a decimal SOL amount into lamports
+fn parse_sol(sol: &str) -> Result<u64> {
+    let (whole, fraction) = sol.trim().split_once('.').unwrap_or((sol.trim(), ""));
+    if whole.is_empty() && fraction.is_empty() || fraction.len() > 9 {
+        bail!("expected SOL with at most 9 decimals, e.g. 85 or 0.5");
+    }
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().context("invalid SOL amount")? };
+    let fraction: u64 = format!("{:0<9}", fraction).parse().context("invalid SOL amount")?;
+    whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(fraction))
+        .context("SOL amount too large")
+}
+
+fn sol_string(lamports: u64) -> String {
+    format!("{}.{:04}", lamports / LAMPORTS_PER_SOL, lamports % LAMPORTS_PER_SOL / 100_000)
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Simulate(args) => simulate(args).map(|()| true),
+        Command::Replay(args) => replay_trades(args),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! Curves the simulator trades against
+//!
+//! Each market keeps the state its program keeps and moves it the way the
+//! program's trade instructions do, pricing with the shared curve crate.
+//! Reserves are reported as the programs' `Trade` events report them.
+
+use asterlaunch_curve::{
+    fees, ConstantProductCurve, CurveError, FeeStructure, LinearCurve, PlatformFee, TradeFees,
+};
+use serde::{Deserialize, Serialize};
+
+/// Direction of a trade, written as the indexer writes it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// What a trade moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fill {
+    /// Base units bought or sold
+    pub token_amount: u64,
+
+    /// Lamports paid by the buyer or received by the seller, fees included
+    pub sol_amount: u64,
+
+    pub fees: TradeFees,
+}
+
+/// Reserves after a trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketState {
+    /// Spot price (lamports per whole token)
+    pub price: u64,
+
+    /// Lamports held by the curve, excluding rent
+    pub sol_reserves: u64,
+
+    /// Unsold tokens (base units)
+    pub token_reserves: u64,
+}
+
+/// A bonding curve and its fee model
+pub trait Market {
+    fn decimals(&self) -> u8;
+
+    fn state(&self) -> Result<MarketState, CurveError>;
+
+    /// Whether the curve has reached graduation
+    fn is_graduated(&self) -> bool;
+
+    /// Buy with at most `sol_amount` lamports, fees included
+    fn buy(&mut self, sol_amount: u64) -> Result<Fill, CurveError>;
+
+    /// Sell `token_amount` base units
+    fn sell(&mut self, token_amount: u64) -> Result<Fill, CurveError>;
+
+    /// Re-run a recorded buy from its instruction's input
+    ///
+    /// The native program buys a token amount and the Anchor program spends
+    /// a SOL amount, so each uses the side of the record it was given
+    fn replay_buy(&mut self, token_amount: u64, sol_amount: u64) -> Result<Fill, CurveError>;
+}
+
+/// The native program's linear curve and reserve ledgers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinearMarket {
+    pub curve: LinearCurve,
+    pub fees: FeeStructure,
+
+    /// Half of each purchase, backing sells
+    pub redeemable_sol: u64,
+
+    /// The other half plus liquidity fees, locked until graduation
+    pub liquidity_sol: u64,
+}
+
+impl LinearMarket {
+    pub fn new(curve: LinearCurve, creator_fee_bps: u16) -> Result<Self, CurveError> {
+        let fees = FeeStructure::with_creator_fee(creator_fee_bps)?;
+        curve.validate(&fees)?;
+        Ok(Self {
+            curve,
+            fees,
+            redeemable_sol: 0,
+            liquidity_sol: 0,
+        })
+    }
+
+    /// Buy exactly `token_amount` base units, as `BuyTokens` does
+    pub fn buy_tokens(&mut self, token_amount: u64) -> Result<Fill, CurveError> {
+        let quote = self.curve.quote_buy(&self.fees, token_amount)?;
+
+        let (redeemable, liquidity) = fees::split_liquidity(quote.cost)?;
+        let liquidity = liquidity
+            .checked_add(quote.fees.liquidity_fee)
+            .ok_or(CurveError::MathOverflow)?;
+        self.redeemable_sol = self.redeemable_sol.checked_add(redeemable).ok_or(CurveError::MathOverflow)?;
+        self.liquidity_sol = self.liquidity_sol.checked_add(liquidity).ok_or(CurveError::MathOverflow)?;
+        self.curve.tokens_sold += token_amount;
+
+        Ok(Fill {
+            token_amount,
+            sol_amount: quote.total_cost,
+            fees: quote.fees,
+        })
+    }
+}
+
+impl Market for LinearMarket {
+    fn decimals(&self) -> u8 {
+        self.curve.decimals
+    }
+
+    fn state(&self) -> Result<MarketState, CurveError> {
+        Ok(MarketState {
+            price: self.curve.spot_price()?,
+            sol_reserves: self.redeemable_sol.checked_add(self.liquidity_sol).ok_or(CurveError::MathOverflow)?,
+            token_reserves: self.curve.tokens_remaining(),
+        })
+    }
+
+    /// Sold out; the curve can then graduate, though it still takes sells
+    /// until it does
+    fn is_graduated(&self) -> bool {
+        self.curve.tokens_remaining() == 0
+    }
+
+    fn buy(&mut self, sol_amount: u64) -> Result<Fill, CurveError> {
+        let token_amount = self.curve.max_buy(&self.fees, sol_amount)?;
+        self.buy_tokens(token_amount)
+    }
+
+    fn sell(&mut self, token_amount: u64) -> Result<Fill, CurveError> {
+        let quote = self.curve.quote_sell(&self.fees, token_amount)?;
+
+        // The redeemed half leaves the redeemable reserve; its liquidity fee
+        // stays behind as locked liquidity
+        self.redeemable_sol = self
+            .redeemable_sol
+            .checked_sub(quote.redeemed)
+            .ok_or(CurveError::InsufficientLiquidity)?;
+        self.liquidity_sol = self
+            .liquidity_sol
+            .checked_add(quote.fees.liquidity_fee)
+            .ok_or(CurveError::MathOverflow)?;
+        self.curve.tokens_sold -= token_amount;
+
+        Ok(Fill {
+            token_amount,
+            sol_amount: quote.sol_out,
+            fees: quote.fees,
+        })
+    }
+
+    fn replay_buy(&mut self, token_amount: u64, _sol_amount: u64) -> Result<Fill, CurveError> {
+        self.buy_tokens(token_amount)
+    }
+}
+
+/// The Anchor program's constant-product curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantProductMarket {
+    pub curve: ConstantProductCurve,
+    pub fees: PlatformFee,
+
+    /// Market cap (lamports) at which a buy graduates the curve
+    pub graduation_threshold: u64,
+
+    pub graduated: bool,
+}
+
+impl ConstantProductMarket {
+    pub fn new(curve: ConstantProductCurve, creator_fee_bps: u16, graduation_threshold: u64) -> Result<Self, CurveError> {
+        if curve.virtual_sol_reserves == 0
+            || curve.virtual_token_reserves == 0
+            || curve.virtual_token_reserves > curve.total_supply
+        {
+            return Err(CurveError::InvalidCurveParameters);
+        }
+        Ok(Self {
+            curve,
+            fees: PlatformFee::with_creator_fee(creator_fee_bps),
+            graduation_threshold,
+            graduated: false,
+        })
+    }
+}
+
+impl Market for ConstantProductMarket {
+    fn decimals(&self) -> u8 {
+        self.curve.decimals
+    }
+
+    fn state(&self) -> Result<MarketState, CurveError> {
+        Ok(MarketState {
+            price: self.curve.spot_price()?,
+            sol_reserves: self.curve.real_sol_reserves,
+            token_reserves: self.curve.total_supply - self.curve.real_token_reserves,
+        })
+    }
+
+    fn is_graduated(&self) -> bool {
+        self.graduated
+    }
+
+    fn buy(&mut self, sol_amount: u64) -> Result<Fill, CurveError> {
+        if self.graduated {
+            return Err(CurveError::InvalidTradeAmount);
+        }
+        let quote = self.curve.quote_buy(&self.fees, sol_amount, self.graduation_threshold)?;
+
+        self.curve.real_sol_reserves = self
+            .curve
+            .real_sol_reserves
+            .checked_add(quote.cost)
+            .ok_or(CurveError::MathOverflow)?;
+        self.curve.real_token_reserves += quote.token_amount;
+
+        // The buy that lifts the market cap to the threshold graduates the curve
+        if self.curve.market_cap()? >= self.graduation_threshold {
+            self.graduated = true;
+        }
+
+        Ok(Fill {
+            token_amount: quote.token_amount,
+            sol_amount,
+            fees: quote.fees,
+        })
+    }
+
+    fn sell(&mut self, token_amount: u64) -> Result<Fill, CurveError> {
+        if self.graduated {
+            return Err(CurveError::InvalidTradeAmount);
+        }
+        let quote = self.curve.quote_sell(&self.fees, token_amount, self.graduation_threshold)?;
+
+        self.curve.real_sol_reserves -= quote.proceeds;
+        self.curve.real_token_reserves -= token_amount;
+
+        Ok(Fill {
+            token_amount,
+            sol_amount: quote.sol_out,
+            fees: quote.fees,
+        })
+    }
+
+    fn replay_buy(&mut self, _token_amount: u64, sol_amount: u64) -> Result<Fill, CurveError> {
+        self.buy(sol_amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_market_ledgers() {
+        let curve = LinearCurve {
+            initial_price: 100_000,
+            price_increment: 10,
+            total_supply: 1_000_000,
+            tokens_sold: 0,
+            decimals: 0,
+        };
+        let mut market = LinearMarket::new(curve, 20).unwrap();
+
+        // 1000 tokens from 100_000 to 109_990 lamports each, as the native
+        // program's quote tests price them
+        let budget = market.curve.quote_buy(&market.fees, 1_000).unwrap().total_cost;
+        let buy = market.buy(budget).unwrap();
+        assert_eq!(buy.token_amount, 1_000);
+        assert_eq!(buy.sol_amount, budget);
+        let state = market.state().unwrap();
+        assert_eq!(state.sol_reserves, 104_995_000 + buy.fees.liquidity_fee);
+        assert_eq!(state.token_reserves, 999_000);
+
+        // Paid in minus paid out is what the curve and fee recipients hold
+        let sell = market.sell(1_000).unwrap();
+        let held = market.state().unwrap().sol_reserves
+            + buy.fees.platform_fee
+            + buy.fees.creator_fee
+            + sell.fees.platform_fee
+            + sell.fees.creator_fee;
+        assert_eq!(buy.sol_amount - sell.sol_amount, held);
+        assert_eq!(market.sell(1), Err(CurveError::InvalidTradeAmount));
+    }
+
+    #[test]
+    fn test_constant_product_market_graduates() {
+        let curve = ConstantProductCurve {
+            virtual_sol_reserves: 30_000_000_000,
+            virtual_token_reserves: 1_000_000_000_000_000,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+            total_supply: 1_000_000_000_000_000,
+            decimals: 6,
+        };
+        assert_eq!(
+            ConstantProductMarket::new(ConstantProductCurve { virtual_sol_reserves: 0, ..curve }, 20, 1),
+            Err(CurveError::InvalidCurveParameters)
+        );
+
+        // Graduates at a 400 SOL market cap, about 110 SOL in reserves
+        let mut market = ConstantProductMarket::new(curve, 20, 400_000_000_000).unwrap();
+        market.buy(70_000_000_000).unwrap();
+        assert!(!market.is_graduated());
+        market.buy(20_000_000_000).unwrap();
+        assert!(market.is_graduated());
+        assert_eq!(market.buy(1_000_000_000), Err(CurveError::InvalidTradeAmount));
+    }
+}
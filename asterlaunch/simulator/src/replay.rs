@@ -0,0 +1,201 @@
+//! Trade replay
+//!
+//! Re-runs a recorded trade list through a fresh curve and compares what
+//! each trade moved, and the reserves it left, with the record. A curve
+//! replayed from its creation that matches every record agrees with the
+//! program's on-chain state.
+//!
+//! Records are read by column name, so the indexer's `trades` table (joined
+//! with `snapshots` for reserves) and the simulator's own `trades.csv`
+//! replay alike. Fees and reserves missing from a record are not compared.
+
+use asterlaunch_curve::CurveError;
+use serde::Deserialize;
+
+use crate::market::{Market, MarketState, Side};
+
+/// A trade as recorded from the program's `Trade` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct RecordedTrade {
+    pub side: Side,
+
+    /// Base units traded
+    pub token_amount: u64,
+
+    /// Lamports paid by the buyer or received by the seller
+    pub sol_amount: u64,
+
+    pub liquidity_fee: Option<u64>,
+
+    /// The platform's fee, excluding any referrer's cut
+    pub platform_fee: Option<u64>,
+    pub creator_fee: Option<u64>,
+
+    #[serde(default)]
+    pub referral_fee: u64,
+
+    /// State after the trade
+    pub sol_reserves: Option<u64>,
+    pub token_reserves: Option<u64>,
+}
+
+/// A recorded value the replay did not reproduce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    /// Position of the trade in the record
+    pub index: usize,
+    pub field: &'static str,
+    pub recorded: u64,
+    pub replayed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Trades replayed
+    pub trades: usize,
+
+    pub divergences: Vec<Divergence>,
+
+    /// The trade the curve rejected, which ends the replay
+    pub rejected: Option<(usize, CurveError)>,
+
+    /// State after the last trade replayed
+    pub state: MarketState,
+    pub graduated: bool,
+}
+
+impl Report {
+    /// Whether every record was reproduced
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty() && self.rejected.is_none()
+    }
+}
+
+/// Replay `trades` in order through `market`
+///
+/// A divergent trade still applies the replayed amounts, so later records
+/// are compared against the curve's own state
+pub fn replay<M: Market + ?Sized>(
+    market: &mut M,
+    trades: impl IntoIterator<Item = RecordedTrade>,
+) -> Result<Report, CurveError> {
+    let mut divergences = Vec::new();
+    let mut rejected = None;
+    let mut replayed = 0;
+
+    for (index, trade) in trades.into_iter().enumerate() {
+        let result = match trade.side {
+            Side::Buy => market.replay_buy(trade.token_amount, trade.sol_amount),
+            Side::Sell => market.sell(trade.token_amount),
+        };
+        let fill = match result {
+            Ok(fill) => fill,
+            Err(err) => {
+                rejected = Some((index, err));
+                break;
+            }
+        };
+        replayed += 1;
+
+        let state = market.state()?;
+        let recorded_platform_fee = trade.platform_fee.map(|fee| fee + trade.referral_fee);
+        let fields = [
+            ("token_amount", Some(trade.token_amount), fill.token_amount),
+            ("sol_amount", Some(trade.sol_amount), fill.sol_amount),
+            ("liquidity_fee", trade.liquidity_fee, fill.fees.liquidity_fee),
+            ("platform_fee", recorded_platform_fee, fill.fees.platform_fee),
+            ("creator_fee", trade.creator_fee, fill.fees.creator_fee),
+            ("sol_reserves", trade.sol_reserves, state.sol_reserves),
+            ("token_reserves", trade.token_reserves, state.token_reserves),
+        ];
+        for (field, recorded, replayed) in fields {
+            if let Some(recorded) = recorded.filter(|recorded| *recorded != replayed) {
+                divergences.push(Divergence { index, field, recorded, replayed });
+            }
+        }
+    }
+
+    Ok(Report {
+        trades: replayed,
+        divergences,
+        rejected,
+        state: market.state()?,
+        graduated: market.is_graduated(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::ConstantProductMarket;
+    use crate::sim::{self, Cohort, Config, Population, TradeRow};
+    use asterlaunch_curve::ConstantProductCurve;
+
+    fn market() -> ConstantProductMarket {
+        let curve = ConstantProductCurve {
+            virtual_sol_reserves: 30_000_000_000,
+            virtual_token_reserves: 1_000_000_000_000_000,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+            total_supply: 1_000_000_000_000_000,
+            decimals: 6,
+        };
+        ConstantProductMarket::new(curve, 20, u64::MAX).unwrap()
+    }
+
+    fn record(row: &TradeRow) -> RecordedTrade {
+        RecordedTrade {
+            side: row.side,
+            token_amount: row.token_amount,
+            sol_amount: row.sol_amount,
+            liquidity_fee: Some(row.liquidity_fee),
+            platform_fee: Some(row.platform_fee),
+            creator_fee: Some(row.creator_fee),
+            referral_fee: 0,
+            sol_reserves: Some(row.sol_reserves),
+            token_reserves: Some(row.token_reserves),
+        }
+    }
+
+    #[test]
+    fn test_replays_simulated_trades() {
+        let cohort = |count| Cohort { count, budget: 2_000_000_000 };
+        let config = Config {
+            steps: 300,
+            seed: 3,
+            population: Population {
+                snipers: cohort(2),
+                retail: cohort(30),
+                panic_sellers: cohort(5),
+                arbitrageurs: cohort(1),
+            },
+            behaviour: Default::default(),
+            fair_price: 0,
+            volatility_bps: 100,
+        };
+        let mut simulated = market();
+        let outcome = sim::run(&mut simulated, &config).unwrap();
+        let mut trades: Vec<_> = outcome.trades.iter().map(record).collect();
+
+        let report = replay(&mut market(), trades.clone()).unwrap();
+        assert!(report.is_consistent());
+        assert_eq!(report.trades, trades.len());
+        assert_eq!(report.state, simulated.state().unwrap());
+
+        // A record the curve could not have produced is flagged, and the
+        // replay carries on from the curve's own state
+        let sell = trades.iter().position(|t| t.side == Side::Sell).unwrap();
+        trades[sell].sol_amount += 1;
+        trades[sell].referral_fee = 1;
+        let report = replay(&mut market(), trades.clone()).unwrap();
+        let fields: Vec<_> = report.divergences.iter().map(|d| (d.index, d.field)).collect();
+        assert_eq!(fields, [(sell, "sol_amount"), (sell, "platform_fee")]);
+        assert_eq!(report.trades, trades.len());
+
+        // Selling more than was ever bought is rejected
+        trades.insert(0, RecordedTrade { side: Side::Sell, ..trades[0] });
+        let report = replay(&mut market(), trades).unwrap();
+        assert_eq!(report.rejected, Some((0, CurveError::InsufficientLiquidity)));
+        assert!(!report.is_consistent());
+    }
+}
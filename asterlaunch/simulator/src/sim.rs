@@ -0,0 +1,326 @@
+//! Simulation runs
+//!
+//! A run steps an agent population against one market until the curve
+//! graduates or the steps run out. Within a step, agents land in priority
+//! order (random within a priority) and each sees the state the previous
+//! trade left. Orders the curve rejects, such as a budget too small to buy
+//! a base unit, are dropped and counted.
+//!
+//! The fair price the arbitrage bots trade towards is a random walk from
+//! the curve's starting spot price, standing in for an external venue.
+//! Runs are deterministic for a seed.
+
+use asterlaunch_curve::CurveError;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::{
+    agent::{Agent, AgentKind, Behaviour, Order, View},
+    market::{Market, Side},
+};
+
+const BPS: u64 = asterlaunch_curve::BPS;
+
+/// Agents of one kind and the SOL each starts with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cohort {
+    pub count: usize,
+
+    /// Lamports per agent
+    pub budget: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Population {
+    pub snipers: Cohort,
+    pub retail: Cohort,
+    pub panic_sellers: Cohort,
+    pub arbitrageurs: Cohort,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub steps: u64,
+    pub seed: u64,
+    pub population: Population,
+    pub behaviour: Behaviour,
+
+    /// Starting fair price (lamports per whole token); 0 starts at the
+    /// curve's spot price
+    pub fair_price: u64,
+
+    /// Largest move of the fair price per step (basis points)
+    pub volatility_bps: u64,
+}
+
+/// Market state at the end of a step; fees are cumulative
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StepRow {
+    pub step: u64,
+    pub price: u64,
+    pub fair_price: u64,
+    pub sol_reserves: u64,
+    pub token_reserves: u64,
+    pub trades: u64,
+    pub rejected: u64,
+
+    /// Lamports traded this step, fees included
+    pub volume: u64,
+
+    pub liquidity_fees: u64,
+    pub platform_fees: u64,
+    pub creator_fees: u64,
+    pub graduated: bool,
+}
+
+/// A filled trade and the state it left; replayable with `replay`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TradeRow {
+    pub step: u64,
+    pub agent: usize,
+    pub kind: AgentKind,
+    pub side: Side,
+    pub token_amount: u64,
+    pub sol_amount: u64,
+    pub liquidity_fee: u64,
+    pub platform_fee: u64,
+    pub creator_fee: u64,
+    pub price: u64,
+    pub sol_reserves: u64,
+    pub token_reserves: u64,
+}
+
+/// An agent's account at the end of the run, valued at the final spot price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AgentRow {
+    pub agent: usize,
+    pub kind: AgentKind,
+    pub trades: u64,
+    pub sol_spent: u64,
+    pub sol_received: u64,
+    pub tokens_held: u64,
+    pub holdings_value: u64,
+    pub fees_paid: u64,
+    pub pnl: i128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub steps: Vec<StepRow>,
+    pub trades: Vec<TradeRow>,
+    pub agents: Vec<AgentRow>,
+
+    /// Step of the trade that graduated the curve
+    pub graduated_at: Option<u64>,
+}
+
+/// Run `config`'s population against `market`
+pub fn run<M: Market + ?Sized>(market: &mut M, config: &Config) -> Result<Outcome, CurveError> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut agents = population(config, &mut rng);
+    let decimals = market.decimals();
+
+    let mut fair_price = match config.fair_price {
+        0 => market.state()?.price,
+        price => price,
+    };
+    let mut outcome = Outcome {
+        steps: Vec::new(),
+        trades: Vec::new(),
+        agents: Vec::new(),
+        graduated_at: None,
+    };
+    let (mut liquidity_fees, mut platform_fees, mut creator_fees) = (0, 0, 0);
+
+    for step in 0..config.steps {
+        if step > 0 {
+            fair_price = walk(fair_price, config.volatility_bps, &mut rng);
+        }
+
+        let mut order: Vec<usize> = (0..agents.len()).collect();
+        order.shuffle(&mut rng);
+        order.sort_by_key(|&i| agents[i].kind.priority());
+
+        let (mut trades, mut rejected, mut volume) = (0, 0, 0);
+        for i in order {
+            let agent = &mut agents[i];
+            let view = View {
+                step,
+                price: market.state()?.price,
+                fair_price,
+                decimals,
+            };
+            let Some(decision) = agent.decide(&view, &config.behaviour, &mut rng) else {
+                continue;
+            };
+            let (side, result) = match decision {
+                Order::Buy(sol_amount) => (Side::Buy, market.buy(sol_amount)),
+                Order::Sell(token_amount) => (Side::Sell, market.sell(token_amount)),
+            };
+            let fill = match result {
+                Ok(fill) => fill,
+                Err(_) => {
+                    rejected += 1;
+                    continue;
+                }
+            };
+            agent.record(decision, &fill, decimals);
+
+            trades += 1;
+            volume += fill.sol_amount;
+            liquidity_fees += fill.fees.liquidity_fee;
+            platform_fees += fill.fees.platform_fee;
+            creator_fees += fill.fees.creator_fee;
+            let state = market.state()?;
+            outcome.trades.push(TradeRow {
+                step,
+                agent: agent.id,
+                kind: agent.kind,
+                side,
+                token_amount: fill.token_amount,
+                sol_amount: fill.sol_amount,
+                liquidity_fee: fill.fees.liquidity_fee,
+                platform_fee: fill.fees.platform_fee,
+                creator_fee: fill.fees.creator_fee,
+                price: state.price,
+                sol_reserves: state.sol_reserves,
+                token_reserves: state.token_reserves,
+            });
+
+            if market.is_graduated() {
+                outcome.graduated_at = Some(step);
+                break;
+            }
+        }
+
+        let state = market.state()?;
+        outcome.steps.push(StepRow {
+            step,
+            price: state.price,
+            fair_price,
+            sol_reserves: state.sol_reserves,
+            token_reserves: state.token_reserves,
+            trades,
+            rejected,
+            volume,
+            liquidity_fees,
+            platform_fees,
+            creator_fees,
+            graduated: outcome.graduated_at.is_some(),
+        });
+        if outcome.graduated_at.is_some() {
+            break;
+        }
+    }
+
+    let price = market.state()?.price;
+    outcome.agents = agents
+        .iter()
+        .map(|agent| AgentRow {
+            agent: agent.id,
+            kind: agent.kind,
+            trades: agent.trades,
+            sol_spent: agent.sol_spent,
+            sol_received: agent.sol_received,
+            tokens_held: agent.tokens,
+            holdings_value: agent.holdings_value(price, decimals),
+            fees_paid: agent.fees_paid,
+            pnl: agent.pnl(price, decimals),
+        })
+        .collect();
+    Ok(outcome)
+}
+
+/// The agents of a population, with their arrival steps
+///
+/// Snipers and bots are there from the first step; panic sellers arrive in
+/// the first tenth of the run and retail buyers in the first half
+fn population(config: &Config, rng: &mut StdRng) -> Vec<Agent> {
+    let population = &config.population;
+    let cohorts = [
+        (AgentKind::Sniper, population.snipers, 0),
+        (AgentKind::Arbitrageur, population.arbitrageurs, 0),
+        (AgentKind::PanicSeller, population.panic_sellers, config.steps / 10),
+        (AgentKind::Retail, population.retail, config.steps / 2),
+    ];
+
+    let mut agents = Vec::new();
+    for (kind, cohort, arrival) in cohorts {
+        for _ in 0..cohort.count {
+            let entry_step = rng.gen_range(0..=arrival);
+            agents.push(Agent::new(agents.len(), kind, cohort.budget, entry_step));
+        }
+    }
+    agents
+}
+
+/// Move `price` up or down by at most `volatility_bps`
+fn walk(price: u64, volatility_bps: u64, rng: &mut StdRng) -> u64 {
+    let volatility = volatility_bps.min(BPS);
+    let factor = BPS - volatility + rng.gen_range(0..=2 * volatility);
+    ((price as u128 * factor as u128 / BPS as u128) as u64).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::LinearMarket;
+    use asterlaunch_curve::LinearCurve;
+
+    const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+    fn config() -> Config {
+        let cohort = |count, sol| Cohort { count, budget: sol * LAMPORTS_PER_SOL };
+        Config {
+            steps: 500,
+            seed: 7,
+            population: Population {
+                snipers: cohort(2, 5),
+                retail: cohort(40, 1),
+                panic_sellers: cohort(10, 2),
+                arbitrageurs: cohort(2, 10),
+            },
+            behaviour: Behaviour::default(),
+            fair_price: 0,
+            volatility_bps: 100,
+        }
+    }
+
+    fn market() -> LinearMarket {
+        let curve = LinearCurve {
+            initial_price: 50_000,
+            price_increment: 140_625,
+            total_supply: 1_000_000_000_000,
+            tokens_sold: 0,
+            decimals: 6,
+        };
+        LinearMarket::new(curve, 20).unwrap()
+    }
+
+    #[test]
+    fn test_run_conserves_sol() {
+        let mut market = market();
+        let outcome = run(&mut market, &config()).unwrap();
+        assert!(!outcome.trades.is_empty());
+
+        // Everything agents paid in, less what they took out, is held by
+        // the curve or was paid to the platform and creators
+        let spent: u64 = outcome.agents.iter().map(|a| a.sol_spent).sum();
+        let received: u64 = outcome.agents.iter().map(|a| a.sol_received).sum();
+        let last = outcome.steps.last().unwrap();
+        assert_eq!(spent - received, last.sol_reserves + last.platform_fees + last.creator_fees);
+
+        let held: u64 = outcome.agents.iter().map(|a| a.tokens_held).sum();
+        assert_eq!(held, market.curve.tokens_sold);
+        assert_eq!(last.token_reserves, market.curve.tokens_remaining());
+    }
+
+    #[test]
+    fn test_run_is_deterministic() {
+        let first = run(&mut market(), &config()).unwrap();
+        assert_eq!(first, run(&mut market(), &config()).unwrap());
+
+        let other = Config { seed: 8, ..config() };
+        assert_ne!(first.trades, run(&mut market(), &other).unwrap().trades);
+    }
+}
@@ -14,6 +14,12 @@
  * - initial_price = starting price per token
  * - price_increment = how much price increases per token sold
  * 
+ * Prices are per whole token. Mints can have up to 9 decimals; the curve
+ * sells base units, each at price(n) / 10^decimals with n counted in
+ * fractional tokens, and rounds each trade in its own favour. The increment
+ * is fixed point at the mint's decimals (10^-decimals lamports), so large
+ * supplies can rise gently.
+ * 
  * EXAMPLE:
  * --------
  * If initial_price = 0.0001 SOL and price_increment = 0.00000001 SOL:
@@ -26,6 +32,17 @@
  * - 50% of all SOL paid goes to liquidity pool
  * - 49% returns to sellers on sells
  * - 1% platform fee (0.5% to liquidity, 0.5% to treasury)
+ * - The token creator's share, if any, is carved out of the treasury half
+ * 
+ * The curve and fee math lives in the `asterlaunch-curve` crate, shared with
+ * the Anchor program and the off-chain tools; this module re-exports it.
+ * 
+ * The curve's SOL reserve is split into two ledgers:
+ * - redeemable: the half of each purchase that backs sells
+ * - liquidity: the other half plus all liquidity fees, locked until graduation
+ * A sell is valued on the curve; the seller receives the redeemable half of
+ * that value minus the 1% fee, and the liquidity fee moves into the locked
+ * liquidity reserve.
  * 
  * GRADUATION TO DEX:
  * ==================
@@ -38,52 +55,36 @@
 
 use crate::error::AsterLaunchError;
 
-/// Fee structure for trades
-pub struct FeeStructure {
-    /// Total fee in basis points (100 = 1%)
-    pub total_fee_bps: u16,
-    
-    /// Portion of fee that goes to liquidity (basis points)
-    pub liquidity_fee_bps: u16,
-    
-    /// Portion of fee that goes to platform treasury (basis points)
-    pub platform_fee_bps: u16,
+pub use asterlaunch_curve::fees::{FeeBreakdown, FeeStructure, LIQUIDITY_SHARE_BPS};
+
+/// Split a fee between recipients by basis-point weight
+/// 
+/// Each share is rounded down; the remainder is left to the caller
+pub fn split_fee(amount: u64, weights_bps: &[u16]) -> Result<Vec<u64>, AsterLaunchError> {
+    weights_bps
+        .iter()
+        .map(|&weight| {
+            let share = (amount as u128)
+                .checked_mul(weight as u128)
+                .ok_or(AsterLaunchError::MathOverflow)?
+                / 10000;
+            u64::try_from(share).map_err(|_| AsterLaunchError::MathOverflow)
+        })
+        .collect()
 }
 
-impl FeeStructure {
-    /// Default fee structure: 1% total (0.5% liquidity, 0.5% platform)
-    pub fn default() -> Self {
-        Self {
-            total_fee_bps: 100,      // 1%
-            liquidity_fee_bps: 50,   // 0.5%
-            platform_fee_bps: 50,    // 0.5%
-        }
-    }
-    
-    /// Calculate fee amounts from a SOL amount
-    pub fn calculate_fees(&self, sol_amount: u64) -> Result<(u64, u64, u64), AsterLaunchError> {
-        let total_fee = sol_amount
-            .checked_mul(self.total_fee_bps as u64)
-            .ok_or(AsterLaunchError::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(AsterLaunchError::MathOverflow)?;
-        
-        let liquidity_fee = total_fee
-            .checked_mul(self.liquidity_fee_bps as u64)
-            .ok_or(AsterLaunchError::MathOverflow)?
-            .checked_div(self.total_fee_bps as u64)
-            .ok_or(AsterLaunchError::MathOverflow)?;
-        
-        let platform_fee = total_fee
-            .checked_sub(liquidity_fee)
-            .ok_or(AsterLaunchError::MathOverflow)?;
-        
-        let net_amount = sol_amount
-            .checked_sub(total_fee)
-            .ok_or(AsterLaunchError::MathOverflow)?;
-        
-        Ok((net_amount, liquidity_fee, platform_fee))
-    }
+/// Split SOL paid into the curve between the redeemable and liquidity reserves
+/// 
+/// Returns: (redeemable, liquidity). The liquidity share is rounded down.
+pub fn split_liquidity(amount: u64) -> Result<(u64, u64), AsterLaunchError> {
+    Ok(asterlaunch_curve::fees::split_liquidity(amount)?)
+}
+
+/// Portion of a curve value paid out of the redeemable reserve on a sell
+/// 
+/// Rounded down, so a sell never redeems more than the matching buy deposited
+pub fn calculate_redeemable(curve_value: u64) -> Result<u64, AsterLaunchError> {
+    Ok(asterlaunch_curve::fees::calculate_redeemable(curve_value)?)
 }
 
 /// Calculate the market cap based on current price and total supply
@@ -118,12 +119,16 @@ pub fn calculate_market_cap(
 pub fn calculate_graduation_liquidity(
     tokens_remaining: u64,
     liquidity_sol: u64,
+    redeemable_sol: u64,
 ) -> Result<(u64, u64), AsterLaunchError> {
     // All remaining tokens go to liquidity
     let token_liquidity = tokens_remaining;
     
-    // All accumulated liquidity SOL goes to the pool
-    let sol_liquidity = liquidity_sol;
+    // All accumulated liquidity SOL goes to the pool; the curve stops
+    // redeeming once graduated, so the redeemable reserve joins it
+    let sol_liquidity = liquidity_sol
+        .checked_add(redeemable_sol)
+        .ok_or(AsterLaunchError::MathOverflow)?;
     
     Ok((token_liquidity, sol_liquidity))
 }
@@ -142,6 +147,89 @@ mod tests {
         assert_eq!(plat_fee, 5_000_000); // 0.005 SOL
     }
 
+    #[test]
+    fn test_creator_fee_calculation() {
+        let fees = FeeStructure::with_creator_fee(20).unwrap(); // 0.2% to the creator
+        let breakdown = fees.calculate_fee_breakdown(1_000_000_000).unwrap();
+        
+        assert_eq!(breakdown.net_amount, 990_000_000);
+        assert_eq!(breakdown.liquidity_fee, 5_000_000);
+        assert_eq!(breakdown.platform_fee, 3_000_000);
+        assert_eq!(breakdown.creator_fee, 2_000_000);
+        
+        // The creator share cannot exceed the platform portion
+        assert!(FeeStructure::with_creator_fee(51).is_err());
+    }
+
+    #[test]
+    fn test_fee_split() {
+        // 60% ops, 25% buyback, 15% insurance of a 0.005 SOL platform fee
+        let shares = split_fee(5_000_000, &[6000, 2500, 1500]).unwrap();
+        assert_eq!(shares, vec![3_000_000, 1_250_000, 750_000]);
+        
+        // Shares round down and never exceed the fee
+        let shares = split_fee(7, &[3333, 3333, 3334]).unwrap();
+        assert_eq!(shares, vec![2, 2, 2]);
+        assert!(shares.iter().sum::<u64>() <= 7);
+    }
+
+    #[test]
+    fn test_liquidity_split() {
+        // 1 SOL paid in: half redeemable, half locked
+        let (redeemable, liquidity) = split_liquidity(1_000_000_000).unwrap();
+        assert_eq!(redeemable, 500_000_000);
+        assert_eq!(liquidity, 500_000_000);
+        
+        // Odd amounts leave the extra lamport redeemable on buys...
+        assert_eq!(split_liquidity(7).unwrap(), (4, 3));
+        // ...and redeem the smaller half on sells
+        assert_eq!(calculate_redeemable(7).unwrap(), 3);
+        
+        // A 1 SOL sell pays 0.49 SOL after the 1% fee
+        let fees = FeeStructure::default().calculate_fee_breakdown(1_000_000_000).unwrap();
+        let redeemable = calculate_redeemable(1_000_000_000).unwrap();
+        assert_eq!(redeemable - fees.total_fee(), 490_000_000);
+    }
+
+    #[test]
+    fn test_buyback_solvency() {
+        use crate::state::BondingCurveState;
+        use asterlaunch_curve::LinearCurve;
+        
+        let mut curve = BondingCurveState::from_curve(
+            &LinearCurve {
+                initial_price: 100_000,
+                price_increment: 7,
+                total_supply: 1_000_000,
+                tokens_sold: 0,
+                decimals: 0,
+            },
+            0,
+        );
+        
+        // Buying in uneven steps deposits enough to buy everything back
+        let mut paid = 0;
+        for amount in [1, 2, 3, 5, 8, 13] {
+            let cost = curve.calculate_buy_price(amount).unwrap();
+            let (redeemable, liquidity) = split_liquidity(cost).unwrap();
+            curve.redeemable_sol += redeemable;
+            curve.liquidity_sol += liquidity;
+            curve.tokens_sold += amount;
+            paid += cost;
+        }
+        assert_eq!(curve.calculate_sell_price(curve.tokens_sold).unwrap(), paid);
+        assert!(curve.redeemable_sol >= curve.calculate_buyback_value().unwrap());
+        
+        let rent = 890_880;
+        assert!(curve.check_solvency(rent + curve.reserve_balance().unwrap(), rent).is_ok());
+        assert!(curve.check_solvency(rent + curve.reserve_balance().unwrap() - 1, rent).is_err());
+        
+        // A redeemable reserve short of the buyback value is insolvent
+        curve.liquidity_sol += curve.redeemable_sol;
+        curve.redeemable_sol = 0;
+        assert!(curve.check_solvency(rent + curve.reserve_balance().unwrap(), rent).is_err());
+    }
+
     #[test]
     fn test_market_cap_calculation() {
         let price_per_token = 100_000; // 0.0001 SOL in lamports
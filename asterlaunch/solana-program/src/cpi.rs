@@ -0,0 +1,199 @@
+/*
+ * CPI Helpers
+ *
+ * Typed wrappers for calling AsterLaunch from another program, enabled by
+ * the `cpi` feature. Each helper builds the instruction, invokes it and
+ * decodes the return data, so a caller gets the fill (or quote) without
+ * re-reading any accounts.
+ */
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction},
+    program::{get_return_data, invoke, invoke_signed},
+    program_error::ProgramError,
+};
+
+use crate::{
+    error::AsterLaunchError,
+    instruction::AsterLaunchInstruction,
+    quote::{BuyQuote, CurveStatus, SellQuote, TradeResult},
+};
+
+/// Accounts of a `BuyTokens` or `SellTokens` call, in instruction order
+#[derive(Clone)]
+pub struct TradeAccounts<'a, 'info> {
+    /// Buyer or seller
+    pub trader: &'a AccountInfo<'info>,
+    pub token_state: &'a AccountInfo<'info>,
+    pub bonding_curve: &'a AccountInfo<'info>,
+    pub curve_token_account: &'a AccountInfo<'info>,
+    pub curve_sol_account: &'a AccountInfo<'info>,
+    pub trader_token_account: &'a AccountInfo<'info>,
+    pub trader_sol_account: &'a AccountInfo<'info>,
+    pub treasury: &'a AccountInfo<'info>,
+    pub platform_config: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub creator_vault: &'a AccountInfo<'info>,
+
+    /// Trader's referral record (`UserReferral::find_address`)
+    pub user_referral: &'a AccountInfo<'info>,
+
+    /// Referrer's referral account; required once the trader has a record
+    pub referral: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> TradeAccounts<'a, 'info> {
+    fn infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = vec![
+            self.trader.clone(),
+            self.token_state.clone(),
+            self.bonding_curve.clone(),
+            self.curve_token_account.clone(),
+            self.curve_sol_account.clone(),
+            self.trader_token_account.clone(),
+            self.trader_sol_account.clone(),
+            self.treasury.clone(),
+            self.platform_config.clone(),
+            self.mint.clone(),
+            self.token_program.clone(),
+            self.system_program.clone(),
+            self.creator_vault.clone(),
+            self.user_referral.clone(),
+        ];
+        if let Some(referral) = self.referral {
+            infos.push(referral.clone());
+        }
+        infos
+    }
+
+    /// Metas as documented on the instruction: a buyer signs and pays from
+    /// its SOL account, a seller signs and is paid into its SOL account
+    fn metas(&self, buying: bool) -> Vec<AccountMeta> {
+        let trader = if buying {
+            AccountMeta::new_readonly(*self.trader.key, true)
+        } else {
+            AccountMeta::new(*self.trader.key, true)
+        };
+        let mut metas = vec![
+            trader,
+            AccountMeta::new(*self.token_state.key, false),
+            AccountMeta::new(*self.bonding_curve.key, false),
+            AccountMeta::new(*self.curve_token_account.key, false),
+            AccountMeta::new(*self.curve_sol_account.key, false),
+            AccountMeta::new(*self.trader_token_account.key, false),
+            AccountMeta::new(*self.trader_sol_account.key, buying),
+            AccountMeta::new(*self.treasury.key, false),
+            AccountMeta::new(*self.platform_config.key, false),
+            AccountMeta::new_readonly(*self.mint.key, false),
+            AccountMeta::new_readonly(*self.token_program.key, false),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+            AccountMeta::new(*self.creator_vault.key, false),
+            AccountMeta::new(*self.user_referral.key, false),
+        ];
+        if let Some(referral) = self.referral {
+            metas.push(AccountMeta::new(*referral.key, false));
+        }
+        metas
+    }
+}
+
+/// Buy `token_amount` tokens paying at most `max_sol_amount`
+///
+/// `signer_seeds` sign for a PDA trader; pass `&[]` when the trader signed
+/// the transaction.
+pub fn buy_tokens(
+    program: &AccountInfo,
+    accounts: TradeAccounts,
+    token_amount: u64,
+    max_sol_amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<TradeResult, ProgramError> {
+    let instruction = Instruction {
+        program_id: *program.key,
+        accounts: accounts.metas(true),
+        data: AsterLaunchInstruction::BuyTokens { token_amount, max_sol_amount }.try_to_vec()?,
+    };
+    invoke_signed(&instruction, &accounts.infos(), signer_seeds)?;
+    returned(program)
+}
+
+/// Sell `token_amount` tokens for at least `min_sol_amount`
+///
+/// `signer_seeds` sign for a PDA trader; pass `&[]` when the trader signed
+/// the transaction.
+pub fn sell_tokens(
+    program: &AccountInfo,
+    accounts: TradeAccounts,
+    token_amount: u64,
+    min_sol_amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<TradeResult, ProgramError> {
+    let instruction = Instruction {
+        program_id: *program.key,
+        accounts: accounts.metas(false),
+        data: AsterLaunchInstruction::SellTokens { token_amount, min_sol_amount }.try_to_vec()?,
+    };
+    invoke_signed(&instruction, &accounts.infos(), signer_seeds)?;
+    returned(program)
+}
+
+/// Quote buying `token_amount` tokens
+pub fn quote_buy<'info>(
+    program: &AccountInfo<'info>,
+    token_state: &AccountInfo<'info>,
+    bonding_curve: &AccountInfo<'info>,
+    token_amount: u64,
+) -> Result<BuyQuote, ProgramError> {
+    query(program, token_state, bonding_curve, AsterLaunchInstruction::QuoteBuy { token_amount })
+}
+
+/// Quote selling `token_amount` tokens
+pub fn quote_sell<'info>(
+    program: &AccountInfo<'info>,
+    token_state: &AccountInfo<'info>,
+    bonding_curve: &AccountInfo<'info>,
+    token_amount: u64,
+) -> Result<SellQuote, ProgramError> {
+    query(program, token_state, bonding_curve, AsterLaunchInstruction::QuoteSell { token_amount })
+}
+
+/// Current state of a token's bonding curve
+pub fn curve_status<'info>(
+    program: &AccountInfo<'info>,
+    token_state: &AccountInfo<'info>,
+    bonding_curve: &AccountInfo<'info>,
+) -> Result<CurveStatus, ProgramError> {
+    query(program, token_state, bonding_curve, AsterLaunchInstruction::GetCurveStatus)
+}
+
+fn query<'info, T: BorshDeserialize>(
+    program: &AccountInfo<'info>,
+    token_state: &AccountInfo<'info>,
+    bonding_curve: &AccountInfo<'info>,
+    instruction: AsterLaunchInstruction,
+) -> Result<T, ProgramError> {
+    let instruction = Instruction {
+        program_id: *program.key,
+        accounts: vec![
+            AccountMeta::new_readonly(*token_state.key, false),
+            AccountMeta::new_readonly(*bonding_curve.key, false),
+        ],
+        data: instruction.try_to_vec()?,
+    };
+    invoke(&instruction, &[token_state.clone(), bonding_curve.clone()])?;
+    returned(program)
+}
+
+/// Decode the return data left by `program`
+fn returned<T: BorshDeserialize>(program: &AccountInfo) -> Result<T, ProgramError> {
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => {
+            T::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData)
+        }
+        _ => Err(AsterLaunchError::MissingReturnData.into()),
+    }
+}
@@ -1,3 +1,4 @@
+use asterlaunch_curve::CurveError;
 use solana_program::program_error::ProgramError;
 use thiserror::Error;
 
@@ -41,6 +42,51 @@ pub enum AsterLaunchError {
 
     #[error("Math Overflow")]
     MathOverflow,
+
+    #[error("Platform Paused")]
+    PlatformPaused,
+
+    #[error("Token Paused")]
+    TokenPaused,
+
+    #[error("Invalid Treasury Account")]
+    InvalidTreasury,
+
+    #[error("Invalid Account Address")]
+    InvalidAccountAddress,
+
+    #[error("Unclaimed Fees Outstanding")]
+    UnclaimedFees,
+
+    #[error("Nothing To Claim")]
+    NothingToClaim,
+
+    #[error("Invalid Referrer")]
+    InvalidReferrer,
+
+    #[error("Referrer Does Not Match Recorded Referral")]
+    ReferrerMismatch,
+
+    #[error("Bonding Curve Reserve Insolvent")]
+    ReserveInsolvent,
+
+    #[error("Missing Return Data")]
+    MissingReturnData,
+    
+    #[error("Invalid Curve Parameters")]
+    InvalidCurveParameters,
+}
+
+impl From<CurveError> for AsterLaunchError {
+    fn from(e: CurveError) -> Self {
+        match e {
+            CurveError::MathOverflow => AsterLaunchError::MathOverflow,
+            CurveError::InvalidTradeAmount => AsterLaunchError::InvalidTradeAmount,
+            CurveError::InsufficientLiquidity => AsterLaunchError::InsufficientFunds,
+            CurveError::InvalidFeeStructure => AsterLaunchError::InvalidFeeStructure,
+            CurveError::InvalidCurveParameters => AsterLaunchError::InvalidCurveParameters,
+        }
+    }
 }
 
 impl From<AsterLaunchError> for ProgramError {
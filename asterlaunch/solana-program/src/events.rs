@@ -0,0 +1,260 @@
+/*
+ * Program Events
+ *
+ * Typed events logged on token creation, every trade and graduation, so
+ * indexers never have to parse `msg!` strings.
+ *
+ * WIRE FORMAT:
+ * ============
+ * Each event is logged with `sol_log_data` as a single data field:
+ *
+ * discriminator (8 bytes) || borsh(event)
+ *
+ * The discriminator is `sha256("event:<Name>")[..8]`, the same as Anchor's
+ * `#[event]`. The Anchor program emits events with the same names and
+ * layouts, so `AsterLaunchEvent::decode` reads events from either program.
+ */
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{hash::hashv, log::sol_log_data, pubkey::Pubkey};
+
+use crate::state::TradeStats;
+
+/// Prefix of the self-CPI instruction data Anchor's `emit_cpi!` emits events in
+pub const EVENT_IX_TAG_LE: [u8; 8] = 0x1d9acb512ea545e4u64.to_le_bytes();
+
+/// An event with an Anchor-compatible discriminator
+pub trait Event: BorshSerialize + BorshDeserialize {
+    /// Event name the discriminator is derived from
+    const NAME: &'static str;
+
+    /// `sha256("event:<NAME>")[..8]`
+    fn discriminator() -> [u8; 8] {
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hashv(&[b"event:", Self::NAME.as_bytes()]).to_bytes()[..8]);
+        discriminator
+    }
+
+    /// Discriminator followed by the borsh-serialized event
+    fn data(&self) -> Vec<u8> {
+        let mut data = Self::discriminator().to_vec();
+        // Serializing into a Vec cannot fail
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Log the event
+    fn emit(&self) {
+        sol_log_data(&[&self.data()]);
+    }
+}
+
+/// Direction of a trade
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// A token was launched on a bonding curve
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TokenCreated {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+
+    /// Total supply (in base units)
+    pub total_supply: u64,
+
+    /// Mint decimals
+    pub decimals: u8,
+
+    pub timestamp: i64,
+}
+
+impl Event for TokenCreated {
+    const NAME: &'static str = "TokenCreated";
+}
+
+/// Tokens were bought from or sold to a bonding curve
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Trade {
+    pub mint: Pubkey,
+    pub trader: Pubkey,
+    pub side: TradeSide,
+
+    /// Tokens bought or sold (in base units)
+    pub token_amount: u64,
+
+    /// SOL paid by the buyer including fees, or received by the seller after fees
+    pub sol_amount: u64,
+
+    /// Fee kept by the curve as liquidity
+    pub liquidity_fee: u64,
+
+    /// Fee paid into the platform treasury
+    pub platform_fee: u64,
+
+    /// Fee paid into the token creator's vault
+    pub creator_fee: u64,
+
+    /// Fee paid to the trader's referrer
+    pub referral_fee: u64,
+
+    /// SOL held by the curve after the trade, excluding rent
+    pub sol_reserves: u64,
+
+    /// Unsold tokens held by the curve after the trade (in base units)
+    pub token_reserves: u64,
+
+    /// Spot price after the trade (lamports per whole token)
+    pub price: u64,
+
+    pub timestamp: i64,
+
+    /// The curve's trading statistics after the trade
+    pub stats: TradeStats,
+}
+
+impl Trade {
+    /// Sum of all fees
+    pub fn total_fee(&self) -> u64 {
+        self.liquidity_fee + self.platform_fee + self.creator_fee + self.referral_fee
+    }
+}
+
+impl Event for Trade {
+    const NAME: &'static str = "Trade";
+}
+
+/// A token left its bonding curve for a DEX pool
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Graduated {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+
+    /// SOL paired into the pool
+    pub sol_liquidity: u64,
+
+    /// Tokens paired into the pool (in base units); 0 from the native
+    /// program, whose curves graduate sold out
+    pub token_liquidity: u64,
+
+    pub timestamp: i64,
+}
+
+impl Event for Graduated {
+    const NAME: &'static str = "Graduated";
+}
+
+/// Any event emitted by either AsterLaunch program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsterLaunchEvent {
+    TokenCreated(TokenCreated),
+    Trade(Trade),
+    Graduated(Graduated),
+}
+
+impl AsterLaunchEvent {
+    /// Decode a logged event (discriminator followed by borsh)
+    ///
+    /// Returns `None` for data that is not an AsterLaunch event
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        let (discriminator, body) = data.split_at(8);
+
+        if discriminator == TokenCreated::discriminator() {
+            TokenCreated::try_from_slice(body).ok().map(Self::TokenCreated)
+        } else if discriminator == Trade::discriminator() {
+            Trade::try_from_slice(body).ok().map(Self::Trade)
+        } else if discriminator == Graduated::discriminator() {
+            Graduated::try_from_slice(body).ok().map(Self::Graduated)
+        } else {
+            None
+        }
+    }
+
+    /// Decode an event from the data of an `emit_cpi!` self-invocation
+    pub fn decode_cpi(instruction_data: &[u8]) -> Option<Self> {
+        instruction_data
+            .strip_prefix(&EVENT_IX_TAG_LE[..])
+            .and_then(Self::decode)
+    }
+
+    /// Decode an event from a `Program data: <base64>` log line
+    pub fn decode_log(line: &str) -> Option<Self> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let encoded = line.strip_prefix("Program data: ")?;
+        // Events are logged as a single data field
+        if encoded.contains(' ') {
+            return None;
+        }
+        Self::decode(&STANDARD.decode(encoded).ok()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade() -> Trade {
+        Trade {
+            mint: Pubkey::new_unique(),
+            trader: Pubkey::new_unique(),
+            side: TradeSide::Sell,
+            token_amount: 1_000,
+            sol_amount: 49_000_000,
+            liquidity_fee: 500_000,
+            platform_fee: 300_000,
+            creator_fee: 200_000,
+            referral_fee: 0,
+            sol_reserves: 51_000_000,
+            token_reserves: 999_000,
+            price: 110_000,
+            timestamp: 1_700_000_000,
+            stats: TradeStats {
+                trade_seq: 7,
+                buy_count: 6,
+                sell_count: 1,
+                buy_volume: 600_000_000,
+                sell_volume: 49_000_000,
+                fees_paid: 7_000_000,
+                last_trade_slot: 1_234,
+                last_price: 110_000,
+            },
+        }
+    }
+
+    #[test]
+    fn test_event_roundtrip() {
+        let trade = trade();
+        let data = trade.data();
+        assert_eq!(&data[..8], &Trade::discriminator());
+        assert_eq!(AsterLaunchEvent::decode(&data), Some(AsterLaunchEvent::Trade(trade.clone())));
+
+        let mut cpi_data = EVENT_IX_TAG_LE.to_vec();
+        cpi_data.extend_from_slice(&data);
+        assert_eq!(AsterLaunchEvent::decode_cpi(&cpi_data), Some(AsterLaunchEvent::Trade(trade)));
+
+        // Unknown discriminators and truncated events are not decoded
+        assert_eq!(AsterLaunchEvent::decode(&[0u8; 16]), None);
+        assert_eq!(AsterLaunchEvent::decode(&data[..data.len() - 1]), None);
+        assert_eq!(AsterLaunchEvent::decode_cpi(&data), None);
+    }
+
+    #[test]
+    fn test_decode_log() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let trade = trade();
+        let line = format!("Program data: {}", STANDARD.encode(trade.data()));
+        assert_eq!(AsterLaunchEvent::decode_log(&line), Some(AsterLaunchEvent::Trade(trade)));
+        assert_eq!(AsterLaunchEvent::decode_log("Program log: Instruction: BuyTokens"), None);
+    }
+}
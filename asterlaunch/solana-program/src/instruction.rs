@@ -1,14 +1,23 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
+/// A platform fee recipient and its share, as set by `SetFeeRecipients`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FeeSplit {
+    /// Account allowed to claim this share
+    pub recipient: Pubkey,
+    /// Share of the platform fee in basis points
+    pub weight_bps: u16,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum AsterLaunchInstruction {
     /// Initialize the platform
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` Platform authority
-    /// 1. `[writable]` Platform config account
-    /// 2. `[]` Treasury account
+    /// 0. `[writable, signer]` Platform authority (pays for account creation)
+    /// 1. `[writable]` Platform config account (PDA, created here)
+    /// 2. `[writable]` Treasury vault (PDA, created here)
     /// 3. `[]` System program
     /// 4. `[]` Rent sysvar
     InitializePlatform,
@@ -16,28 +25,35 @@ pub enum AsterLaunchInstruction {
     /// Create a new token with bonding curve
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` Token creator
+    /// 0. `[writable, signer]` Token creator (pays for account creation)
     /// 1. `[writable]` Platform config account
-    /// 2. `[writable]` Token state account
-    /// 3. `[writable]` Token mint
-    /// 4. `[writable]` Bonding curve state account
-    /// 5. `[writable]` Bonding curve token account
-    /// 6. `[writable]` Bonding curve SOL account
-    /// 7. `[writable]` Creator's account (pays creation fee)
-    /// 8. `[writable]` Platform treasury
+    /// 2. `[writable]` Token state account (PDA, created here)
+    /// 3. `[writable, signer]` Token mint (created here)
+    /// 4. `[writable]` Bonding curve state account (PDA, created here)
+    /// 5. `[writable]` Bonding curve token account (PDA, created here)
+    /// 6. `[writable]` Bonding curve SOL account (PDA, created here)
+    /// 7. `[writable, signer]` Creator's account (pays creation fee)
+    /// 8. `[writable]` Platform treasury vault
     /// 9. `[]` Token program
     /// 10. `[]` System program
     /// 11. `[]` Rent sysvar
+    /// 12. `[writable]` Creator fee vault (PDA, created here)
     CreateToken {
         name: String,
         ticker: String,
         description: String,
         metadata_uri: String,
+        /// Supply minted to the curve (in base units)
         total_supply: u64,
+        /// Mint decimals, at most `asterlaunch_curve::MAX_DECIMALS`; the curve
+        /// prices whole tokens of this many base units
+        decimals: u8,
     },
 
     /// Buy tokens from bonding curve
     /// 
+    /// Sets return data to a borsh `quote::TradeResult` with the fill.
+    /// 
     /// Accounts expected:
     /// 0. `[signer]` Buyer
     /// 1. `[writable]` Token state account
@@ -45,12 +61,15 @@ pub enum AsterLaunchInstruction {
     /// 3. `[writable]` Bonding curve token account
     /// 4. `[writable]` Bonding curve SOL account
     /// 5. `[writable]` Buyer's token account
-    /// 6. `[writable]` Buyer's SOL account
-    /// 7. `[writable]` Platform treasury (receives fees)
+    /// 6. `[writable, signer]` Buyer's SOL account (pays cost and fees)
+    /// 7. `[writable]` Platform treasury vault (receives fees)
     /// 8. `[writable]` Platform config
     /// 9. `[]` Token mint
     /// 10. `[]` Token program
     /// 11. `[]` System program
+    /// 12. `[writable]` Creator fee vault
+    /// 13. `[writable]` Buyer's referral record (PDA, created on first referred trade)
+    /// 14. `[writable]` Optional: referrer's referral account (required once the record exists, and must match it)
     BuyTokens {
         /// Amount of tokens to buy
         token_amount: u64,
@@ -60,19 +79,24 @@ pub enum AsterLaunchInstruction {
 
     /// Sell tokens back to bonding curve
     /// 
+    /// Sets return data to a borsh `quote::TradeResult` with the fill.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` Seller
+    /// 0. `[writable, signer]` Seller (owner of the seller's token account)
     /// 1. `[writable]` Token state account
     /// 2. `[writable]` Bonding curve state account
     /// 3. `[writable]` Bonding curve token account
     /// 4. `[writable]` Bonding curve SOL account
     /// 5. `[writable]` Seller's token account
-    /// 6. `[writable]` Seller's SOL account
-    /// 7. `[writable]` Platform treasury (receives fees)
+    /// 6. `[writable]` Seller's SOL account (receives proceeds)
+    /// 7. `[writable]` Platform treasury vault (receives fees)
     /// 8. `[writable]` Platform config
     /// 9. `[]` Token mint
     /// 10. `[]` Token program
     /// 11. `[]` System program
+    /// 12. `[writable]` Creator fee vault
+    /// 13. `[writable]` Seller's referral record (PDA, created on first referred trade)
+    /// 14. `[writable]` Optional: referrer's referral account (required once the record exists, and must match it)
     SellTokens {
         /// Amount of tokens to sell
         token_amount: u64,
@@ -81,7 +105,9 @@ pub enum AsterLaunchInstruction {
     },
 
     /// Graduate token to DEX (Raydium)
-    /// Called automatically when market cap reaches $69K
+    /// 
+    /// Fails with `GraduationThresholdNotMet` until the curve has sold out;
+    /// afterwards the curve stops trading.
     /// 
     /// Accounts expected:
     /// 0. `[signer]` Any user (permissionless)
@@ -97,4 +123,149 @@ pub enum AsterLaunchInstruction {
     /// 10. `[]` Token program
     /// 11. `[]` System program
     GraduateToDEX,
+
+    /// Pause platform operations (circuit breaker)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Platform authority
+    /// 1. `[writable]` Platform config account
+    PausePlatform {
+        /// Operations to pause (bitmask of `PlatformConfig::PAUSE_*`)
+        flags: u8,
+    },
+
+    /// Resume paused platform operations
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Platform authority
+    /// 1. `[writable]` Platform config account
+    UnpausePlatform {
+        /// Operations to resume (bitmask of `PlatformConfig::PAUSE_*`)
+        flags: u8,
+    },
+
+    /// Pause or resume trading and graduation of a single token
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Platform authority
+    /// 1. `[]` Platform config account
+    /// 2. `[writable]` Token state account
+    PauseToken {
+        /// `true` to pause, `false` to resume
+        paused: bool,
+    },
+
+    /// Withdraw collected fees from the treasury vault
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Platform authority
+    /// 1. `[]` Platform config account
+    /// 2. `[writable]` Platform treasury vault
+    /// 3. `[writable]` Destination account
+    WithdrawTreasury {
+        /// Lamports to withdraw (the vault keeps its rent-exempt minimum)
+        amount: u64,
+        /// Account receiving the lamports
+        destination: Pubkey,
+    },
+
+    /// Replace the platform fee recipients
+    /// 
+    /// Weights must sum to 10000 bps. Recipients that stay keep their
+    /// unclaimed balance; a recipient with unclaimed fees cannot be removed.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Platform authority
+    /// 1. `[writable]` Platform config account
+    SetFeeRecipients {
+        /// New fee split (at most `MAX_FEE_RECIPIENTS` entries, empty to disable)
+        recipients: Vec<FeeSplit>,
+    },
+
+    /// Claim the signer's accrued share of platform fees
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Fee recipient (receives the lamports)
+    /// 1. `[writable]` Platform config account
+    /// 2. `[writable]` Platform treasury vault
+    ClaimPlatformFees,
+
+    /// Set the creator share of the trading fee for newly created tokens
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Platform authority
+    /// 1. `[writable]` Platform config account
+    SetCreatorFee {
+        /// Basis points of each trade (at most the platform portion)
+        creator_fee_bps: u16,
+    },
+
+    /// Claim a token's accrued creator fees
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Token creator (receives the lamports)
+    /// 1. `[]` Token state account
+    /// 2. `[writable]` Bonding curve state account
+    /// 3. `[writable]` Creator fee vault
+    ClaimCreatorFees,
+
+    /// Create the referral account of the signer
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Referrer
+    /// 1. `[writable]` Referral account (PDA, created here)
+    /// 2. `[]` System program
+    RegisterReferrer,
+
+    /// Claim a referrer's accrued referral fees
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Referrer (receives the lamports)
+    /// 1. `[writable]` Referral account
+    ClaimReferralFees,
+
+    /// Set the referrer share of the platform fee
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Platform authority
+    /// 1. `[writable]` Platform config account
+    SetReferralFee {
+        /// Basis points of the platform fee
+        referral_fee_bps: u16,
+    },
+
+    /// Quote buying tokens without trading
+    /// 
+    /// Sets return data to a borsh `quote::BuyQuote`, computed exactly as
+    /// `BuyTokens` would; fails where `BuyTokens` would fail to price.
+    /// 
+    /// Accounts expected:
+    /// 0. `[]` Token state account
+    /// 1. `[]` Bonding curve state account
+    QuoteBuy {
+        /// Amount of tokens to buy
+        token_amount: u64,
+    },
+
+    /// Quote selling tokens without trading
+    /// 
+    /// Sets return data to a borsh `quote::SellQuote`, computed exactly as
+    /// `SellTokens` would.
+    /// 
+    /// Accounts expected:
+    /// 0. `[]` Token state account
+    /// 1. `[]` Bonding curve state account
+    QuoteSell {
+        /// Amount of tokens to sell
+        token_amount: u64,
+    },
+
+    /// Read a curve's price, reserves and graduation progress
+    /// 
+    /// Sets return data to a borsh `quote::CurveStatus`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[]` Token state account
+    /// 1. `[]` Bonding curve state account
+    GetCurveStatus,
 }
\ No newline at end of file
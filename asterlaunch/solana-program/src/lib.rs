@@ -13,16 +13,19 @@ pub mod processor;
 pub mod state;
 pub mod error;
 pub mod bonding_curve;
+pub mod events;
+pub mod quote;
+#[cfg(feature = "cpi")]
+pub mod cpi;
 
 use solana_program::{
     account_info::AccountInfo,
-    entrypoint,
     entrypoint::ProgramResult,
     pubkey::Pubkey,
 };
 
 #[cfg(not(feature = "no-entrypoint"))]
-entrypoint!(process_instruction);
+solana_program::entrypoint!(process_instruction);
 
 pub fn process_instruction(
     program_id: &Pubkey,
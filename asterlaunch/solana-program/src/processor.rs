@@ -4,24 +4,33 @@
  * This module processes all instructions for the AsterLaunch program.
  */
 
-use borsh::BorshDeserialize;
+use asterlaunch_curve::LinearCurve;
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
+    clock::Clock,
     msg,
-    program::invoke,
+    program::{invoke, invoke_signed, set_return_data},
     program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
+    system_instruction,
+    system_program,
     sysvar::Sysvar,
     program_error::ProgramError,
 };
 
 use crate::{
-    instruction::AsterLaunchInstruction,
-    state::{PlatformConfig, TokenState, BondingCurveState},
+    instruction::{AsterLaunchInstruction, FeeSplit},
+    state::{
+        PlatformConfig, TokenState, BondingCurveState, FeeRecipient, ReferralState,
+        UserReferral, MAX_FEE_RECIPIENTS,
+    },
     error::AsterLaunchError,
-    bonding_curve::FeeStructure,
+    bonding_curve::{self, FeeStructure},
+    events::{Event, Graduated, TokenCreated, Trade, TradeSide},
+    quote::{self, TradeResult},
 };
 
 pub struct Processor;
@@ -46,6 +55,7 @@ impl Processor {
                 description,
                 metadata_uri,
                 total_supply,
+                decimals,
             } => {
                 msg!("Instruction: CreateToken");
                 Self::process_create_token(
@@ -56,6 +66,7 @@ impl Processor {
                     description,
                     metadata_uri,
                     total_supply,
+                    decimals,
                 )
             }
             AsterLaunchInstruction::BuyTokens {
@@ -76,10 +87,69 @@ impl Processor {
                 msg!("Instruction: GraduateToDEX");
                 Self::process_graduate_to_dex(program_id, accounts)
             }
+            AsterLaunchInstruction::PausePlatform { flags } => {
+                msg!("Instruction: PausePlatform");
+                Self::process_set_platform_pause(program_id, accounts, flags, true)
+            }
+            AsterLaunchInstruction::UnpausePlatform { flags } => {
+                msg!("Instruction: UnpausePlatform");
+                Self::process_set_platform_pause(program_id, accounts, flags, false)
+            }
+            AsterLaunchInstruction::PauseToken { paused } => {
+                msg!("Instruction: PauseToken");
+                Self::process_pause_token(program_id, accounts, paused)
+            }
+            AsterLaunchInstruction::WithdrawTreasury { amount, destination } => {
+                msg!("Instruction: WithdrawTreasury");
+                Self::process_withdraw_treasury(program_id, accounts, amount, destination)
+            }
+            AsterLaunchInstruction::SetFeeRecipients { recipients } => {
+                msg!("Instruction: SetFeeRecipients");
+                Self::process_set_fee_recipients(program_id, accounts, recipients)
+            }
+            AsterLaunchInstruction::ClaimPlatformFees => {
+                msg!("Instruction: ClaimPlatformFees");
+                Self::process_claim_platform_fees(program_id, accounts)
+            }
+            AsterLaunchInstruction::SetCreatorFee { creator_fee_bps } => {
+                msg!("Instruction: SetCreatorFee");
+                Self::process_set_creator_fee(program_id, accounts, creator_fee_bps)
+            }
+            AsterLaunchInstruction::ClaimCreatorFees => {
+                msg!("Instruction: ClaimCreatorFees");
+                Self::process_claim_creator_fees(program_id, accounts)
+            }
+            AsterLaunchInstruction::RegisterReferrer => {
+                msg!("Instruction: RegisterReferrer");
+                Self::process_register_referrer(program_id, accounts)
+            }
+            AsterLaunchInstruction::ClaimReferralFees => {
+                msg!("Instruction: ClaimReferralFees");
+                Self::process_claim_referral_fees(program_id, accounts)
+            }
+            AsterLaunchInstruction::SetReferralFee { referral_fee_bps } => {
+                msg!("Instruction: SetReferralFee");
+                Self::process_set_referral_fee(program_id, accounts, referral_fee_bps)
+            }
+            AsterLaunchInstruction::QuoteBuy { token_amount } => {
+                msg!("Instruction: QuoteBuy");
+                let curve = Self::load_quoted_curve(program_id, accounts)?;
+                Self::return_data(&quote::quote_buy(&curve, token_amount)?)
+            }
+            AsterLaunchInstruction::QuoteSell { token_amount } => {
+                msg!("Instruction: QuoteSell");
+                let curve = Self::load_quoted_curve(program_id, accounts)?;
+                Self::return_data(&quote::quote_sell(&curve, token_amount)?)
+            }
+            AsterLaunchInstruction::GetCurveStatus => {
+                msg!("Instruction: GetCurveStatus");
+                let curve = Self::load_quoted_curve(program_id, accounts)?;
+                Self::return_data(&quote::curve_status(&curve)?)
+            }
         }
     }
 
-    /// Initialize the platform configuration
+    /// Initialize the platform configuration and treasury vault
     fn process_initialize_platform(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
@@ -89,14 +159,44 @@ impl Processor {
         let authority = next_account_info(account_info_iter)?;
         let config_account = next_account_info(account_info_iter)?;
         let treasury = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
         
         // Verify authority is signer
         if !authority.is_signer {
             return Err(AsterLaunchError::Unauthorized.into());
         }
         
+        let (config_address, config_bump) = PlatformConfig::find_address(program_id);
+        Self::assert_address(config_account, &config_address)?;
+        
+        let (treasury_address, treasury_bump) = PlatformConfig::find_treasury_address(program_id);
+        if *treasury.key != treasury_address {
+            return Err(AsterLaunchError::InvalidTreasury.into());
+        }
+        
+        Self::create_pda_account(
+            authority,
+            config_account,
+            PlatformConfig::LEN,
+            program_id,
+            system_program_account,
+            &[&[PlatformConfig::SEED, &[config_bump]]],
+        )?;
+        
+        // The treasury holds no data; it is program-owned so withdrawals
+        // can only happen through `WithdrawTreasury`
+        Self::create_pda_account(
+            authority,
+            treasury,
+            0,
+            program_id,
+            system_program_account,
+            &[&[PlatformConfig::TREASURY_SEED, &[treasury_bump]]],
+        )?;
+        
         // Create platform config
-        let config = PlatformConfig::new(*authority.key, *treasury.key);
+        let config = PlatformConfig::new(*authority.key, treasury_address);
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
         
         msg!("Platform initialized successfully");
         msg!("Authority: {}", authority.key);
@@ -108,6 +208,7 @@ impl Processor {
     }
 
     /// Create a new token with bonding curve
+    #[allow(clippy::too_many_arguments)]
     fn process_create_token(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
@@ -116,7 +217,29 @@ impl Processor {
         description: String,
         metadata_uri: String,
         total_supply: u64,
+        decimals: u8,
     ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let creator = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let token_state_account = next_account_info(account_info_iter)?;
+        let mint = next_account_info(account_info_iter)?;
+        let bonding_curve_account = next_account_info(account_info_iter)?;
+        let curve_token_account = next_account_info(account_info_iter)?;
+        let curve_sol_account = next_account_info(account_info_iter)?;
+        let creator_fee_account = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
+        let _rent_sysvar = next_account_info(account_info_iter)?;
+        let creator_vault = next_account_info(account_info_iter)?;
+        
+        let mut config = Self::load_platform_config(program_id, config_account)?;
+        if config.is_paused(PlatformConfig::PAUSE_CREATIONS) {
+            return Err(AsterLaunchError::PlatformPaused.into());
+        }
+        
         msg!("Creating token: {} ({})", name, ticker);
         msg!("Total supply: {}", total_supply);
         msg!("Description: {}", description);
@@ -128,14 +251,207 @@ impl Processor {
         if ticker.len() > TokenState::MAX_TICKER_LEN {
             return Err(AsterLaunchError::InvalidTokenMetadata.into());
         }
+        if description.len() > TokenState::MAX_DESCRIPTION_LEN {
+            return Err(AsterLaunchError::InvalidTokenMetadata.into());
+        }
+        if metadata_uri.len() > TokenState::MAX_URI_LEN {
+            return Err(AsterLaunchError::InvalidTokenMetadata.into());
+        }
+        if total_supply == 0 {
+            return Err(AsterLaunchError::InvalidTradeAmount.into());
+        }
         
-        // Initialize bonding curve with default parameters
+        // Default curve parameters, in lamports per whole token; the
+        // increment is fixed point at the mint's decimals
         let initial_price = 100_000; // 0.0001 SOL per token
-        let price_increment = 10; // Very small increment per token
+        let price_increment = 10u64
+            .checked_pow(decimals as u32)
+            .and_then(|scale| scale.checked_mul(10)) // Very small increment per token
+            .ok_or(AsterLaunchError::InvalidCurveParameters)?;
+        
+        // Selling out the whole supply must be priceable, so no later
+        // trade can overflow the curve math
+        let pricing = LinearCurve {
+            initial_price,
+            price_increment,
+            total_supply,
+            tokens_sold: 0,
+            decimals,
+        };
+        let fees = FeeStructure::with_creator_fee(config.creator_fee_bps).map_err(AsterLaunchError::from)?;
+        pricing.validate(&fees).map_err(AsterLaunchError::from)?;
+        
+        if !creator.is_signer || !mint.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if *token_program.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if *treasury.key != config.treasury {
+            return Err(AsterLaunchError::InvalidTreasury.into());
+        }
+        if !token_state_account.data_is_empty() {
+            return Err(AsterLaunchError::TokenAlreadyExists.into());
+        }
+        
+        let (token_state_address, token_state_bump) = TokenState::find_address(program_id, mint.key);
+        Self::assert_address(token_state_account, &token_state_address)?;
+        let (curve_address, curve_bump) = BondingCurveState::find_address(program_id, mint.key);
+        Self::assert_address(bonding_curve_account, &curve_address)?;
+        let (token_reserve_address, token_reserve_bump) =
+            BondingCurveState::find_token_reserve_address(program_id, mint.key);
+        Self::assert_address(curve_token_account, &token_reserve_address)?;
+        let (sol_reserve_address, sol_reserve_bump) =
+            BondingCurveState::find_sol_reserve_address(program_id, mint.key);
+        Self::assert_address(curve_sol_account, &sol_reserve_address)?;
+        let (creator_vault_address, creator_vault_bump) =
+            BondingCurveState::find_creator_vault_address(program_id, mint.key);
+        Self::assert_address(creator_vault, &creator_vault_address)?;
+        
+        // Create the mint with the bonding curve PDA as mint authority
+        Self::create_pda_account(
+            creator,
+            mint,
+            spl_token::state::Mint::LEN,
+            &spl_token::id(),
+            system_program_account,
+            &[],
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                mint.key,
+                &curve_address,
+                None,
+                decimals,
+            )?,
+            &[mint.clone(), token_program.clone()],
+        )?;
+        
+        // Create program accounts
+        Self::create_pda_account(
+            creator,
+            token_state_account,
+            TokenState::LEN,
+            program_id,
+            system_program_account,
+            &[&[TokenState::SEED, mint.key.as_ref(), &[token_state_bump]]],
+        )?;
+        Self::create_pda_account(
+            creator,
+            bonding_curve_account,
+            BondingCurveState::LEN,
+            program_id,
+            system_program_account,
+            &[&[BondingCurveState::SEED, mint.key.as_ref(), &[curve_bump]]],
+        )?;
+        Self::create_pda_account(
+            creator,
+            curve_sol_account,
+            0,
+            program_id,
+            system_program_account,
+            &[&[BondingCurveState::SOL_RESERVE_SEED, mint.key.as_ref(), &[sol_reserve_bump]]],
+        )?;
+        Self::create_pda_account(
+            creator,
+            creator_vault,
+            0,
+            program_id,
+            system_program_account,
+            &[&[BondingCurveState::CREATOR_VAULT_SEED, mint.key.as_ref(), &[creator_vault_bump]]],
+        )?;
+        
+        // Create the curve's token reserve and mint the full supply into it
+        Self::create_pda_account(
+            creator,
+            curve_token_account,
+            spl_token::state::Account::LEN,
+            &spl_token::id(),
+            system_program_account,
+            &[&[BondingCurveState::TOKEN_RESERVE_SEED, mint.key.as_ref(), &[token_reserve_bump]]],
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                curve_token_account.key,
+                mint.key,
+                &curve_address,
+            )?,
+            &[curve_token_account.clone(), mint.clone(), token_program.clone()],
+        )?;
+        invoke_signed(
+            &spl_token::instruction::mint_to(
+                &spl_token::id(),
+                mint.key,
+                curve_token_account.key,
+                &curve_address,
+                &[],
+                total_supply,
+            )?,
+            &[mint.clone(), curve_token_account.clone(), bonding_curve_account.clone(), token_program.clone()],
+            &[&[BondingCurveState::SEED, mint.key.as_ref(), &[curve_bump]]],
+        )?;
+        
+        // Collect creation fee into the treasury vault
+        Self::transfer_lamports(
+            creator_fee_account,
+            treasury,
+            system_program_account,
+            config.creation_fee_lamports,
+        )?;
+        
+        // Initialize bonding curve with the validated parameters
+        let curve = BondingCurveState {
+            token_mint: *mint.key,
+            token_reserve: token_reserve_address,
+            sol_reserve: sol_reserve_address,
+            ..BondingCurveState::from_curve(&pricing, config.creator_fee_bps)
+        };
+        curve.serialize(&mut &mut bonding_curve_account.data.borrow_mut()[..])?;
+        
+        let token_state = TokenState {
+            mint: *mint.key,
+            creator: *creator.key,
+            bonding_curve: curve_address,
+            name,
+            ticker,
+            description,
+            metadata_uri,
+            total_supply,
+            circulating_supply: 0,
+            is_graduated: false,
+            created_at: Clock::get()?.unix_timestamp,
+            graduated_at: 0,
+            is_paused: false,
+        };
+        token_state.serialize(&mut &mut token_state_account.data.borrow_mut()[..])?;
+        
+        TokenCreated {
+            mint: token_state.mint,
+            creator: token_state.creator,
+            bonding_curve: token_state.bonding_curve,
+            name: token_state.name.clone(),
+            symbol: token_state.ticker.clone(),
+            uri: token_state.metadata_uri.clone(),
+            total_supply,
+            decimals,
+            timestamp: token_state.created_at,
+        }
+        .emit();
+        
+        config.total_tokens_created = config.total_tokens_created
+            .checked_add(1)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        config.total_fees_collected = config.total_fees_collected
+            .checked_add(config.creation_fee_lamports)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
         
         msg!("Bonding curve initialized");
-        msg!("Initial price: {} lamports", initial_price);
-        msg!("Price increment: {} lamports per token", price_increment);
+        msg!("Initial price: {} lamports per token", initial_price);
+        msg!("Price increment: {} (10^-{} lamports) per token sold", price_increment, decimals);
+        msg!("Decimals: {}", decimals);
         msg!("Token created successfully!");
         
         Ok(())
@@ -148,26 +464,169 @@ impl Processor {
         token_amount: u64,
         max_sol_amount: u64,
     ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let buyer = next_account_info(account_info_iter)?;
+        let token_state_account = next_account_info(account_info_iter)?;
+        let bonding_curve_account = next_account_info(account_info_iter)?;
+        let curve_token_account = next_account_info(account_info_iter)?;
+        let curve_sol_account = next_account_info(account_info_iter)?;
+        let buyer_token_account = next_account_info(account_info_iter)?;
+        let buyer_sol_account = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let _mint = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
+        let creator_vault = next_account_info(account_info_iter)?;
+        let user_referral_account = next_account_info(account_info_iter)?;
+        let referral_account = next_account_info(account_info_iter).ok();
+        
+        let mut config = Self::load_platform_config(program_id, config_account)?;
+        let mut token_state = Self::load_token_state(program_id, token_state_account)?;
+        Self::assert_not_paused(&config, &token_state, PlatformConfig::PAUSE_BUYS)?;
+        
+        let (mut curve, curve_bump) = Self::load_bonding_curve(
+            program_id,
+            bonding_curve_account,
+            curve_token_account,
+            curve_sol_account,
+            &token_state,
+        )?;
+        Self::assert_trade_accounts(&config, treasury, token_program)?;
+        Self::assert_creator_vault(program_id, creator_vault, &curve)?;
+        Self::sync_sol_reserve(&mut curve, curve_sol_account)?;
+        
         msg!("Buying {} tokens", token_amount);
         
-        if token_amount == 0 {
-            return Err(AsterLaunchError::InvalidTradeAmount.into());
+        // Price on the bonding curve; fees are charged on top
+        let quote = quote::quote_buy(&curve, token_amount)?;
+        let (cost, fees, total_cost) = (quote.cost, quote.fees, quote.total_cost);
+        
+        // Apply slippage protection
+        if total_cost > max_sol_amount {
+            return Err(AsterLaunchError::SlippageExceeded.into());
         }
         
-        // Calculate price based on bonding curve
-        // This would use BondingCurveState::calculate_buy_price()
+        // The buyer's referral record is keyed on this account, so only the
+        // buyer may attribute their trades
+        if !buyer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
         
-        let fees = FeeStructure::default();
-        msg!("Trading fee: {}bps", fees.total_fee_bps);
+        // The referrer, if any, takes its cut out of the platform fee
+        let mut referral = Self::resolve_referral(
+            program_id,
+            buyer.key,
+            buyer_sol_account,
+            user_referral_account,
+            referral_account,
+            system_program_account,
+        )?;
+        let referral_fee = match referral {
+            Some(_) => config.referral_fee(fees.platform_fee)?,
+            None => 0,
+        };
+        let treasury_fee = fees.platform_fee - referral_fee;
         
-        // Apply slippage protection
-        // Transfer SOL from buyer
-        // Mint tokens to buyer
-        // Add fees to liquidity and treasury
+        // Transfer SOL from buyer: cost and liquidity fee to the curve,
+        // platform fee to the treasury vault, creator fee to the creator vault
+        Self::transfer_lamports(
+            buyer_sol_account,
+            curve_sol_account,
+            system_program_account,
+            cost.checked_add(fees.liquidity_fee).ok_or(AsterLaunchError::MathOverflow)?,
+        )?;
+        Self::transfer_lamports(buyer_sol_account, treasury, system_program_account, treasury_fee)?;
+        Self::transfer_lamports(buyer_sol_account, creator_vault, system_program_account, fees.creator_fee)?;
+        if let (Some(referral), Some(referral_account)) = (referral.as_mut(), referral_account) {
+            Self::transfer_lamports(buyer_sol_account, referral_account, system_program_account, referral_fee)?;
+            Self::record_referral_trade(referral_account, referral, referral_fee, cost)?;
+        }
+        
+        // Transfer tokens from the curve reserve to the buyer
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                curve_token_account.key,
+                buyer_token_account.key,
+                bonding_curve_account.key,
+                &[],
+                token_amount,
+            )?,
+            &[
+                curve_token_account.clone(),
+                buyer_token_account.clone(),
+                bonding_curve_account.clone(),
+                token_program.clone(),
+            ],
+            &[&[BondingCurveState::SEED, curve.token_mint.as_ref(), &[curve_bump]]],
+        )?;
+        
+        curve.tokens_sold = curve.tokens_sold
+            .checked_add(token_amount)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        curve.sol_collected = curve.sol_collected
+            .checked_add(cost)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        
+        // Half of the cost backs sells, the rest and the liquidity fee are locked
+        let (redeemable, liquidity) = bonding_curve::split_liquidity(cost)?;
+        curve.redeemable_sol = curve.redeemable_sol
+            .checked_add(redeemable)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        curve.liquidity_sol = curve.liquidity_sol
+            .checked_add(liquidity)
+            .and_then(|l| l.checked_add(fees.liquidity_fee))
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        curve.creator_fees_accrued = curve.creator_fees_accrued
+            .checked_add(fees.creator_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        Self::assert_solvent(&curve, curve_sol_account)?;
+        
+        let clock = Clock::get()?;
+        // The referral fee is part of the platform fee
+        let fees_paid = fees.total_fee();
+        curve.stats.record(TradeSide::Buy, total_cost, fees_paid, clock.slot, curve.spot_price()?)?;
+        curve.serialize(&mut &mut bonding_curve_account.data.borrow_mut()[..])?;
         
+        token_state.circulating_supply = curve.tokens_sold;
+        token_state.serialize(&mut &mut token_state_account.data.borrow_mut()[..])?;
+        
+        config.total_fees_collected = config.total_fees_collected
+            .checked_add(treasury_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        config.accrue_platform_fee(treasury_fee)?;
+        config.record_trade(total_cost, fees_paid)?;
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+        
+        Trade {
+            mint: curve.token_mint,
+            trader: *buyer.key,
+            side: TradeSide::Buy,
+            token_amount,
+            sol_amount: total_cost,
+            liquidity_fee: fees.liquidity_fee,
+            platform_fee: treasury_fee,
+            creator_fee: fees.creator_fee,
+            referral_fee,
+            sol_reserves: curve.reserve_balance()?,
+            token_reserves: curve.total_supply - curve.tokens_sold,
+            price: curve.stats.last_price,
+            timestamp: clock.unix_timestamp,
+            stats: curve.stats,
+        }
+        .emit();
+        
+        msg!("Bought {} tokens for {} lamports (fees: {} lamports)", token_amount, cost, fees.total_fee());
         msg!("Tokens purchased successfully!");
         
-        Ok(())
+        Self::return_data(&TradeResult {
+            tokens: token_amount,
+            sol: total_cost,
+            fees: fees_paid,
+            new_price: curve.stats.last_price,
+        })
     }
 
     /// Sell tokens back to the bonding curve
@@ -177,25 +636,177 @@ impl Processor {
         token_amount: u64,
         min_sol_amount: u64,
     ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let seller = next_account_info(account_info_iter)?;
+        let token_state_account = next_account_info(account_info_iter)?;
+        let bonding_curve_account = next_account_info(account_info_iter)?;
+        let curve_token_account = next_account_info(account_info_iter)?;
+        let curve_sol_account = next_account_info(account_info_iter)?;
+        let seller_token_account = next_account_info(account_info_iter)?;
+        let seller_sol_account = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let _mint = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
+        let creator_vault = next_account_info(account_info_iter)?;
+        let user_referral_account = next_account_info(account_info_iter)?;
+        let referral_account = next_account_info(account_info_iter).ok();
+        
+        let mut config = Self::load_platform_config(program_id, config_account)?;
+        let mut token_state = Self::load_token_state(program_id, token_state_account)?;
+        Self::assert_not_paused(&config, &token_state, PlatformConfig::PAUSE_SELLS)?;
+        
+        let (mut curve, _) = Self::load_bonding_curve(
+            program_id,
+            bonding_curve_account,
+            curve_token_account,
+            curve_sol_account,
+            &token_state,
+        )?;
+        Self::assert_trade_accounts(&config, treasury, token_program)?;
+        Self::assert_creator_vault(program_id, creator_vault, &curve)?;
+        Self::sync_sol_reserve(&mut curve, curve_sol_account)?;
+        
         msg!("Selling {} tokens", token_amount);
         
-        if token_amount == 0 {
-            return Err(AsterLaunchError::InvalidTradeAmount.into());
+        // Value the tokens on the bonding curve; the seller redeems half of
+        // that value and the fees on the full value come out of it
+        let quote = quote::quote_sell(&curve, token_amount)?;
+        let (proceeds, fees, redeemed, sol_out) = (quote.proceeds, quote.fees, quote.redeemed, quote.sol_out);
+        
+        // Apply slippage protection
+        if sol_out < min_sol_amount {
+            return Err(AsterLaunchError::SlippageExceeded.into());
         }
         
-        // Calculate sell price based on bonding curve
-        // This would use BondingCurveState::calculate_sell_price()
+        // The referrer, if any, takes its cut out of the platform fee
+        let mut referral = Self::resolve_referral(
+            program_id,
+            seller.key,
+            seller,
+            user_referral_account,
+            referral_account,
+            system_program_account,
+        )?;
+        let referral_fee = match referral {
+            Some(_) => config.referral_fee(fees.platform_fee)?,
+            None => 0,
+        };
+        let treasury_fee = fees.platform_fee - referral_fee;
         
-        let fees = FeeStructure::default();
+        // Return tokens from the seller to the curve reserve
+        invoke(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                seller_token_account.key,
+                curve_token_account.key,
+                seller.key,
+                &[],
+                token_amount,
+            )?,
+            &[
+                seller_token_account.clone(),
+                curve_token_account.clone(),
+                seller.clone(),
+                token_program.clone(),
+            ],
+        )?;
         
-        // Apply slippage protection
-        // Burn tokens from seller
-        // Transfer SOL to seller (minus fees)
-        // Add fees to treasury
+        // Pay the seller, the treasury vault and the creator vault out of the
+        // curve's SOL reserve; the liquidity fee stays in the reserve
+        let payout = sol_out
+            .checked_add(fees.platform_fee)
+            .and_then(|p| p.checked_add(fees.creator_fee))
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        **curve_sol_account.try_borrow_mut_lamports()? = curve_sol_account
+            .lamports()
+            .checked_sub(payout)
+            .ok_or(AsterLaunchError::InsufficientFunds)?;
+        **seller_sol_account.try_borrow_mut_lamports()? = seller_sol_account
+            .lamports()
+            .checked_add(sol_out)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        **treasury.try_borrow_mut_lamports()? = treasury
+            .lamports()
+            .checked_add(treasury_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        **creator_vault.try_borrow_mut_lamports()? = creator_vault
+            .lamports()
+            .checked_add(fees.creator_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        if let (Some(referral), Some(referral_account)) = (referral.as_mut(), referral_account) {
+            **referral_account.try_borrow_mut_lamports()? = referral_account
+                .lamports()
+                .checked_add(referral_fee)
+                .ok_or(AsterLaunchError::MathOverflow)?;
+            Self::record_referral_trade(referral_account, referral, referral_fee, proceeds)?;
+        }
         
+        curve.tokens_sold = curve.tokens_sold
+            .checked_sub(token_amount)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        curve.sol_collected = curve.sol_collected
+            .checked_sub(proceeds)
+            .ok_or(AsterLaunchError::InsufficientFunds)?;
+        
+        // The redeemed half leaves the redeemable reserve; its liquidity fee
+        // stays behind as locked liquidity
+        curve.redeemable_sol = curve.redeemable_sol
+            .checked_sub(redeemed)
+            .ok_or(AsterLaunchError::InsufficientFunds)?;
+        curve.liquidity_sol = curve.liquidity_sol
+            .checked_add(fees.liquidity_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        curve.creator_fees_accrued = curve.creator_fees_accrued
+            .checked_add(fees.creator_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        Self::assert_solvent(&curve, curve_sol_account)?;
+        
+        let clock = Clock::get()?;
+        // The referral fee is part of the platform fee
+        let fees_paid = fees.total_fee();
+        curve.stats.record(TradeSide::Sell, sol_out, fees_paid, clock.slot, curve.spot_price()?)?;
+        curve.serialize(&mut &mut bonding_curve_account.data.borrow_mut()[..])?;
+        
+        token_state.circulating_supply = curve.tokens_sold;
+        token_state.serialize(&mut &mut token_state_account.data.borrow_mut()[..])?;
+        
+        config.total_fees_collected = config.total_fees_collected
+            .checked_add(treasury_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        config.accrue_platform_fee(treasury_fee)?;
+        config.record_trade(sol_out, fees_paid)?;
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+        
+        Trade {
+            mint: curve.token_mint,
+            trader: *seller.key,
+            side: TradeSide::Sell,
+            token_amount,
+            sol_amount: sol_out,
+            liquidity_fee: fees.liquidity_fee,
+            platform_fee: treasury_fee,
+            creator_fee: fees.creator_fee,
+            referral_fee,
+            sol_reserves: curve.reserve_balance()?,
+            token_reserves: curve.total_supply - curve.tokens_sold,
+            price: curve.stats.last_price,
+            timestamp: clock.unix_timestamp,
+            stats: curve.stats,
+        }
+        .emit();
+        
+        msg!("Sold {} tokens for {} lamports (fees: {} lamports)", token_amount, sol_out, fees.total_fee());
         msg!("Tokens sold successfully!");
         
-        Ok(())
+        Self::return_data(&TradeResult {
+            tokens: token_amount,
+            sol: sol_out,
+            fees: fees_paid,
+            new_price: curve.stats.last_price,
+        })
     }
 
     /// Graduate token to DEX when threshold is reached
@@ -203,22 +814,783 @@ impl Processor {
         program_id: &Pubkey,
         accounts: &[AccountInfo],
     ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let _payer = next_account_info(account_info_iter)?;
+        let token_state_account = next_account_info(account_info_iter)?;
+        let bonding_curve_account = next_account_info(account_info_iter)?;
+        let curve_token_account = next_account_info(account_info_iter)?;
+        let curve_sol_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        
+        let config = Self::load_platform_config(program_id, config_account)?;
+        let mut token_state = Self::load_token_state(program_id, token_state_account)?;
+        Self::assert_not_paused(&config, &token_state, PlatformConfig::PAUSE_GRADUATIONS)?;
+        
+        let (mut curve, _) = Self::load_bonding_curve(
+            program_id,
+            bonding_curve_account,
+            curve_token_account,
+            curve_sol_account,
+            &token_state,
+        )?;
+        
         msg!("Initiating DEX graduation");
         
-        // Check if graduation threshold is met
-        // Market cap must be >= $69,000
+        if !curve.is_active || curve.has_graduated {
+            return Err(AsterLaunchError::TokenAlreadyGraduated.into());
+        }
+        
+        // Without a SOL price feed the USD market cap is unknown, so the
+        // curve graduates once it has sold out
+        if curve.tokens_sold < curve.total_supply {
+            return Err(AsterLaunchError::GraduationThresholdNotMet.into());
+        }
         
         msg!("Graduation threshold reached!");
         
         // 1. Calculate liquidity amounts
+        let (token_liquidity, sol_liquidity) = bonding_curve::calculate_graduation_liquidity(
+            curve.total_supply
+                .checked_sub(curve.tokens_sold)
+                .ok_or(AsterLaunchError::MathOverflow)?,
+            curve.liquidity_sol,
+            curve.redeemable_sol,
+        )?;
+        msg!("Pool liquidity: {} tokens, {} lamports", token_liquidity, sol_liquidity);
+        
         // 2. Create Raydium pool
         // 3. Add liquidity (all remaining tokens + accumulated SOL)
         // 4. Burn LP tokens to lock liquidity permanently
-        // 5. Mark bonding curve as graduated
+        
+        // 5. Mark bonding curve as graduated; trading stops here
+        let timestamp = Clock::get()?.unix_timestamp;
+        curve.is_active = false;
+        curve.has_graduated = true;
+        curve.serialize(&mut &mut bonding_curve_account.data.borrow_mut()[..])?;
+        
+        token_state.is_graduated = true;
+        token_state.graduated_at = timestamp;
+        token_state.serialize(&mut &mut token_state_account.data.borrow_mut()[..])?;
+        
+        Graduated {
+            mint: curve.token_mint,
+            bonding_curve: *bonding_curve_account.key,
+            sol_liquidity,
+            token_liquidity,
+            timestamp,
+        }
+        .emit();
         
         msg!("Token graduated to DEX successfully!");
         msg!("Liquidity permanently locked");
         
         Ok(())
     }
+
+    /// Set or clear platform-wide pause flags
+    fn process_set_platform_pause(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        flags: u8,
+        paused: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let authority = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        
+        let mut config = Self::load_platform_config(program_id, config_account)?;
+        Self::assert_authority(&config, authority)?;
+        
+        if flags & !PlatformConfig::PAUSE_ALL != 0 {
+            return Err(AsterLaunchError::InvalidInstruction.into());
+        }
+        
+        if paused {
+            config.pause_flags |= flags;
+        } else {
+            config.pause_flags &= !flags;
+        }
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+        
+        msg!("Pause flags: {:#06b}", config.pause_flags);
+        
+        Ok(())
+    }
+
+    /// Pause or resume a single token
+    fn process_pause_token(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let authority = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let token_state_account = next_account_info(account_info_iter)?;
+        
+        let config = Self::load_platform_config(program_id, config_account)?;
+        Self::assert_authority(&config, authority)?;
+        
+        let mut token_state = Self::load_token_state(program_id, token_state_account)?;
+        token_state.is_paused = paused;
+        token_state.serialize(&mut &mut token_state_account.data.borrow_mut()[..])?;
+        
+        msg!("Token {} paused: {}", token_state.mint, paused);
+        
+        Ok(())
+    }
+
+    /// Withdraw lamports from the treasury vault to a destination account
+    fn process_withdraw_treasury(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        destination: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let authority = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
+        let destination_account = next_account_info(account_info_iter)?;
+        
+        let config = Self::load_platform_config(program_id, config_account)?;
+        Self::assert_authority(&config, authority)?;
+        
+        if *treasury.key != config.treasury || treasury.owner != program_id {
+            return Err(AsterLaunchError::InvalidTreasury.into());
+        }
+        Self::assert_address(destination_account, &destination)?;
+        
+        if amount == 0 {
+            return Err(AsterLaunchError::InvalidTradeAmount.into());
+        }
+        
+        // The vault must stay rent exempt, and fees accrued to recipients
+        // are theirs to claim
+        let rent_minimum = Rent::get()?.minimum_balance(treasury.data_len());
+        let available = treasury
+            .lamports()
+            .saturating_sub(rent_minimum)
+            .saturating_sub(config.total_fees_accrued()?);
+        if amount > available {
+            return Err(AsterLaunchError::InsufficientFunds.into());
+        }
+        
+        **treasury.try_borrow_mut_lamports()? -= amount;
+        **destination_account.try_borrow_mut_lamports()? = destination_account
+            .lamports()
+            .checked_add(amount)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        
+        msg!("Withdrew {} lamports from treasury to {}", amount, destination);
+        
+        Ok(())
+    }
+
+    /// Replace the platform fee recipients
+    fn process_set_fee_recipients(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        recipients: Vec<FeeSplit>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let authority = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        
+        let mut config = Self::load_platform_config(program_id, config_account)?;
+        Self::assert_authority(&config, authority)?;
+        
+        // Validate the new split
+        if recipients.len() > MAX_FEE_RECIPIENTS {
+            return Err(AsterLaunchError::InvalidFeeStructure.into());
+        }
+        let total_weight = recipients
+            .iter()
+            .map(|r| r.weight_bps as u32)
+            .sum::<u32>();
+        if !recipients.is_empty() && total_weight != 10000 {
+            return Err(AsterLaunchError::InvalidFeeStructure.into());
+        }
+        for (i, split) in recipients.iter().enumerate() {
+            if split.weight_bps == 0 || recipients[..i].iter().any(|r| r.recipient == split.recipient) {
+                return Err(AsterLaunchError::InvalidFeeStructure.into());
+            }
+        }
+        
+        // Carry over unclaimed balances; removing a recipient that is still
+        // owed fees is not allowed
+        let mut fee_recipients = [FeeRecipient::default(); MAX_FEE_RECIPIENTS];
+        for (slot, split) in fee_recipients.iter_mut().zip(&recipients) {
+            let accrued = config
+                .fee_recipients()
+                .iter()
+                .find(|r| r.recipient == split.recipient)
+                .map_or(0, |r| r.accrued);
+            *slot = FeeRecipient {
+                recipient: split.recipient,
+                weight_bps: split.weight_bps,
+                accrued,
+            };
+        }
+        for old in config.fee_recipients() {
+            if old.accrued > 0 && !recipients.iter().any(|r| r.recipient == old.recipient) {
+                return Err(AsterLaunchError::UnclaimedFees.into());
+            }
+        }
+        
+        config.fee_recipients = fee_recipients;
+        config.fee_recipient_count = recipients.len() as u8;
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+        
+        for r in config.fee_recipients() {
+            msg!("Fee recipient {}: {}bps", r.recipient, r.weight_bps);
+        }
+        
+        Ok(())
+    }
+
+    /// Pay a fee recipient its accrued share from the treasury vault
+    fn process_claim_platform_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let recipient = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
+        
+        if !recipient.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let mut config = Self::load_platform_config(program_id, config_account)?;
+        if *treasury.key != config.treasury || treasury.owner != program_id {
+            return Err(AsterLaunchError::InvalidTreasury.into());
+        }
+        
+        let count = config.fee_recipient_count as usize;
+        let entry = config.fee_recipients[..count]
+            .iter_mut()
+            .find(|r| r.recipient == *recipient.key)
+            .ok_or(AsterLaunchError::Unauthorized)?;
+        let amount = entry.accrued;
+        if amount == 0 {
+            return Err(AsterLaunchError::NothingToClaim.into());
+        }
+        entry.accrued = 0;
+        
+        **treasury.try_borrow_mut_lamports()? = treasury
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(AsterLaunchError::InsufficientFunds)?;
+        **recipient.try_borrow_mut_lamports()? = recipient
+            .lamports()
+            .checked_add(amount)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+        
+        msg!("Claimed {} lamports of platform fees for {}", amount, recipient.key);
+        
+        Ok(())
+    }
+
+    /// Set the creator fee share applied to newly created tokens
+    fn process_set_creator_fee(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        creator_fee_bps: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let authority = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        
+        let mut config = Self::load_platform_config(program_id, config_account)?;
+        Self::assert_authority(&config, authority)?;
+        
+        // Must fit inside the platform portion of the fee
+        FeeStructure::with_creator_fee(creator_fee_bps).map_err(AsterLaunchError::from)?;
+        
+        config.creator_fee_bps = creator_fee_bps;
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+        
+        msg!("Creator fee: {}bps", creator_fee_bps);
+        
+        Ok(())
+    }
+
+    /// Pay a token creator the fees accrued in its creator vault
+    fn process_claim_creator_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let creator = next_account_info(account_info_iter)?;
+        let token_state_account = next_account_info(account_info_iter)?;
+        let bonding_curve_account = next_account_info(account_info_iter)?;
+        let creator_vault = next_account_info(account_info_iter)?;
+        
+        let token_state = Self::load_token_state(program_id, token_state_account)?;
+        if !creator.is_signer || *creator.key != token_state.creator {
+            return Err(AsterLaunchError::Unauthorized.into());
+        }
+        
+        let (curve_address, _) = BondingCurveState::find_address(program_id, &token_state.mint);
+        Self::assert_address(bonding_curve_account, &curve_address)?;
+        if bonding_curve_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut curve = BondingCurveState::deserialize(&mut &bonding_curve_account.data.borrow()[..])?;
+        Self::assert_creator_vault(program_id, creator_vault, &curve)?;
+        
+        let amount = curve.creator_fees_accrued;
+        if amount == 0 {
+            return Err(AsterLaunchError::NothingToClaim.into());
+        }
+        curve.creator_fees_accrued = 0;
+        
+        **creator_vault.try_borrow_mut_lamports()? = creator_vault
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(AsterLaunchError::InsufficientFunds)?;
+        **creator.try_borrow_mut_lamports()? = creator
+            .lamports()
+            .checked_add(amount)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        curve.serialize(&mut &mut bonding_curve_account.data.borrow_mut()[..])?;
+        
+        msg!("Claimed {} lamports of creator fees for {}", amount, token_state.mint);
+        
+        Ok(())
+    }
+
+    /// Create the signer's referral account
+    fn process_register_referrer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let referrer = next_account_info(account_info_iter)?;
+        let referral_account = next_account_info(account_info_iter)?;
+        let system_program_account = next_account_info(account_info_iter)?;
+        
+        if !referrer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let (referral_address, referral_bump) = ReferralState::find_address(program_id, referrer.key);
+        Self::assert_address(referral_account, &referral_address)?;
+        
+        Self::create_pda_account(
+            referrer,
+            referral_account,
+            ReferralState::LEN,
+            program_id,
+            system_program_account,
+            &[&[ReferralState::SEED, referrer.key.as_ref(), &[referral_bump]]],
+        )?;
+        
+        let referral = ReferralState {
+            referrer: *referrer.key,
+            accrued: 0,
+            total_earned: 0,
+            referred_volume: 0,
+            referred_trades: 0,
+            referred_users: 0,
+            is_initialized: true,
+        };
+        referral.serialize(&mut &mut referral_account.data.borrow_mut()[..])?;
+        
+        msg!("Registered referrer {}", referrer.key);
+        
+        Ok(())
+    }
+
+    /// Pay a referrer its accrued referral fees
+    fn process_claim_referral_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let referrer = next_account_info(account_info_iter)?;
+        let referral_account = next_account_info(account_info_iter)?;
+        
+        let mut referral = Self::load_referral(program_id, referral_account)?;
+        if !referrer.is_signer || *referrer.key != referral.referrer {
+            return Err(AsterLaunchError::Unauthorized.into());
+        }
+        
+        let amount = referral.accrued;
+        if amount == 0 {
+            return Err(AsterLaunchError::NothingToClaim.into());
+        }
+        referral.accrued = 0;
+        
+        **referral_account.try_borrow_mut_lamports()? = referral_account
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(AsterLaunchError::InsufficientFunds)?;
+        **referrer.try_borrow_mut_lamports()? = referrer
+            .lamports()
+            .checked_add(amount)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        referral.serialize(&mut &mut referral_account.data.borrow_mut()[..])?;
+        
+        msg!("Claimed {} lamports of referral fees for {}", amount, referrer.key);
+        
+        Ok(())
+    }
+
+    /// Set the referrer share of the platform fee
+    fn process_set_referral_fee(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        referral_fee_bps: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        
+        let authority = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        
+        let mut config = Self::load_platform_config(program_id, config_account)?;
+        Self::assert_authority(&config, authority)?;
+        
+        if referral_fee_bps > 10000 {
+            return Err(AsterLaunchError::InvalidFeeStructure.into());
+        }
+        
+        config.referral_fee_bps = referral_fee_bps;
+        config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+        
+        msg!("Referral fee: {}bps of the platform fee", referral_fee_bps);
+        
+        Ok(())
+    }
+
+    /// Resolve the referral account to credit for a trade
+    /// 
+    /// The referrer is recorded on the user's first referred trade; after
+    /// that every trade of the user must credit the recorded referrer, so
+    /// leaving the referral account out fails with `ReferrerMismatch`
+    fn resolve_referral<'a>(
+        program_id: &Pubkey,
+        user: &Pubkey,
+        payer: &AccountInfo<'a>,
+        user_referral_account: &AccountInfo<'a>,
+        referral_account: Option<&AccountInfo<'a>>,
+        system_program_account: &AccountInfo<'a>,
+    ) -> Result<Option<ReferralState>, ProgramError> {
+        let (record_address, record_bump) = UserReferral::find_address(program_id, user);
+        Self::assert_address(user_referral_account, &record_address)?;
+        
+        if user_referral_account.data_is_empty() {
+            let referral_account = match referral_account {
+                Some(referral_account) => referral_account,
+                None => return Ok(None),
+            };
+            let mut referral = Self::load_referral(program_id, referral_account)?;
+            if referral.referrer == *user {
+                return Err(AsterLaunchError::InvalidReferrer.into());
+            }
+            
+            Self::create_pda_account(
+                payer,
+                user_referral_account,
+                UserReferral::LEN,
+                program_id,
+                system_program_account,
+                &[&[UserReferral::SEED, user.as_ref(), &[record_bump]]],
+            )?;
+            let record = UserReferral {
+                user: *user,
+                referrer: referral.referrer,
+                created_at: Clock::get()?.unix_timestamp,
+            };
+            record.serialize(&mut &mut user_referral_account.data.borrow_mut()[..])?;
+            
+            referral.referred_users = referral.referred_users
+                .checked_add(1)
+                .ok_or(AsterLaunchError::MathOverflow)?;
+            msg!("Referral recorded: {} -> {}", user, referral.referrer);
+            return Ok(Some(referral));
+        }
+        
+        if user_referral_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let record = UserReferral::deserialize(&mut &user_referral_account.data.borrow()[..])?;
+        let referral_account = referral_account.ok_or(AsterLaunchError::ReferrerMismatch)?;
+        let referral = Self::load_referral(program_id, referral_account)?;
+        if record.referrer != referral.referrer {
+            return Err(AsterLaunchError::ReferrerMismatch.into());
+        }
+        
+        Ok(Some(referral))
+    }
+
+    /// Update a referrer's balance and stats after a referred trade
+    fn record_referral_trade(
+        referral_account: &AccountInfo,
+        referral: &mut ReferralState,
+        referral_fee: u64,
+        volume: u64,
+    ) -> ProgramResult {
+        referral.accrued = referral.accrued
+            .checked_add(referral_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        referral.total_earned = referral.total_earned
+            .checked_add(referral_fee)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        referral.referred_volume = referral.referred_volume
+            .checked_add(volume)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        referral.referred_trades = referral.referred_trades
+            .checked_add(1)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        referral.serialize(&mut &mut referral_account.data.borrow_mut()[..])?;
+        
+        Ok(())
+    }
+
+    /// Deserialize a referral account, checking it is the referrer's PDA
+    fn load_referral(
+        program_id: &Pubkey,
+        referral_account: &AccountInfo,
+    ) -> Result<ReferralState, ProgramError> {
+        if referral_account.owner != program_id {
+            return Err(AsterLaunchError::InvalidReferrer.into());
+        }
+        
+        let referral = ReferralState::deserialize(&mut &referral_account.data.borrow()[..])?;
+        if !referral.is_initialized
+            || ReferralState::find_address(program_id, &referral.referrer).0 != *referral_account.key
+        {
+            return Err(AsterLaunchError::InvalidReferrer.into());
+        }
+        
+        Ok(referral)
+    }
+
+    /// Deserialize the platform config, checking it belongs to this program
+    fn load_platform_config(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+    ) -> Result<PlatformConfig, ProgramError> {
+        if config_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        let config = PlatformConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+        if !config.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        
+        Ok(config)
+    }
+
+    /// Deserialize a token state account, checking it belongs to this program
+    fn load_token_state(
+        program_id: &Pubkey,
+        token_state_account: &AccountInfo,
+    ) -> Result<TokenState, ProgramError> {
+        if token_state_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        let token_state = TokenState::deserialize(&mut &token_state_account.data.borrow()[..])?;
+        if TokenState::find_address(program_id, &token_state.mint).0 != *token_state_account.key {
+            return Err(AsterLaunchError::InvalidAccountAddress.into());
+        }
+        
+        Ok(token_state)
+    }
+
+    /// Load the curve a quote instruction prices: token state, then bonding curve
+    fn load_quoted_curve(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<BondingCurveState, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let token_state_account = next_account_info(account_info_iter)?;
+        let bonding_curve_account = next_account_info(account_info_iter)?;
+        
+        let token_state = Self::load_token_state(program_id, token_state_account)?;
+        if *bonding_curve_account.key != token_state.bonding_curve {
+            return Err(AsterLaunchError::InvalidAccountAddress.into());
+        }
+        if bonding_curve_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        Ok(BondingCurveState::deserialize(&mut &bonding_curve_account.data.borrow()[..])?)
+    }
+    
+    /// Set a borsh value as the instruction's return data
+    fn return_data(value: &impl BorshSerialize) -> ProgramResult {
+        set_return_data(&value.try_to_vec()?);
+        Ok(())
+    }
+    
+    /// Deserialize a token's bonding curve and check its reserve accounts
+    /// 
+    /// Returns the curve state and the bump of its PDA (the reserve authority)
+    fn load_bonding_curve(
+        program_id: &Pubkey,
+        bonding_curve_account: &AccountInfo,
+        curve_token_account: &AccountInfo,
+        curve_sol_account: &AccountInfo,
+        token_state: &TokenState,
+    ) -> Result<(BondingCurveState, u8), ProgramError> {
+        let (curve_address, curve_bump) = BondingCurveState::find_address(program_id, &token_state.mint);
+        if *bonding_curve_account.key != curve_address || token_state.bonding_curve != curve_address {
+            return Err(AsterLaunchError::InvalidAccountAddress.into());
+        }
+        if bonding_curve_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        let curve = BondingCurveState::deserialize(&mut &bonding_curve_account.data.borrow()[..])?;
+        Self::assert_address(curve_token_account, &curve.token_reserve)?;
+        Self::assert_address(curve_sol_account, &curve.sol_reserve)?;
+        
+        Ok((curve, curve_bump))
+    }
+
+    /// Credit lamports sent straight to the SOL reserve to locked liquidity,
+    /// so the reserve ledgers match its balance before a trade
+    fn sync_sol_reserve(curve: &mut BondingCurveState, curve_sol_account: &AccountInfo) -> ProgramResult {
+        let rent_minimum = Rent::get()?.minimum_balance(curve_sol_account.data_len());
+        let accounted = curve.reserve_balance()?
+            .checked_add(rent_minimum)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        
+        if let Some(surplus) = curve_sol_account.lamports().checked_sub(accounted).filter(|s| *s > 0) {
+            curve.liquidity_sol = curve.liquidity_sol
+                .checked_add(surplus)
+                .ok_or(AsterLaunchError::MathOverflow)?;
+            msg!("Swept {} unaccounted lamports into liquidity", surplus);
+        }
+        
+        Ok(())
+    }
+
+    /// Abort the trade unless the curve's SOL reserve is solvent
+    fn assert_solvent(curve: &BondingCurveState, curve_sol_account: &AccountInfo) -> ProgramResult {
+        let rent_minimum = Rent::get()?.minimum_balance(curve_sol_account.data_len());
+        curve.check_solvency(curve_sol_account.lamports(), rent_minimum)?;
+        
+        Ok(())
+    }
+
+    /// Verify the signer is the platform authority
+    fn assert_authority(config: &PlatformConfig, authority: &AccountInfo) -> ProgramResult {
+        if !authority.is_signer || *authority.key != config.authority {
+            return Err(AsterLaunchError::Unauthorized.into());
+        }
+        
+        Ok(())
+    }
+
+    /// Reject the operation if it is paused platform-wide or for this token
+    fn assert_not_paused(
+        config: &PlatformConfig,
+        token_state: &TokenState,
+        flag: u8,
+    ) -> ProgramResult {
+        if config.is_paused(flag) {
+            return Err(AsterLaunchError::PlatformPaused.into());
+        }
+        if token_state.is_paused {
+            return Err(AsterLaunchError::TokenPaused.into());
+        }
+        
+        Ok(())
+    }
+
+    /// Verify the treasury vault and token program passed to a trade
+    fn assert_trade_accounts(
+        config: &PlatformConfig,
+        treasury: &AccountInfo,
+        token_program: &AccountInfo,
+    ) -> ProgramResult {
+        if *treasury.key != config.treasury {
+            return Err(AsterLaunchError::InvalidTreasury.into());
+        }
+        if *token_program.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        Ok(())
+    }
+
+    /// Verify the creator fee vault belongs to this curve
+    fn assert_creator_vault(
+        program_id: &Pubkey,
+        creator_vault: &AccountInfo,
+        curve: &BondingCurveState,
+    ) -> ProgramResult {
+        let (creator_vault_address, _) =
+            BondingCurveState::find_creator_vault_address(program_id, &curve.token_mint);
+        Self::assert_address(creator_vault, &creator_vault_address)?;
+        if creator_vault.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        Ok(())
+    }
+
+    /// Verify an account is at the expected address
+    fn assert_address(account: &AccountInfo, expected: &Pubkey) -> ProgramResult {
+        if account.key != expected {
+            return Err(AsterLaunchError::InvalidAccountAddress.into());
+        }
+        
+        Ok(())
+    }
+
+    /// Create an account through the system program
+    /// 
+    /// `signer_seeds` are needed when the new account is a PDA
+    fn create_pda_account<'a>(
+        payer: &AccountInfo<'a>,
+        new_account: &AccountInfo<'a>,
+        space: usize,
+        owner: &Pubkey,
+        system_program_account: &AccountInfo<'a>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if *system_program_account.key != system_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(payer.key, new_account.key, lamports, space as u64, owner),
+            &[payer.clone(), new_account.clone(), system_program_account.clone()],
+            signer_seeds,
+        )
+    }
+
+    /// Transfer lamports out of a system account
+    fn transfer_lamports<'a>(
+        from: &AccountInfo<'a>,
+        to: &AccountInfo<'a>,
+        system_program_account: &AccountInfo<'a>,
+        lamports: u64,
+    ) -> ProgramResult {
+        if lamports == 0 {
+            return Ok(());
+        }
+        
+        invoke(
+            &system_instruction::transfer(from.key, to.key, lamports),
+            &[from.clone(), to.clone(), system_program_account.clone()],
+        )
+    }
 }
\ No newline at end of file
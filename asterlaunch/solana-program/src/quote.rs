@@ -0,0 +1,173 @@
+/*
+ * Trade Quotes
+ *
+ * Prices a buy or sell on a bonding curve without executing it. The trade
+ * instructions price themselves with these same functions, so a quote from
+ * `QuoteBuy`/`QuoteSell` (read from return data, via `simulateTransaction`
+ * or CPI) is exactly what the trade would charge or pay.
+ *
+ * `BuyTokens` and `SellTokens` return what actually moved as a
+ * `TradeResult`.
+ *
+ * All amounts are lamports and base units; prices are lamports per whole
+ * token of the curve's decimals.
+ */
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{
+    bonding_curve::FeeStructure,
+    error::AsterLaunchError,
+    state::{BondingCurveState, TradeStats},
+};
+
+/// Quote and result types, shared with the Anchor program and off-chain tools
+/// 
+/// `TradeResult` is the fill returned by `BuyTokens` and `SellTokens`
+pub use asterlaunch_curve::quote::{BuyQuote, SellQuote, TradeFees, TradeResult};
+
+/// State of a bonding curve, returned by `GetCurveStatus`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurveStatus {
+    /// Spot price of the next token
+    pub price: u64,
+
+    pub total_supply: u64,
+    pub tokens_sold: u64,
+    pub tokens_remaining: u64,
+
+    /// Spot price times total supply in whole tokens
+    pub market_cap: u64,
+
+    /// SOL held by the curve, excluding rent
+    pub sol_reserves: u64,
+    pub redeemable_sol: u64,
+    pub liquidity_sol: u64,
+
+    /// Share of the supply sold; the curve sells out, and can graduate, at 10000
+    pub graduation_progress_bps: u16,
+
+    pub is_active: bool,
+    pub has_graduated: bool,
+    pub stats: TradeStats,
+}
+
+/// Quote buying `token_amount` tokens, failing as `BuyTokens` would
+pub fn quote_buy(curve: &BondingCurveState, token_amount: u64) -> Result<BuyQuote, AsterLaunchError> {
+    if token_amount == 0 {
+        return Err(AsterLaunchError::InvalidTradeAmount);
+    }
+    if !curve.is_active || curve.has_graduated {
+        return Err(AsterLaunchError::TokenAlreadyGraduated);
+    }
+
+    Ok(curve.linear().quote_buy(&fees(curve)?, token_amount)?)
+}
+
+/// Quote selling `token_amount` tokens, failing as `SellTokens` would
+pub fn quote_sell(curve: &BondingCurveState, token_amount: u64) -> Result<SellQuote, AsterLaunchError> {
+    if token_amount == 0 {
+        return Err(AsterLaunchError::InvalidTradeAmount);
+    }
+    if !curve.is_active || curve.has_graduated {
+        return Err(AsterLaunchError::TokenAlreadyGraduated);
+    }
+
+    Ok(curve.linear().quote_sell(&fees(curve)?, token_amount)?)
+}
+
+/// Current state of a curve
+pub fn curve_status(curve: &BondingCurveState) -> Result<CurveStatus, AsterLaunchError> {
+    let price = curve.spot_price()?;
+    let market_cap = (price as u128)
+        .checked_mul(curve.total_supply as u128)
+        .map(|cap| cap / 10u128.pow(curve.decimals as u32))
+        .and_then(|cap| u64::try_from(cap).ok())
+        .ok_or(AsterLaunchError::MathOverflow)?;
+    let graduation_progress_bps = if curve.has_graduated {
+        10000
+    } else {
+        curve.linear().progress_bps()
+    };
+
+    Ok(CurveStatus {
+        price,
+        total_supply: curve.total_supply,
+        tokens_sold: curve.tokens_sold,
+        tokens_remaining: curve.total_supply.saturating_sub(curve.tokens_sold),
+        market_cap,
+        sol_reserves: curve.reserve_balance()?,
+        redeemable_sol: curve.redeemable_sol,
+        liquidity_sol: curve.liquidity_sol,
+        graduation_progress_bps,
+        is_active: curve.is_active,
+        has_graduated: curve.has_graduated,
+        stats: curve.stats,
+    })
+}
+
+fn fees(curve: &BondingCurveState) -> Result<FeeStructure, AsterLaunchError> {
+    Ok(FeeStructure::with_creator_fee(curve.creator_fee_bps)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bonding_curve;
+    use asterlaunch_curve::LinearCurve;
+
+    fn curve() -> BondingCurveState {
+        BondingCurveState::from_curve(
+            &LinearCurve {
+                initial_price: 100_000,
+                price_increment: 10,
+                total_supply: 1_000_000,
+                tokens_sold: 0,
+                decimals: 0,
+            },
+            20,
+        )
+    }
+
+    #[test]
+    fn test_quote_buy() {
+        let curve = curve();
+        let quote = quote_buy(&curve, 1_000).unwrap();
+
+        // 1000 tokens from 100_000 to 109_990 lamports each
+        assert_eq!(quote.cost, 104_995_000);
+        assert_eq!(quote.fees.total_fee(), 1_049_950);
+        assert_eq!(quote.total_cost, quote.cost + quote.fees.total_fee());
+        assert_eq!(quote.price_before, 100_000);
+        assert_eq!(quote.price_after, 110_000);
+        assert_eq!(quote.price_impact_bps, 499);
+        assert_eq!(quote.graduation_progress_bps, 10);
+
+        assert!(matches!(quote_buy(&curve, 0), Err(AsterLaunchError::InvalidTradeAmount)));
+        assert!(matches!(quote_buy(&curve, 1_000_001), Err(AsterLaunchError::InvalidTradeAmount)));
+        let graduated = BondingCurveState { has_graduated: true, ..curve };
+        assert!(matches!(quote_buy(&graduated, 1), Err(AsterLaunchError::TokenAlreadyGraduated)));
+    }
+
+    #[test]
+    fn test_quote_sell_undoes_buy() {
+        let mut curve = curve();
+        let buy = quote_buy(&curve, 1_000).unwrap();
+        curve.tokens_sold += 1_000;
+
+        let sell = quote_sell(&curve, 1_000).unwrap();
+        assert_eq!(sell.proceeds, buy.cost);
+        assert_eq!(sell.redeemed, bonding_curve::calculate_redeemable(buy.cost).unwrap());
+        assert_eq!(sell.sol_out, sell.redeemed - sell.fees.total_fee());
+        assert_eq!(sell.price_before, buy.price_after);
+        assert_eq!(sell.price_after, buy.price_before);
+        assert_eq!(sell.graduation_progress_bps, 0);
+
+        assert!(matches!(quote_sell(&curve, 1_001), Err(AsterLaunchError::InvalidTradeAmount)));
+
+        let status = curve_status(&curve).unwrap();
+        assert_eq!(status.price, 110_000);
+        assert_eq!(status.tokens_remaining, 999_000);
+        assert_eq!(status.market_cap, 110_000_000_000);
+    }
+}
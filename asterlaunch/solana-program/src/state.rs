@@ -1,13 +1,36 @@
+use asterlaunch_curve::LinearCurve;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
+use crate::{bonding_curve::split_fee, error::AsterLaunchError, events::TradeSide};
+
+/// Maximum number of platform fee recipients
+pub const MAX_FEE_RECIPIENTS: usize = 8;
+
+/// A share of the platform fee and its unclaimed balance
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeeRecipient {
+    /// Account allowed to claim this share
+    pub recipient: Pubkey,
+    
+    /// Share of the platform fee in basis points (all shares sum to 10000)
+    pub weight_bps: u16,
+    
+    /// Fees accrued in the treasury vault and not yet claimed (in lamports)
+    pub accrued: u64,
+}
+
+impl FeeRecipient {
+    pub const LEN: usize = 32 + 2 + 8;
+}
+
 /// Global platform configuration
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct PlatformConfig {
     /// Platform authority (admin)
     pub authority: Pubkey,
     
-    /// Platform treasury for collecting fees
+    /// Platform treasury vault (program-owned PDA) collecting all fees
     pub treasury: Pubkey,
     
     /// Trading fee in basis points (100 = 1%)
@@ -17,6 +40,9 @@ pub struct PlatformConfig {
     pub creation_fee_lamports: u64,
     
     /// Graduation threshold in USD (multiplied by 1e6 for precision)
+    /// 
+    /// Not enforced: without a SOL price feed the program graduates a curve
+    /// once it has sold out
     pub graduation_threshold_usd: u64,
     
     /// Total tokens created on platform
@@ -27,10 +53,55 @@ pub struct PlatformConfig {
     
     /// Is initialized
     pub is_initialized: bool,
+    
+    /// Bitmask of paused operations (see `PAUSE_*` constants)
+    pub pause_flags: u8,
+    
+    /// Total fees paid into the treasury (in lamports)
+    pub total_fees_collected: u64,
+    
+    /// Number of active entries in `fee_recipients`
+    pub fee_recipient_count: u8,
+    
+    /// Platform fee split; only the first `fee_recipient_count` are active
+    pub fee_recipients: [FeeRecipient; MAX_FEE_RECIPIENTS],
+    
+    /// Creator share of the trading fee for new tokens (basis points of the trade)
+    pub creator_fee_bps: u16,
+    
+    /// Referrer share of the platform fee (basis points of the platform fee)
+    pub referral_fee_bps: u16,
+    
+    /// Trades across all tokens
+    pub total_trades: u64,
+    
+    /// Fees paid on trades across all tokens, whoever received them (in lamports)
+    pub total_trade_fees: u64,
 }
 
 impl PlatformConfig {
-    pub const LEN: usize = 32 + 32 + 2 + 8 + 8 + 8 + 8 + 1;
+    pub const LEN: usize = 32 + 32 + 2 + 8 + 8 + 8 + 8 + 1 + 1 + 8 +
+        1 + (FeeRecipient::LEN * MAX_FEE_RECIPIENTS) + 2 + 2 + 8 + 8;
+    
+    /// PDA seed of the platform config account
+    pub const SEED: &'static [u8] = b"platform_config";
+    
+    /// PDA seed of the treasury vault
+    pub const TREASURY_SEED: &'static [u8] = b"treasury";
+    
+    /// Pause flag: reject `BuyTokens`
+    pub const PAUSE_BUYS: u8 = 1 << 0;
+    /// Pause flag: reject `SellTokens`
+    pub const PAUSE_SELLS: u8 = 1 << 1;
+    /// Pause flag: reject `CreateToken`
+    pub const PAUSE_CREATIONS: u8 = 1 << 2;
+    /// Pause flag: reject `GraduateToDEX`
+    pub const PAUSE_GRADUATIONS: u8 = 1 << 3;
+    /// All operations paused (full circuit breaker)
+    pub const PAUSE_ALL: u8 = Self::PAUSE_BUYS
+        | Self::PAUSE_SELLS
+        | Self::PAUSE_CREATIONS
+        | Self::PAUSE_GRADUATIONS;
     
     /// Default platform configuration
     pub fn new(authority: Pubkey, treasury: Pubkey) -> Self {
@@ -43,8 +114,83 @@ impl PlatformConfig {
             total_tokens_created: 0,
             total_volume: 0,
             is_initialized: true,
+            pause_flags: 0,
+            total_fees_collected: 0,
+            fee_recipient_count: 0,
+            fee_recipients: [FeeRecipient::default(); MAX_FEE_RECIPIENTS],
+            creator_fee_bps: 20, // 0.2% of the trade, out of the platform's 0.5%
+            referral_fee_bps: 2000, // 20% of the platform fee
+            total_trades: 0,
+            total_trade_fees: 0,
         }
     }
+    
+    /// Roll a trade into the platform totals
+    /// 
+    /// `sol_amount` and `fees` are counted the same way as in [`TradeStats`]
+    pub fn record_trade(&mut self, sol_amount: u64, fees: u64) -> Result<(), AsterLaunchError> {
+        self.total_trades = self.total_trades
+            .checked_add(1)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        self.total_volume = self.total_volume
+            .checked_add(sol_amount)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        self.total_trade_fees = self.total_trade_fees
+            .checked_add(fees)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        Ok(())
+    }
+    
+    /// Referrer's cut of a platform fee
+    pub fn referral_fee(&self, platform_fee: u64) -> Result<u64, AsterLaunchError> {
+        Ok(split_fee(platform_fee, &[self.referral_fee_bps])?[0])
+    }
+    
+    /// Active fee recipients
+    pub fn fee_recipients(&self) -> &[FeeRecipient] {
+        &self.fee_recipients[..self.fee_recipient_count as usize]
+    }
+    
+    /// Fees owed to recipients that are still held in the treasury vault
+    pub fn total_fees_accrued(&self) -> Result<u64, AsterLaunchError> {
+        self.fee_recipients()
+            .iter()
+            .try_fold(0u64, |total, r| total.checked_add(r.accrued))
+            .ok_or(AsterLaunchError::MathOverflow)
+    }
+    
+    /// Credit a platform fee to the recipients according to their weights
+    /// 
+    /// Rounding dust, or the whole fee when no recipients are configured,
+    /// stays unallocated in the treasury
+    pub fn accrue_platform_fee(&mut self, platform_fee: u64) -> Result<(), AsterLaunchError> {
+        let count = self.fee_recipient_count as usize;
+        let weights: Vec<u16> = self.fee_recipients[..count].iter().map(|r| r.weight_bps).collect();
+        let shares = split_fee(platform_fee, &weights)?;
+        
+        for (recipient, share) in self.fee_recipients[..count].iter_mut().zip(shares) {
+            recipient.accrued = recipient.accrued
+                .checked_add(share)
+                .ok_or(AsterLaunchError::MathOverflow)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Derive the platform config address
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    /// Derive the treasury vault address
+    pub fn find_treasury_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::TREASURY_SEED], program_id)
+    }
+    
+    /// Check whether any of the given operations are paused
+    pub fn is_paused(&self, flags: u8) -> bool {
+        self.pause_flags & flags != 0
+    }
 }
 
 /// Token metadata and state
@@ -85,6 +231,9 @@ pub struct TokenState {
     
     /// Graduation timestamp (0 if not graduated)
     pub graduated_at: i64,
+    
+    /// Trading paused for this token by the platform authority
+    pub is_paused: bool,
 }
 
 impl TokenState {
@@ -93,12 +242,86 @@ impl TokenState {
     pub const MAX_DESCRIPTION_LEN: usize = 200;
     pub const MAX_URI_LEN: usize = 200;
     
+    /// PDA seed of the token state account
+    pub const SEED: &'static [u8] = b"token_state";
+    
     pub const LEN: usize = 32 + 32 + 32 + 
         (4 + Self::MAX_NAME_LEN) + 
         (4 + Self::MAX_TICKER_LEN) + 
         (4 + Self::MAX_DESCRIPTION_LEN) + 
         (4 + Self::MAX_URI_LEN) + 
-        8 + 8 + 1 + 8 + 8;
+        8 + 8 + 1 + 8 + 8 + 1;
+    
+    /// Derive the token state address for a mint
+    pub fn find_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, mint.as_ref()], program_id)
+    }
+}
+
+/// Trading statistics of a bonding curve, updated on every trade
+/// 
+/// Every `Trade` event carries the statistics after the trade; since
+/// `trade_seq` goes up by exactly one per trade, an indexer that sees a jump
+/// has missed trades.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TradeStats {
+    /// Number of trades so far; the first trade is 1
+    pub trade_seq: u64,
+    
+    pub buy_count: u64,
+    pub sell_count: u64,
+    
+    /// SOL paid by buyers, including fees (in lamports)
+    pub buy_volume: u64,
+    
+    /// SOL received by sellers, after fees (in lamports)
+    pub sell_volume: u64,
+    
+    /// All fees paid on trades: liquidity, platform, creator and referral (in lamports)
+    pub fees_paid: u64,
+    
+    /// Slot of the latest trade
+    pub last_trade_slot: u64,
+    
+    /// Spot price after the latest trade (lamports per whole token)
+    pub last_price: u64,
+}
+
+impl TradeStats {
+    pub const LEN: usize = 8 * 8;
+    
+    /// Count a trade
+    pub fn record(
+        &mut self,
+        side: TradeSide,
+        sol_amount: u64,
+        fees: u64,
+        slot: u64,
+        price: u64,
+    ) -> Result<(), AsterLaunchError> {
+        let (count, volume) = match side {
+            TradeSide::Buy => (&mut self.buy_count, &mut self.buy_volume),
+            TradeSide::Sell => (&mut self.sell_count, &mut self.sell_volume),
+        };
+        *count = count.checked_add(1).ok_or(AsterLaunchError::MathOverflow)?;
+        *volume = volume.checked_add(sol_amount).ok_or(AsterLaunchError::MathOverflow)?;
+        self.fees_paid = self.fees_paid
+            .checked_add(fees)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        self.trade_seq = self.trade_seq
+            .checked_add(1)
+            .ok_or(AsterLaunchError::MathOverflow)?;
+        self.last_trade_slot = slot;
+        self.last_price = price;
+        Ok(())
+    }
+    
+    /// SOL traded in both directions (in lamports)
+    pub fn total_volume(&self) -> Result<u64, AsterLaunchError> {
+        self.buy_volume
+            .checked_add(self.sell_volume)
+            .ok_or(AsterLaunchError::MathOverflow)
+    }
 }
 
 /// Bonding curve state
@@ -115,12 +338,16 @@ pub struct BondingCurveState {
     /// Curve's SOL reserve account
     pub sol_reserve: Pubkey,
     
-    /// Initial price per token (in lamports)
+    /// Initial price per whole token (in lamports)
     pub initial_price: u64,
     
-    /// Price increment per token sold (in lamports)
+    /// Price increment per whole token sold (in 10^-decimals lamports per
+    /// whole token)
     pub price_increment: u64,
     
+    /// Mint decimals; supply and sales are in base units
+    pub decimals: u8,
+    
     /// Total tokens available for sale
     pub total_supply: u64,
     
@@ -130,7 +357,7 @@ pub struct BondingCurveState {
     /// Total SOL collected
     pub sol_collected: u64,
     
-    /// Total SOL for liquidity (50% of collected)
+    /// SOL locked for DEX liquidity (50% of collected plus liquidity fees)
     pub liquidity_sol: u64,
     
     /// Current market cap in USD (with 6 decimals)
@@ -141,73 +368,212 @@ pub struct BondingCurveState {
     
     /// Has graduated
     pub has_graduated: bool,
+    
+    /// Creator share of the trading fee, fixed at creation (basis points)
+    pub creator_fee_bps: u16,
+    
+    /// Creator fees held in the creator vault and not yet claimed
+    pub creator_fees_accrued: u64,
+    
+    /// SOL backing sells back to the curve (the other 50% of collected)
+    pub redeemable_sol: u64,
+    
+    /// Trading statistics
+    pub stats: TradeStats,
 }
 
 impl BondingCurveState {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1;
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 2 + 8 + 8 + TradeStats::LEN;
+    
+    /// PDA seed of the bonding curve state account (also the reserve authority)
+    pub const SEED: &'static [u8] = b"bonding_curve";
+    
+    /// PDA seed of the curve's token reserve account
+    pub const TOKEN_RESERVE_SEED: &'static [u8] = b"token_reserve";
+    
+    /// PDA seed of the curve's SOL reserve account
+    pub const SOL_RESERVE_SEED: &'static [u8] = b"sol_reserve";
+    
+    /// PDA seed of the creator fee vault
+    pub const CREATOR_VAULT_SEED: &'static [u8] = b"creator_vault";
+    
+    /// Derive the bonding curve state address for a mint
+    pub fn find_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, mint.as_ref()], program_id)
+    }
+    
+    /// Derive the curve's token reserve address for a mint
+    pub fn find_token_reserve_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::TOKEN_RESERVE_SEED, mint.as_ref()], program_id)
+    }
+    
+    /// Derive the curve's SOL reserve address for a mint
+    pub fn find_sol_reserve_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SOL_RESERVE_SEED, mint.as_ref()], program_id)
+    }
+    
+    /// Derive the creator fee vault address for a mint
+    pub fn find_creator_vault_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::CREATOR_VAULT_SEED, mint.as_ref()], program_id)
+    }
+    
+    /// Active curve priced by `curve` with nothing collected yet; the mint
+    /// and reserve addresses are left default for the caller to fill in
+    pub fn from_curve(curve: &LinearCurve, creator_fee_bps: u16) -> Self {
+        Self {
+            token_mint: Pubkey::default(),
+            token_reserve: Pubkey::default(),
+            sol_reserve: Pubkey::default(),
+            initial_price: curve.initial_price,
+            price_increment: curve.price_increment,
+            decimals: curve.decimals,
+            total_supply: curve.total_supply,
+            tokens_sold: curve.tokens_sold,
+            sol_collected: 0,
+            liquidity_sol: 0,
+            market_cap_usd: 0,
+            is_active: true,
+            has_graduated: false,
+            creator_fee_bps,
+            creator_fees_accrued: 0,
+            redeemable_sol: 0,
+            stats: TradeStats::default(),
+        }
+    }
+    
+    /// Pricing state of the curve
+    pub fn linear(&self) -> LinearCurve {
+        LinearCurve {
+            initial_price: self.initial_price,
+            price_increment: self.price_increment,
+            total_supply: self.total_supply,
+            tokens_sold: self.tokens_sold,
+            decimals: self.decimals,
+        }
+    }
     
     /// Calculate buy price for a given amount of tokens
     /// Uses the integral of the linear bonding curve
     pub fn calculate_buy_price(&self, token_amount: u64) -> Result<u64, crate::error::AsterLaunchError> {
-        // Price = sum from i=tokens_sold to tokens_sold+token_amount of (initial_price + i * price_increment)
-        // This is: token_amount * initial_price + price_increment * (sum of i from tokens_sold to tokens_sold+token_amount)
-        // Sum of arithmetic sequence: n * (first + last) / 2
-        
-        let tokens_sold = self.tokens_sold;
-        let first_price = self.initial_price
-            .checked_add(tokens_sold.checked_mul(self.price_increment)
-                .ok_or(crate::error::AsterLaunchError::MathOverflow)?)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
-        
-        let last_price = self.initial_price
-            .checked_add((tokens_sold + token_amount - 1).checked_mul(self.price_increment)
-                .ok_or(crate::error::AsterLaunchError::MathOverflow)?)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
-        
-        let avg_price = (first_price.checked_add(last_price)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?)
-            .checked_div(2)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
-        
-        let total_cost = avg_price.checked_mul(token_amount)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
-        
-        Ok(total_cost)
+        Ok(self.linear().buy_cost(token_amount)?)
     }
     
     /// Calculate sell price for a given amount of tokens
     /// Slightly lower than buy price due to fees
     pub fn calculate_sell_price(&self, token_amount: u64) -> Result<u64, crate::error::AsterLaunchError> {
-        if token_amount > self.tokens_sold {
-            return Err(crate::error::AsterLaunchError::InvalidTradeAmount);
+        Ok(self.linear().sell_value(token_amount)?)
+    }
+    
+    /// Price of the next base unit sold (in lamports per whole token)
+    pub fn spot_price(&self) -> Result<u64, crate::error::AsterLaunchError> {
+        Ok(self.linear().spot_price()?)
+    }
+    
+    /// Redeemable SOL needed to buy back every circulating token
+    pub fn calculate_buyback_value(&self) -> Result<u64, crate::error::AsterLaunchError> {
+        if self.tokens_sold == 0 {
+            return Ok(0);
         }
         
-        let new_tokens_sold = self.tokens_sold.checked_sub(token_amount)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
-        
-        let first_price = self.initial_price
-            .checked_add(new_tokens_sold.checked_mul(self.price_increment)
-                .ok_or(crate::error::AsterLaunchError::MathOverflow)?)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
-        
-        let last_price = self.initial_price
-            .checked_add((self.tokens_sold - 1).checked_mul(self.price_increment)
-                .ok_or(crate::error::AsterLaunchError::MathOverflow)?)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
-        
-        let avg_price = (first_price.checked_add(last_price)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?)
-            .checked_div(2)
-            .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
-        
-        let total_return = avg_price.checked_mul(token_amount)
+        crate::bonding_curve::calculate_redeemable(self.calculate_sell_price(self.tokens_sold)?)
+    }
+    
+    /// SOL the reserve ledgers account for, excluding rent
+    pub fn reserve_balance(&self) -> Result<u64, crate::error::AsterLaunchError> {
+        self.redeemable_sol
+            .checked_add(self.liquidity_sol)
+            .ok_or(crate::error::AsterLaunchError::MathOverflow)
+    }
+    
+    /// Check the curve's solvency against the SOL reserve's actual balance
+    /// 
+    /// The reserve must hold exactly its rent minimum plus the ledgered
+    /// reserves, and the redeemable reserve must cover selling back every
+    /// circulating token
+    pub fn check_solvency(
+        &self,
+        reserve_lamports: u64,
+        rent_minimum: u64,
+    ) -> Result<(), crate::error::AsterLaunchError> {
+        let accounted = self.reserve_balance()?
+            .checked_add(rent_minimum)
             .ok_or(crate::error::AsterLaunchError::MathOverflow)?;
+        if reserve_lamports != accounted {
+            return Err(crate::error::AsterLaunchError::ReserveInsolvent);
+        }
+        if self.redeemable_sol < self.calculate_buyback_value()? {
+            return Err(crate::error::AsterLaunchError::ReserveInsolvent);
+        }
         
-        Ok(total_return)
+        Ok(())
     }
     
     /// Check if token has reached graduation threshold
     pub fn check_graduation(&self, graduation_threshold: u64) -> bool {
         self.market_cap_usd >= graduation_threshold && !self.has_graduated
     }
+}
+
+/// Referral account of a referrer
+/// 
+/// Holds the referrer's unclaimed fees as lamports on top of its rent
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ReferralState {
+    /// Referrer allowed to claim
+    pub referrer: Pubkey,
+    
+    /// Fees accrued and not yet claimed (in lamports)
+    pub accrued: u64,
+    
+    /// Lifetime fees earned (in lamports)
+    pub total_earned: u64,
+    
+    /// SOL volume traded by referred users (in lamports)
+    pub referred_volume: u64,
+    
+    /// Trades made by referred users
+    pub referred_trades: u64,
+    
+    /// Users attributed to this referrer
+    pub referred_users: u64,
+    
+    /// Is initialized
+    pub is_initialized: bool,
+}
+
+impl ReferralState {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 1;
+    
+    /// PDA seed of a referral account
+    pub const SEED: &'static [u8] = b"referral";
+    
+    /// Derive the referral account address for a referrer
+    pub fn find_address(program_id: &Pubkey, referrer: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, referrer.as_ref()], program_id)
+    }
+}
+
+/// Sticky referral attribution, recorded on a user's first referred trade
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UserReferral {
+    /// Trader
+    pub user: Pubkey,
+    
+    /// Referrer credited for all of the user's trades
+    pub referrer: Pubkey,
+    
+    /// Attribution timestamp
+    pub created_at: i64,
+}
+
+impl UserReferral {
+    pub const LEN: usize = 32 + 32 + 8;
+    
+    /// PDA seed of a user referral record
+    pub const SEED: &'static [u8] = b"user_referral";
+    
+    /// Derive the referral record address for a user
+    pub fn find_address(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, user.as_ref()], program_id)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,913 @@
+//! The program run in-process with `solana-program-test`
+//!
+//! `token_lifecycle` takes a token from platform setup through trading by
+//! many wallets to graduation. The other tests trigger every
+//! `AsterLaunchError` an instruction can return. Each trade is checked
+//! against the program's own quote and the lamports and tokens it moved.
+//!
+//! Not covered: `NotRentExempt` and `BondingCurveNotInitialized` are never
+//! returned, and `MissingReturnData` is raised by the `cpi` wrappers in the
+//! calling program.
+
+use asterlaunch_program::{
+    error::AsterLaunchError,
+    instruction::{AsterLaunchInstruction, FeeSplit},
+    process_instruction,
+    quote::{self, BuyQuote, SellQuote, TradeResult},
+    state::{BondingCurveState, PlatformConfig, ReferralState, TokenState, UserReferral},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// The test mint: 1000 whole tokens of 6 decimals
+const DECIMALS: u8 = 6;
+const TOKEN: u64 = 1_000_000;
+const TOTAL_SUPPLY: u64 = 1_000 * TOKEN;
+
+/// A token's accounts, all derived from its mint
+struct Token {
+    mint: Pubkey,
+    creator: Keypair,
+    token_state: Pubkey,
+    bonding_curve: Pubkey,
+    token_reserve: Pubkey,
+    sol_reserve: Pubkey,
+    creator_vault: Pubkey,
+}
+
+impl Token {
+    fn new(program_id: &Pubkey, mint: Pubkey, creator: Keypair) -> Self {
+        Self {
+            mint,
+            creator,
+            token_state: TokenState::find_address(program_id, &mint).0,
+            bonding_curve: BondingCurveState::find_address(program_id, &mint).0,
+            token_reserve: BondingCurveState::find_token_reserve_address(program_id, &mint).0,
+            sol_reserve: BondingCurveState::find_sol_reserve_address(program_id, &mint).0,
+            creator_vault: BondingCurveState::find_creator_vault_address(program_id, &mint).0,
+        }
+    }
+}
+
+/// A funded wallet with a token account for one mint
+struct Trader {
+    keypair: Keypair,
+    token_account: Pubkey,
+}
+
+impl Trader {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+}
+
+/// Everything a trade moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Balances {
+    wallet: u64,
+    tokens: u64,
+    sol_reserve: u64,
+    treasury: u64,
+    creator_vault: u64,
+}
+
+struct Env {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    authority: Keypair,
+    config: Pubkey,
+    treasury: Pubkey,
+    rent: Rent,
+}
+
+impl Env {
+    /// Start a bank with the program loaded and initialize the platform
+    async fn start() -> Self {
+        let program_id = Pubkey::new_unique();
+        let authority = Keypair::new();
+        let mut program_test =
+            ProgramTest::new("asterlaunch_program", program_id, processor!(process_instruction));
+        program_test.add_account(
+            authority.pubkey(),
+            Account { lamports: 100 * LAMPORTS_PER_SOL, ..Account::default() },
+        );
+
+        let mut context = program_test.start_with_context().await;
+        let rent = context.banks_client.get_rent().await.unwrap();
+        let mut env = Self {
+            context,
+            program_id,
+            authority,
+            config: PlatformConfig::find_address(&program_id).0,
+            treasury: PlatformConfig::find_treasury_address(&program_id).0,
+            rent,
+        };
+        let initialize = env.initialize_platform(env.treasury);
+        env.send_as_authority(initialize).await.unwrap();
+        env
+    }
+
+    /// Process `instruction`, paid for by the context payer, and return its
+    /// return data
+    async fn process(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Result<Vec<u8>, BanksClientError> {
+        // A fresh blockhash for every transaction, so repeating one is not
+        // rejected as already processed
+        let blockhash = self.context.get_new_latest_blockhash().await?;
+        let payer = &self.context.payer;
+        let signers: Vec<&Keypair> = [payer].into_iter().chain(signers.iter().copied()).collect();
+        let transaction = Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &signers, blockhash);
+
+        let outcome = self.context.banks_client.process_transaction_with_metadata(transaction).await?;
+        outcome.result.map_err(BanksClientError::TransactionError)?;
+        Ok(outcome.metadata.and_then(|metadata| metadata.return_data).map_or_else(Vec::new, |data| data.data))
+    }
+
+    async fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        self.process(instruction, signers).await.map(|_| ())
+    }
+
+    async fn send_as_authority(&mut self, instruction: Instruction) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        self.send(instruction, &[&authority]).await
+    }
+
+    async fn trade(&mut self, instruction: Instruction, trader: &Trader) -> Result<TradeResult, BanksClientError> {
+        let data = self.process(instruction, &[&trader.keypair]).await?;
+        Ok(TradeResult::try_from_slice(&data).unwrap())
+    }
+
+    async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.context.banks_client.get_balance(address).await.unwrap()
+    }
+
+    async fn account(&mut self, address: Pubkey) -> Option<Account> {
+        self.context.banks_client.get_account(address).await.unwrap()
+    }
+
+    async fn state<T: BorshDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.account(address).await.expect("account exists");
+        T::deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    /// Overwrite a program account's state, as a corrupted or malicious
+    /// write would
+    async fn set_state<T: BorshSerialize>(&mut self, address: Pubkey, state: &T) {
+        let mut account = self.account(address).await.expect("account exists");
+        state.serialize(&mut &mut account.data[..]).unwrap();
+        self.context.set_account(&address, &account.into());
+    }
+
+    async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        let account = self.account(address).await.expect("token account exists");
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    async fn balances(&mut self, token: &Token, trader: &Trader) -> Balances {
+        Balances {
+            wallet: self.lamports(trader.pubkey()).await,
+            tokens: self.token_balance(trader.token_account).await,
+            sol_reserve: self.lamports(token.sol_reserve).await,
+            treasury: self.lamports(self.treasury).await,
+            creator_vault: self.lamports(token.creator_vault).await,
+        }
+    }
+
+    /// A new wallet holding 50 SOL
+    async fn wallet(&mut self) -> Keypair {
+        let wallet = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        let fund = system_instruction::transfer(&payer, &wallet.pubkey(), 50 * LAMPORTS_PER_SOL);
+        self.send(fund, &[]).await.unwrap();
+        wallet
+    }
+
+    /// A new wallet with an empty token account for `token`
+    async fn trader(&mut self, token: &Token) -> Trader {
+        let keypair = self.wallet().await;
+        let token_account = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        let len = spl_token::state::Account::LEN;
+        let create = system_instruction::create_account(
+            &payer,
+            &token_account.pubkey(),
+            self.rent.minimum_balance(len),
+            len as u64,
+            &spl_token::id(),
+        );
+        self.send(create, &[&token_account]).await.unwrap();
+        let initialize = spl_token::instruction::initialize_account3(
+            &spl_token::id(),
+            &token_account.pubkey(),
+            &token.mint,
+            &keypair.pubkey(),
+        )
+        .unwrap();
+        self.send(initialize, &[]).await.unwrap();
+
+        Trader { keypair, token_account: token_account.pubkey() }
+    }
+
+    /// Create the test token from a new wallet
+    async fn launch(&mut self) -> Token {
+        let creator = self.wallet().await;
+        let mint = Keypair::new();
+        let create = self.create_token(&creator.pubkey(), &mint.pubkey(), "Aster", TOTAL_SUPPLY, DECIMALS);
+        self.send(create, &[&creator, &mint]).await.unwrap();
+        Token::new(&self.program_id, mint.pubkey(), creator)
+    }
+
+    /// Buy `amount` and check every balance the buy moved against its quote
+    async fn buy_and_check(&mut self, token: &Token, trader: &Trader, amount: u64) -> BuyQuote {
+        let curve: BondingCurveState = self.state(token.bonding_curve).await;
+        let quote = quote::quote_buy(&curve, amount).unwrap();
+        let before = self.balances(token, trader).await;
+
+        let fill = self.trade(self.buy(token, trader, amount, quote.total_cost), trader).await.unwrap();
+        let after = self.balances(token, trader).await;
+        assert_eq!(
+            fill,
+            TradeResult {
+                tokens: amount,
+                sol: quote.total_cost,
+                fees: quote.fees.total_fee(),
+                new_price: quote.price_after,
+            }
+        );
+        assert_eq!(before.wallet - after.wallet, quote.total_cost);
+        assert_eq!(after.tokens - before.tokens, amount);
+        assert_eq!(after.sol_reserve - before.sol_reserve, quote.cost + quote.fees.liquidity_fee);
+        assert_eq!(after.treasury - before.treasury, quote.fees.platform_fee);
+        assert_eq!(after.creator_vault - before.creator_vault, quote.fees.creator_fee);
+
+        self.assert_ledgers(token).await;
+        quote
+    }
+
+    /// Sell `amount` and check every balance the sell moved against its quote
+    async fn sell_and_check(&mut self, token: &Token, trader: &Trader, amount: u64) -> SellQuote {
+        let curve: BondingCurveState = self.state(token.bonding_curve).await;
+        let quote = quote::quote_sell(&curve, amount).unwrap();
+        let before = self.balances(token, trader).await;
+
+        let fill = self.trade(self.sell(token, trader, amount, quote.sol_out), trader).await.unwrap();
+        let after = self.balances(token, trader).await;
+        assert_eq!(
+            fill,
+            TradeResult {
+                tokens: amount,
+                sol: quote.sol_out,
+                fees: quote.fees.total_fee(),
+                new_price: quote.price_after,
+            }
+        );
+        assert_eq!(after.wallet - before.wallet, quote.sol_out);
+        assert_eq!(before.tokens - after.tokens, amount);
+        assert_eq!(
+            before.sol_reserve - after.sol_reserve,
+            quote.sol_out + quote.fees.platform_fee + quote.fees.creator_fee
+        );
+        assert_eq!(after.treasury - before.treasury, quote.fees.platform_fee);
+        assert_eq!(after.creator_vault - before.creator_vault, quote.fees.creator_fee);
+
+        self.assert_ledgers(token).await;
+        quote
+    }
+
+    /// The curve's accounts hold exactly what its ledgers record, and the
+    /// redeemable reserve can buy back every circulating token
+    async fn assert_ledgers(&mut self, token: &Token) {
+        let curve: BondingCurveState = self.state(token.bonding_curve).await;
+        let token_state: TokenState = self.state(token.token_state).await;
+        let rent_minimum = self.rent.minimum_balance(0);
+
+        assert_eq!(
+            self.lamports(token.sol_reserve).await,
+            rent_minimum + curve.redeemable_sol + curve.liquidity_sol
+        );
+        assert_eq!(self.lamports(token.creator_vault).await, rent_minimum + curve.creator_fees_accrued);
+        assert_eq!(self.token_balance(token.token_reserve).await, curve.total_supply - curve.tokens_sold);
+        assert_eq!(token_state.circulating_supply, curve.tokens_sold);
+        assert!(curve.redeemable_sol >= curve.calculate_buyback_value().unwrap());
+    }
+
+    fn instruction(&self, instruction: AsterLaunchInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction.try_to_vec().unwrap(),
+        }
+    }
+
+    fn initialize_platform(&self, treasury: Pubkey) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::InitializePlatform,
+            vec![
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new(treasury, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+            ],
+        )
+    }
+
+    fn create_token(&self, creator: &Pubkey, mint: &Pubkey, name: &str, total_supply: u64, decimals: u8) -> Instruction {
+        let token = Token::new(&self.program_id, *mint, Keypair::new());
+        self.instruction(
+            AsterLaunchInstruction::CreateToken {
+                name: name.to_string(),
+                ticker: "ASTR".to_string(),
+                description: "A token on a bonding curve".to_string(),
+                metadata_uri: "https://example.com/aster.json".to_string(),
+                total_supply,
+                decimals,
+            },
+            vec![
+                AccountMeta::new(*creator, true),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new(token.token_state, false),
+                AccountMeta::new(*mint, true),
+                AccountMeta::new(token.bonding_curve, false),
+                AccountMeta::new(token.token_reserve, false),
+                AccountMeta::new(token.sol_reserve, false),
+                AccountMeta::new(*creator, true),
+                AccountMeta::new(self.treasury, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+                AccountMeta::new(token.creator_vault, false),
+            ],
+        )
+    }
+
+    fn trade_accounts(&self, token: &Token, trader: &Trader) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(trader.pubkey(), true),
+            AccountMeta::new(token.token_state, false),
+            AccountMeta::new(token.bonding_curve, false),
+            AccountMeta::new(token.token_reserve, false),
+            AccountMeta::new(token.sol_reserve, false),
+            AccountMeta::new(trader.token_account, false),
+            AccountMeta::new(trader.pubkey(), true),
+            AccountMeta::new(self.treasury, false),
+            AccountMeta::new(self.config, false),
+            AccountMeta::new_readonly(token.mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(token.creator_vault, false),
+            AccountMeta::new(UserReferral::find_address(&self.program_id, &trader.pubkey()).0, false),
+        ]
+    }
+
+    fn buy(&self, token: &Token, trader: &Trader, token_amount: u64, max_sol_amount: u64) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::BuyTokens { token_amount, max_sol_amount },
+            self.trade_accounts(token, trader),
+        )
+    }
+
+    fn sell(&self, token: &Token, trader: &Trader, token_amount: u64, min_sol_amount: u64) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::SellTokens { token_amount, min_sol_amount },
+            self.trade_accounts(token, trader),
+        )
+    }
+
+    /// A trade credited to `referral_account`
+    fn referred(&self, mut trade: Instruction, referral_account: Pubkey) -> Instruction {
+        trade.accounts.push(AccountMeta::new(referral_account, false));
+        trade
+    }
+
+    fn graduate(&self, token: &Token) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::GraduateToDEX,
+            vec![
+                AccountMeta::new(self.context.payer.pubkey(), true),
+                AccountMeta::new(token.token_state, false),
+                AccountMeta::new(token.bonding_curve, false),
+                AccountMeta::new(token.token_reserve, false),
+                AccountMeta::new(token.sol_reserve, false),
+                AccountMeta::new_readonly(self.config, false),
+            ],
+        )
+    }
+
+    /// An instruction signed by the platform authority on the config
+    fn admin(&self, instruction: AsterLaunchInstruction) -> Instruction {
+        self.instruction(
+            instruction,
+            vec![AccountMeta::new_readonly(self.authority.pubkey(), true), AccountMeta::new(self.config, false)],
+        )
+    }
+
+    fn pause_token(&self, token: &Token, paused: bool) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::PauseToken { paused },
+            vec![
+                AccountMeta::new_readonly(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(self.config, false),
+                AccountMeta::new(token.token_state, false),
+            ],
+        )
+    }
+
+    fn withdraw_treasury(&self, amount: u64, destination: Pubkey) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::WithdrawTreasury { amount, destination },
+            vec![
+                AccountMeta::new_readonly(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(self.config, false),
+                AccountMeta::new(self.treasury, false),
+                AccountMeta::new(destination, false),
+            ],
+        )
+    }
+
+    fn claim_platform_fees(&self, recipient: &Pubkey) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::ClaimPlatformFees,
+            vec![
+                AccountMeta::new(*recipient, true),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new(self.treasury, false),
+            ],
+        )
+    }
+
+    fn claim_creator_fees(&self, token: &Token, creator: &Pubkey) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::ClaimCreatorFees,
+            vec![
+                AccountMeta::new(*creator, true),
+                AccountMeta::new_readonly(token.token_state, false),
+                AccountMeta::new(token.bonding_curve, false),
+                AccountMeta::new(token.creator_vault, false),
+            ],
+        )
+    }
+
+    fn register_referrer(&self, referrer: &Pubkey) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::RegisterReferrer,
+            vec![
+                AccountMeta::new(*referrer, true),
+                AccountMeta::new(ReferralState::find_address(&self.program_id, referrer).0, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    }
+
+    fn claim_referral_fees(&self, signer: &Pubkey, referral_account: Pubkey) -> Instruction {
+        self.instruction(
+            AsterLaunchInstruction::ClaimReferralFees,
+            vec![AccountMeta::new(*signer, true), AccountMeta::new(referral_account, false)],
+        )
+    }
+}
+
+/// Assert `result` failed with `expected` from the program
+fn assert_error<T: std::fmt::Debug>(result: Result<T, BanksClientError>, expected: AsterLaunchError) {
+    match result.expect_err("the instruction should fail").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, expected as u32, "expected {:?}", expected)
+        }
+        err => panic!("expected {:?}, got {:?}", expected, err),
+    }
+}
+
+#[tokio::test]
+async fn token_lifecycle() {
+    let mut env = Env::start().await;
+    let rent_minimum = env.rent.minimum_balance(0);
+
+    let config: PlatformConfig = env.state(env.config).await;
+    assert!(config.is_initialized);
+    assert_eq!(config.authority, env.authority.pubkey());
+    assert_eq!(config.treasury, env.treasury);
+    assert_eq!(env.lamports(env.treasury).await, rent_minimum);
+
+    // Platform fees are split between two recipients
+    let recipients = [env.wallet().await, env.wallet().await];
+    let split = vec![
+        FeeSplit { recipient: recipients[0].pubkey(), weight_bps: 6000 },
+        FeeSplit { recipient: recipients[1].pubkey(), weight_bps: 4000 },
+    ];
+    env.send_as_authority(env.admin(AsterLaunchInstruction::SetFeeRecipients { recipients: split }))
+        .await
+        .unwrap();
+
+    // Creation: the creator pays the creation fee and the rent of every
+    // account, and the curve holds the whole supply
+    let creator = env.wallet().await;
+    let creator_before = env.lamports(creator.pubkey()).await;
+    let token = {
+        let mint = Keypair::new();
+        let create = env.create_token(&creator.pubkey(), &mint.pubkey(), "Aster", TOTAL_SUPPLY, DECIMALS);
+        env.send(create, &[&creator, &mint]).await.unwrap();
+        Token::new(&env.program_id, mint.pubkey(), creator)
+    };
+    let account_rent: u64 = [
+        spl_token::state::Mint::LEN,
+        TokenState::LEN,
+        BondingCurveState::LEN,
+        0,
+        0,
+        spl_token::state::Account::LEN,
+    ]
+    .iter()
+    .map(|len| env.rent.minimum_balance(*len))
+    .sum();
+    assert_eq!(
+        creator_before - env.lamports(token.creator.pubkey()).await,
+        config.creation_fee_lamports + account_rent
+    );
+    assert_eq!(env.lamports(env.treasury).await, rent_minimum + config.creation_fee_lamports);
+    assert_eq!(env.token_balance(token.token_reserve).await, TOTAL_SUPPLY);
+
+    let token_state: TokenState = env.state(token.token_state).await;
+    assert_eq!(token_state.mint, token.mint);
+    assert_eq!(token_state.creator, token.creator.pubkey());
+    assert_eq!(token_state.bonding_curve, token.bonding_curve);
+    assert_eq!(token_state.total_supply, TOTAL_SUPPLY);
+    assert!(!token_state.is_graduated);
+
+    let curve: BondingCurveState = env.state(token.bonding_curve).await;
+    assert_eq!(curve.total_supply, TOTAL_SUPPLY);
+    assert_eq!(curve.decimals, DECIMALS);
+    assert_eq!(curve.tokens_sold, 0);
+    assert_eq!(curve.creator_fee_bps, config.creator_fee_bps);
+    assert!(curve.is_active && !curve.has_graduated);
+    env.assert_ledgers(&token).await;
+
+    // Six wallets buy different amounts, then half of them sell part back
+    let mut traders = Vec::new();
+    for _ in 0..6 {
+        traders.push(env.trader(&token).await);
+    }
+    for (i, trader) in traders.iter().enumerate() {
+        env.buy_and_check(&token, trader, (i as u64 + 1) * 25 * TOKEN).await;
+    }
+    for (i, trader) in traders.iter().enumerate().skip(3) {
+        env.sell_and_check(&token, trader, (i as u64 - 2) * 10 * TOKEN + 1).await;
+    }
+
+    let curve: BondingCurveState = env.state(token.bonding_curve).await;
+    assert_eq!(curve.stats.buy_count, 6);
+    assert_eq!(curve.stats.sell_count, 3);
+    assert_eq!(curve.stats.trade_seq, 9);
+
+    // The curve cannot graduate before it sells out
+    assert_error(env.send(env.graduate(&token), &[]).await, AsterLaunchError::GraduationThresholdNotMet);
+
+    // The first wallet buys out the rest of the supply
+    let remaining = curve.total_supply - curve.tokens_sold;
+    let last_buy = env.buy_and_check(&token, &traders[0], remaining).await;
+    assert_eq!(last_buy.graduation_progress_bps, 10000);
+
+    let sol_reserve = env.lamports(token.sol_reserve).await;
+    env.send(env.graduate(&token), &[]).await.unwrap();
+    let curve: BondingCurveState = env.state(token.bonding_curve).await;
+    assert!(curve.has_graduated && !curve.is_active);
+    let token_state: TokenState = env.state(token.token_state).await;
+    assert!(token_state.is_graduated);
+    assert!(token_state.graduated_at >= token_state.created_at);
+    assert_eq!(env.lamports(token.sol_reserve).await, sol_reserve);
+
+    // A graduated curve no longer trades, and nothing moves
+    let trader = &traders[1];
+    let before = env.balances(&token, trader).await;
+    assert_error(
+        env.trade(env.buy(&token, trader, TOKEN, u64::MAX), trader).await,
+        AsterLaunchError::TokenAlreadyGraduated,
+    );
+    assert_error(
+        env.trade(env.sell(&token, trader, TOKEN, 0), trader).await,
+        AsterLaunchError::TokenAlreadyGraduated,
+    );
+    assert_error(env.send(env.graduate(&token), &[]).await, AsterLaunchError::TokenAlreadyGraduated);
+    assert_eq!(env.balances(&token, trader).await, before);
+
+    // The creator claims its fees; the vault keeps its rent
+    let accrued = curve.creator_fees_accrued;
+    assert!(accrued > 0);
+    let creator_before = env.lamports(token.creator.pubkey()).await;
+    let claim = env.claim_creator_fees(&token, &token.creator.pubkey());
+    env.send(claim.clone(), &[&token.creator]).await.unwrap();
+    assert_eq!(env.lamports(token.creator.pubkey()).await, creator_before + accrued);
+    assert_eq!(env.lamports(token.creator_vault).await, rent_minimum);
+    assert_error(env.send(claim, &[&token.creator]).await, AsterLaunchError::NothingToClaim);
+
+    // The recipients were credited every treasury fee by weight; the rest
+    // of the treasury is the authority's to withdraw
+    let config: PlatformConfig = env.state(env.config).await;
+    let owed: Vec<u64> = config.fee_recipients().iter().map(|r| r.accrued).collect();
+    assert!(owed[0] > owed[1] && owed[1] > 0);
+    let recipient_before = env.lamports(recipients[0].pubkey()).await;
+    env.send(env.claim_platform_fees(&recipients[0].pubkey()), &[&recipients[0]]).await.unwrap();
+    assert_eq!(env.lamports(recipients[0].pubkey()).await, recipient_before + owed[0]);
+
+    let treasury = env.lamports(env.treasury).await;
+    let available = treasury - rent_minimum - owed[1];
+    let destination = Pubkey::new_unique();
+    env.send_as_authority(env.withdraw_treasury(available, destination)).await.unwrap();
+    assert_eq!(env.lamports(destination).await, available);
+    assert_eq!(env.lamports(env.treasury).await, rent_minimum + owed[1]);
+    assert_error(
+        env.send_as_authority(env.withdraw_treasury(1, destination)).await,
+        AsterLaunchError::InsufficientFunds,
+    );
+}
+
+#[tokio::test]
+async fn platform_errors() {
+    let mut env = Env::start().await;
+    let stranger = env.wallet().await;
+
+    // Undecodable data, and pause flags that do not exist
+    let garbage = Instruction { data: vec![0xff], ..env.admin(AsterLaunchInstruction::InitializePlatform) };
+    assert_error(env.send_as_authority(garbage).await, AsterLaunchError::InvalidInstruction);
+    assert_error(
+        env.send_as_authority(env.admin(AsterLaunchInstruction::PausePlatform { flags: 1 << 7 })).await,
+        AsterLaunchError::InvalidInstruction,
+    );
+
+    // Only the authority administers the platform
+    let mut pause = env.admin(AsterLaunchInstruction::PausePlatform { flags: PlatformConfig::PAUSE_ALL });
+    pause.accounts[0].pubkey = stranger.pubkey();
+    assert_error(env.send(pause, &[&stranger]).await, AsterLaunchError::Unauthorized);
+
+    // Fee settings out of range
+    assert_error(
+        env.send_as_authority(env.admin(AsterLaunchInstruction::SetCreatorFee { creator_fee_bps: 51 })).await,
+        AsterLaunchError::InvalidFeeStructure,
+    );
+    assert_error(
+        env.send_as_authority(env.admin(AsterLaunchInstruction::SetReferralFee { referral_fee_bps: 10_001 })).await,
+        AsterLaunchError::InvalidFeeStructure,
+    );
+    let half = vec![FeeSplit { recipient: stranger.pubkey(), weight_bps: 5000 }];
+    assert_error(
+        env.send_as_authority(env.admin(AsterLaunchInstruction::SetFeeRecipients { recipients: half })).await,
+        AsterLaunchError::InvalidFeeStructure,
+    );
+
+    // The platform initializes once, with its own treasury
+    assert_error(
+        env.send_as_authority(env.initialize_platform(Pubkey::new_unique())).await,
+        AsterLaunchError::InvalidTreasury,
+    );
+
+    // Withdrawals: non-zero, to the named account, and only what is free
+    let destination = Pubkey::new_unique();
+    assert_error(
+        env.send_as_authority(env.withdraw_treasury(0, destination)).await,
+        AsterLaunchError::InvalidTradeAmount,
+    );
+    let mut misdirected = env.withdraw_treasury(1, destination);
+    misdirected.accounts[3].pubkey = stranger.pubkey();
+    assert_error(env.send_as_authority(misdirected).await, AsterLaunchError::InvalidAccountAddress);
+    assert_error(
+        env.send_as_authority(env.withdraw_treasury(1, destination)).await,
+        AsterLaunchError::InsufficientFunds,
+    );
+
+    // Claims by a recipient that is owed nothing, and by a non-recipient
+    let all = vec![FeeSplit { recipient: stranger.pubkey(), weight_bps: 10000 }];
+    env.send_as_authority(env.admin(AsterLaunchInstruction::SetFeeRecipients { recipients: all }))
+        .await
+        .unwrap();
+    assert_error(
+        env.send(env.claim_platform_fees(&stranger.pubkey()), &[&stranger]).await,
+        AsterLaunchError::NothingToClaim,
+    );
+    let other = env.wallet().await;
+    assert_error(
+        env.send(env.claim_platform_fees(&other.pubkey()), &[&other]).await,
+        AsterLaunchError::Unauthorized,
+    );
+}
+
+#[tokio::test]
+async fn create_token_errors() {
+    let mut env = Env::start().await;
+    let creator = env.wallet().await;
+    let mint = Keypair::new();
+    let create = |name: &str, total_supply, decimals| {
+        env.create_token(&creator.pubkey(), &mint.pubkey(), name, total_supply, decimals)
+    };
+
+    let long_name = create(&"A".repeat(TokenState::MAX_NAME_LEN + 1), TOTAL_SUPPLY, DECIMALS);
+    let no_supply = create("Aster", 0, DECIMALS);
+    let too_precise = create("Aster", TOTAL_SUPPLY, asterlaunch_curve::MAX_DECIMALS + 1);
+    let mut wrong_treasury = create("Aster", TOTAL_SUPPLY, DECIMALS);
+    wrong_treasury.accounts[8].pubkey = creator.pubkey();
+    let valid = create("Aster", TOTAL_SUPPLY, DECIMALS);
+
+    assert_error(env.send(long_name, &[&creator, &mint]).await, AsterLaunchError::InvalidTokenMetadata);
+    assert_error(env.send(no_supply, &[&creator, &mint]).await, AsterLaunchError::InvalidTradeAmount);
+    assert_error(env.send(too_precise, &[&creator, &mint]).await, AsterLaunchError::InvalidCurveParameters);
+    assert_error(env.send(wrong_treasury, &[&creator, &mint]).await, AsterLaunchError::InvalidTreasury);
+
+    // Paused creations
+    let flags = PlatformConfig::PAUSE_CREATIONS;
+    env.send_as_authority(env.admin(AsterLaunchInstruction::PausePlatform { flags })).await.unwrap();
+    assert_error(env.send(valid.clone(), &[&creator, &mint]).await, AsterLaunchError::PlatformPaused);
+    env.send_as_authority(env.admin(AsterLaunchInstruction::UnpausePlatform { flags })).await.unwrap();
+
+    // A mint launches once
+    env.send(valid.clone(), &[&creator, &mint]).await.unwrap();
+    assert_error(env.send(valid, &[&creator, &mint]).await, AsterLaunchError::TokenAlreadyExists);
+
+    // An overflowing platform counter fails the creation, and every account
+    // it would have created with it
+    let mut config: PlatformConfig = env.state(env.config).await;
+    config.total_tokens_created = u64::MAX;
+    env.set_state(env.config, &config).await;
+    let mint = Keypair::new();
+    let create = env.create_token(&creator.pubkey(), &mint.pubkey(), "Aster", TOTAL_SUPPLY, DECIMALS);
+    assert_error(env.send(create, &[&creator, &mint]).await, AsterLaunchError::MathOverflow);
+    assert!(env.account(mint.pubkey()).await.is_none());
+    assert!(env.account(TokenState::find_address(&env.program_id, &mint.pubkey()).0).await.is_none());
+}
+
+#[tokio::test]
+async fn trade_errors() {
+    let mut env = Env::start().await;
+    let token = env.launch().await;
+    let other = env.launch().await;
+    let trader = env.trader(&token).await;
+
+    // Claiming before any trade
+    assert_error(
+        env.send(env.claim_creator_fees(&token, &token.creator.pubkey()), &[&token.creator]).await,
+        AsterLaunchError::NothingToClaim,
+    );
+
+    // Amounts the curve cannot fill
+    assert_error(env.trade(env.buy(&token, &trader, 0, u64::MAX), &trader).await, AsterLaunchError::InvalidTradeAmount);
+    assert_error(
+        env.trade(env.buy(&token, &trader, TOTAL_SUPPLY + 1, u64::MAX), &trader).await,
+        AsterLaunchError::InvalidTradeAmount,
+    );
+
+    // Slippage on both sides
+    let quote = env.buy_and_check(&token, &trader, 100 * TOKEN).await;
+    let curve: BondingCurveState = env.state(token.bonding_curve).await;
+    let next = quote::quote_buy(&curve, TOKEN).unwrap();
+    assert_error(
+        env.trade(env.buy(&token, &trader, TOKEN, next.total_cost - 1), &trader).await,
+        AsterLaunchError::SlippageExceeded,
+    );
+    let sell = quote::quote_sell(&curve, TOKEN).unwrap();
+    assert_error(
+        env.trade(env.sell(&token, &trader, TOKEN, sell.sol_out + 1), &trader).await,
+        AsterLaunchError::SlippageExceeded,
+    );
+    assert_error(
+        env.trade(env.sell(&token, &trader, quote.token_amount + 1, 0), &trader).await,
+        AsterLaunchError::InvalidTradeAmount,
+    );
+
+    // Only the creator claims its fees
+    assert_error(
+        env.send(env.claim_creator_fees(&token, &trader.pubkey()), &[&trader.keypair]).await,
+        AsterLaunchError::Unauthorized,
+    );
+
+    // Paused buys still let holders sell
+    let flags = PlatformConfig::PAUSE_BUYS;
+    env.send_as_authority(env.admin(AsterLaunchInstruction::PausePlatform { flags })).await.unwrap();
+    assert_error(env.trade(env.buy(&token, &trader, TOKEN, u64::MAX), &trader).await, AsterLaunchError::PlatformPaused);
+    env.sell_and_check(&token, &trader, TOKEN).await;
+    env.send_as_authority(env.admin(AsterLaunchInstruction::UnpausePlatform { flags })).await.unwrap();
+
+    // A paused token trades neither way
+    env.send_as_authority(env.pause_token(&token, true)).await.unwrap();
+    assert_error(env.trade(env.buy(&token, &trader, TOKEN, u64::MAX), &trader).await, AsterLaunchError::TokenPaused);
+    assert_error(env.trade(env.sell(&token, &trader, TOKEN, 0), &trader).await, AsterLaunchError::TokenPaused);
+    env.send_as_authority(env.pause_token(&token, false)).await.unwrap();
+
+    // Accounts that belong to another token or are not the treasury
+    let mut wrong_vault = env.buy(&token, &trader, TOKEN, u64::MAX);
+    wrong_vault.accounts[12].pubkey = other.creator_vault;
+    assert_error(env.trade(wrong_vault, &trader).await, AsterLaunchError::InvalidAccountAddress);
+    let mut wrong_reserve = env.sell(&token, &trader, TOKEN, 0);
+    wrong_reserve.accounts[4].pubkey = other.sol_reserve;
+    assert_error(env.trade(wrong_reserve, &trader).await, AsterLaunchError::InvalidAccountAddress);
+    let mut wrong_treasury = env.buy(&token, &trader, TOKEN, u64::MAX);
+    wrong_treasury.accounts[7].pubkey = token.creator.pubkey();
+    assert_error(env.trade(wrong_treasury, &trader).await, AsterLaunchError::InvalidTreasury);
+
+    // A recipient owed fees cannot be dropped from the split
+    let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let split = |recipient| vec![FeeSplit { recipient, weight_bps: 10000 }];
+    env.send_as_authority(env.admin(AsterLaunchInstruction::SetFeeRecipients { recipients: split(recipients[0]) }))
+        .await
+        .unwrap();
+    env.buy_and_check(&token, &trader, TOKEN).await;
+    assert_error(
+        env.send_as_authority(env.admin(AsterLaunchInstruction::SetFeeRecipients { recipients: split(recipients[1]) }))
+            .await,
+        AsterLaunchError::UnclaimedFees,
+    );
+
+    // A curve whose redeemable reserve no longer covers its holders takes
+    // no more trades
+    let mut curve: BondingCurveState = env.state(token.bonding_curve).await;
+    curve.redeemable_sol -= 1_000;
+    curve.liquidity_sol += 1_000;
+    env.set_state(token.bonding_curve, &curve).await;
+    let before = env.balances(&token, &trader).await;
+    assert_error(env.trade(env.buy(&token, &trader, TOKEN, u64::MAX), &trader).await, AsterLaunchError::ReserveInsolvent);
+    assert_eq!(env.balances(&token, &trader).await, before);
+}
+
+#[tokio::test]
+async fn referred_trades() {
+    let mut env = Env::start().await;
+    let token = env.launch().await;
+    let referrer = env.trader(&token).await;
+    let rival = env.wallet().await;
+    let trader = env.trader(&token).await;
+
+    let referral = ReferralState::find_address(&env.program_id, &referrer.pubkey()).0;
+    let rival_referral = ReferralState::find_address(&env.program_id, &rival.pubkey()).0;
+    env.send(env.register_referrer(&referrer.pubkey()), &[&referrer.keypair]).await.unwrap();
+    env.send(env.register_referrer(&rival.pubkey()), &[&rival]).await.unwrap();
+
+    // The first referred trade records the referrer, paid for by the trader,
+    // and the referrer's cut comes out of the treasury's
+    let curve: BondingCurveState = env.state(token.bonding_curve).await;
+    let quote = quote::quote_buy(&curve, 10 * TOKEN).unwrap();
+    let config: PlatformConfig = env.state(env.config).await;
+    let referral_fee = config.referral_fee(quote.fees.platform_fee).unwrap();
+    assert!(referral_fee > 0);
+    let before = env.balances(&token, &trader).await;
+    let referral_before = env.lamports(referral).await;
+
+    let buy = env.referred(env.buy(&token, &trader, 10 * TOKEN, quote.total_cost), referral);
+    env.trade(buy, &trader).await.unwrap();
+    let after = env.balances(&token, &trader).await;
+    assert_eq!(before.wallet - after.wallet, quote.total_cost + env.rent.minimum_balance(UserReferral::LEN));
+    assert_eq!(after.treasury - before.treasury, quote.fees.platform_fee - referral_fee);
+    assert_eq!(env.lamports(referral).await, referral_before + referral_fee);
+
+    let record: UserReferral =
+        env.state(UserReferral::find_address(&env.program_id, &trader.pubkey()).0).await;
+    assert_eq!(record.referrer, referrer.pubkey());
+    let state: ReferralState = env.state(referral).await;
+    assert_eq!((state.accrued, state.referred_trades, state.referred_users), (referral_fee, 1, 1));
+    env.assert_ledgers(&token).await;
+
+    // The recorded referrer sticks, and cannot be left out
+    let switch = env.referred(env.sell(&token, &trader, TOKEN, 0), rival_referral);
+    assert_error(env.trade(switch, &trader).await, AsterLaunchError::ReferrerMismatch);
+    assert_error(env.trade(env.sell(&token, &trader, TOKEN, 0), &trader).await, AsterLaunchError::ReferrerMismatch);
+
+    // Nobody else can pay for a wallet's first referred buy and bind it to
+    // their own referral account
+    let attacker = env.trader(&token).await;
+    let victim = env.wallet().await;
+    let victim_record = UserReferral::find_address(&env.program_id, &victim.pubkey()).0;
+    let mut spoofed = env.referred(env.buy(&token, &attacker, TOKEN, u64::MAX), rival_referral);
+    spoofed.accounts[0] = AccountMeta::new(victim.pubkey(), false);
+    spoofed.accounts[13] = AccountMeta::new(victim_record, false);
+    let err = env.trade(spoofed, &attacker).await.expect_err("the instruction should fail").unwrap();
+    assert!(
+        matches!(err, TransactionError::InstructionError(_, InstructionError::MissingRequiredSignature)),
+        "expected a missing signature, got {:?}",
+        err
+    );
+    assert!(env.account(victim_record).await.is_none());
+
+    // No self-referral, and no referral accounts the program does not own
+    let own = env.referred(env.buy(&token, &referrer, TOKEN, u64::MAX), referral);
+    assert_error(env.trade(own, &referrer).await, AsterLaunchError::InvalidReferrer);
+    let forged = env.referred(env.buy(&token, &referrer, TOKEN, u64::MAX), rival.pubkey());
+    assert_error(env.trade(forged, &referrer).await, AsterLaunchError::InvalidReferrer);
+
+    // Claims: only by the referrer, and only what has accrued
+    assert_error(
+        env.send(env.claim_referral_fees(&rival.pubkey(), referral), &[&rival]).await,
+        AsterLaunchError::Unauthorized,
+    );
+    let wallet_before = env.lamports(referrer.pubkey()).await;
+    let claim = env.claim_referral_fees(&referrer.pubkey(), referral);
+    env.send(claim.clone(), &[&referrer.keypair]).await.unwrap();
+    assert_eq!(env.lamports(referrer.pubkey()).await, wallet_before + referral_fee);
+    assert_eq!(env.lamports(referral).await, env.rent.minimum_balance(ReferralState::LEN));
+    assert_error(env.send(claim, &[&referrer.keypair]).await, AsterLaunchError::NothingToClaim);
+}
@@ -3,6 +3,7 @@
 import { useState, useEffect } from 'react';
 import { useParams } from 'next/navigation';
 import { getTokenByTicker } from '@/lib/mockData';
+import { estimateBuy, estimateSell, formatSol, parseSol } from '@/lib/quote';
 import { Token } from '@/types';
 import { LineChart, Line, XAxis, YAxis, Tooltip, ResponsiveContainer } from 'recharts';
 
@@ -12,12 +13,42 @@ export default function TokenPage() {
   const [token, setToken] = useState<Token | null>(null);
   const [tradeAmount, setTradeAmount] = useState('');
   const [tradeType, setTradeType] = useState<'buy' | 'sell'>('buy');
+  const [estimate, setEstimate] = useState<string | null>(null);
 
   useEffect(() => {
     const foundToken = getTokenByTicker(ticker);
     setToken(foundToken || null);
   }, [ticker]);
 
+  // Quote with the program's integer math; sells are in whole tokens
+  useEffect(() => {
+    setEstimate(null);
+    if (!token || !tradeAmount) return;
+
+    let quote: Promise<string> | null = null;
+    if (tradeType === 'buy') {
+      const lamports = parseSol(tradeAmount);
+      if (lamports !== null) {
+        quote = estimateBuy(token, lamports).then((tokens) => `${tokens.toLocaleString()} ${token.ticker}`);
+      }
+    } else if (/^\d+$/.test(tradeAmount.trim())) {
+      quote = estimateSell(token, BigInt(tradeAmount.trim())).then((lamports) => `${formatSol(lamports)} SOL`);
+    }
+    if (!quote) return;
+
+    let cancelled = false;
+    quote
+      .then((text) => {
+        if (!cancelled) setEstimate(text);
+      })
+      .catch(() => {
+        if (!cancelled) setEstimate('Amount exceeds the curve');
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [token, tradeAmount, tradeType]);
+
   if (!token) {
     return (
       <div className="min-h-screen flex items-center justify-center">
@@ -215,10 +246,7 @@ export default function TokenPage() {
               <div className="bg-background-tertiary rounded-lg p-4 mb-6">
                 <p className="text-xs text-text-secondary mb-1">You will receive (estimated)</p>
                 <p className="text-xl font-bold text-text-primary">
-                  {tradeType === 'buy' 
-                    ? `~${(parseFloat(tradeAmount) / token.price).toFixed(2)} ${token.ticker}`
-                    : `~${(parseFloat(tradeAmount) * token.price).toFixed(4)} SOL`
-                  }
+                  {estimate ?? '…'}
                 </p>
               </div>
             )}
@@ -0,0 +1,73 @@
+import init, { NativeCurve } from '../../curve-wasm/pkg/asterlaunch_curve_wasm';
+import { Token } from '@/types';
+
+// Quotes from the program's own integer math (asterlaunch-curve compiled to
+// WebAssembly), so estimates match what a trade charges to the lamport.
+// Build the bindings first with `npm run build:wasm`.
+
+/** Curve parameters `CreateToken` gives every token (lamports per whole
+ * token); the increment is scaled by the mint's decimals on chain */
+const INITIAL_PRICE = BigInt(100000);
+const PRICE_INCREMENT = BigInt(10);
+
+const LAMPORTS_PER_SOL = BigInt(1000000000);
+
+let ready: Promise<unknown> | null = null;
+
+const load = () => {
+  ready ??= init();
+  return ready;
+};
+
+/** Base units per whole token */
+const unitsPerToken = (token: Token): bigint => BigInt(10 ** (token.decimals ?? 0));
+
+const withCurve = async <T>(token: Token, quote: (curve: NativeCurve) => T): Promise<T> => {
+  await load();
+  // The curve counts base units; creator fees come out of the platform
+  // share, so totals don't depend on them
+  const units = unitsPerToken(token);
+  const curve = new NativeCurve(
+    INITIAL_PRICE,
+    PRICE_INCREMENT * units,
+    BigInt(Math.floor(token.totalSupply)) * units,
+    BigInt(Math.floor(token.tokensSold)) * units,
+    token.decimals ?? 0,
+    0,
+  );
+  try {
+    return quote(curve);
+  } finally {
+    curve.free();
+  }
+};
+
+/** Parse a decimal SOL amount into lamports without going through floats */
+export const parseSol = (sol: string): bigint | null => {
+  const match = /^(\d*)(?:\.(\d{0,9})\d*)?$/.exec(sol.trim());
+  if (!match || (!match[1] && !match[2])) return null;
+  const whole = BigInt(match[1] || '0');
+  const fraction = BigInt((match[2] || '').padEnd(9, '0'));
+  return whole * LAMPORTS_PER_SOL + fraction;
+};
+
+export const formatSol = (lamports: bigint, digits = 4): string => {
+  const whole = lamports / LAMPORTS_PER_SOL;
+  const fraction = (lamports % LAMPORTS_PER_SOL).toString().padStart(9, '0').slice(0, digits);
+  return digits > 0 ? `${whole}.${fraction}` : whole.toString();
+};
+
+/** Whole tokens a SOL budget buys, fees included */
+export const estimateBuy = (token: Token, lamports: bigint): Promise<bigint> =>
+  withCurve(token, (curve) => curve.maxBuy(lamports) / unitsPerToken(token));
+
+/** SOL received for selling whole tokens, after fees */
+export const estimateSell = (token: Token, tokens: bigint): Promise<bigint> =>
+  withCurve(token, (curve) => {
+    const quote = curve.quoteSell(tokens * unitsPerToken(token));
+    try {
+      return quote.solOut;
+    } finally {
+      quote.free();
+    }
+  });
@@ -13,6 +13,8 @@ export interface Token {
   change24h: number;
   volume24h: number;
   totalSupply: number;
+  /** Mint decimals; supplies here are whole tokens (tokens from before decimals were set have 0) */
+  decimals?: number;
   
   // Bonding curve data
   tokensSold: number;
@@ -0,0 +1,372 @@
+//! Event extraction and trade decoding
+//!
+//! The native program logs events as `Program data:` lines; the Anchor
+//! program emits them through a self-CPI (`emit_cpi!`). Log lines are
+//! attributed to the program on top of the invocation stack, so events are
+//! picked up when an AsterLaunch program is invoked through CPI, and data
+//! logged by any other program is ignored.
+
+use std::{collections::HashMap, str::FromStr};
+
+use asterlaunch_program::events::{self, AsterLaunchEvent, TradeSide};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    model::{ui_amount, CurveSnapshot, Side, Trade, LAMPORTS_PER_SOL},
+    transaction::TransactionRecord,
+};
+
+/// Mint decimals of tokens launched by the Anchor program
+pub const ANCHOR_TOKEN_DECIMALS: u8 = 6;
+
+/// Mint decimals of native tokens launched before `CreateToken` took decimals
+pub const NATIVE_LEGACY_DECIMALS: u8 = 0;
+
+/// Deployed programs to decode events from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Programs {
+    /// Native `asterlaunch-program` deployment
+    pub native: Option<Pubkey>,
+
+    /// Anchor `asterlaunch` deployment
+    pub anchor: Option<Pubkey>,
+}
+
+impl Programs {
+    /// IDs of the configured programs
+    pub fn ids(&self) -> Vec<Pubkey> {
+        self.native.into_iter().chain(self.anchor).collect()
+    }
+}
+
+/// Program an event came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramKind {
+    Native,
+    Anchor,
+}
+
+/// An event and the program that emitted it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedEvent {
+    pub program: ProgramKind,
+    pub event: AsterLaunchEvent,
+}
+
+/// Everything decoded from one transaction
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodedTransaction {
+    pub signature: String,
+    pub slot: u64,
+
+    /// Failed transactions commit nothing and decode to no events
+    pub failed: bool,
+
+    /// The node truncated the logs, so native events may be missing
+    pub logs_truncated: bool,
+
+    pub events: Vec<DecodedEvent>,
+    pub trades: Vec<Trade>,
+    pub snapshots: Vec<CurveSnapshot>,
+}
+
+/// Decodes AsterLaunch transactions, remembering each mint's decimals
+#[derive(Debug, Clone, Default)]
+pub struct Decoder {
+    programs: Programs,
+    decimals: HashMap<Pubkey, u8>,
+}
+
+impl Decoder {
+    pub fn new(programs: Programs) -> Self {
+        Self {
+            programs,
+            decimals: HashMap::new(),
+        }
+    }
+
+    /// Record a mint's decimals, for tokens created before decoding started
+    pub fn set_decimals(&mut self, mint: Pubkey, decimals: u8) {
+        self.decimals.insert(mint, decimals);
+    }
+
+    /// Decimals of a mint, falling back to the program's default
+    pub fn decimals(&self, program: ProgramKind, mint: &Pubkey) -> u8 {
+        self.decimals.get(mint).copied().unwrap_or(match program {
+            ProgramKind::Native => NATIVE_LEGACY_DECIMALS,
+            ProgramKind::Anchor => ANCHOR_TOKEN_DECIMALS,
+        })
+    }
+
+    /// Extract the events of a successful transaction, in emission order per
+    /// program, and whether the logs were truncated
+    pub fn events(&self, tx: &TransactionRecord) -> (Vec<DecodedEvent>, bool) {
+        if tx.failed {
+            return (Vec::new(), false);
+        }
+
+        let (mut decoded, truncated) = self.logged_events(&tx.log_messages);
+
+        if let Some(anchor) = self.programs.anchor {
+            decoded.extend(
+                tx.inner_instructions
+                    .iter()
+                    .filter(|instruction| instruction.program_id == anchor)
+                    .filter_map(|instruction| AsterLaunchEvent::decode_cpi(&instruction.data))
+                    .map(|event| DecodedEvent { program: ProgramKind::Anchor, event }),
+            );
+        }
+
+        (decoded, truncated)
+    }
+
+    /// Events logged while an AsterLaunch program was executing
+    fn logged_events(&self, logs: &[String]) -> (Vec<DecodedEvent>, bool) {
+        let mut decoded = Vec::new();
+        let mut stack: Vec<Option<Pubkey>> = Vec::new();
+
+        for line in logs {
+            if line == "Log truncated" {
+                return (decoded, true);
+            }
+            if line.starts_with("Program data: ") {
+                let program = stack.last().copied().flatten().and_then(|id| self.program_kind(&id));
+                if let (Some(program), Some(event)) = (program, AsterLaunchEvent::decode_log(line)) {
+                    decoded.push(DecodedEvent { program, event });
+                }
+                continue;
+            }
+
+            // "Program <id> invoke [n]", "Program <id> success", "Program <id> failed: ..."
+            let mut parts = line.split_whitespace();
+            if parts.next() != Some("Program") {
+                continue;
+            }
+            let program_id = parts.next().and_then(|id| Pubkey::from_str(id).ok());
+            match parts.next() {
+                Some("invoke") => stack.push(program_id),
+                Some("success") | Some("failed:") => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+
+        (decoded, false)
+    }
+
+    fn program_kind(&self, program_id: &Pubkey) -> Option<ProgramKind> {
+        if self.programs.native == Some(*program_id) {
+            Some(ProgramKind::Native)
+        } else if self.programs.anchor == Some(*program_id) {
+            Some(ProgramKind::Anchor)
+        } else {
+            None
+        }
+    }
+
+    /// Decode a transaction into events, trades and curve snapshots
+    pub fn decode(&mut self, tx: &TransactionRecord) -> DecodedTransaction {
+        let (events, logs_truncated) = self.events(tx);
+        let mut trades = Vec::new();
+        let mut snapshots = Vec::new();
+
+        for (index, decoded) in events.iter().enumerate() {
+            match &decoded.event {
+                AsterLaunchEvent::TokenCreated(created) => {
+                    self.set_decimals(created.mint, created.decimals);
+                }
+                AsterLaunchEvent::Trade(trade) => {
+                    let decimals = self.decimals(decoded.program, &trade.mint);
+                    let (decoded_trade, snapshot) = convert_trade(tx, index, trade, decimals);
+                    trades.push(decoded_trade);
+                    snapshots.push(snapshot);
+                }
+                AsterLaunchEvent::Graduated(_) => {}
+            }
+        }
+
+        DecodedTransaction {
+            signature: tx.signature.clone(),
+            slot: tx.slot,
+            failed: tx.failed,
+            logs_truncated,
+            events,
+            trades,
+            snapshots,
+        }
+    }
+}
+
+/// Build the frontend trade record and post-trade curve snapshot of a trade event
+fn convert_trade(
+    tx: &TransactionRecord,
+    index: usize,
+    trade: &events::Trade,
+    decimals: u8,
+) -> (Trade, CurveSnapshot) {
+    let amount = ui_amount(trade.token_amount, decimals);
+    let sol = trade.sol_amount as f64 / LAMPORTS_PER_SOL;
+    let timestamp = trade.timestamp.saturating_mul(1000);
+
+    let decoded_trade = Trade {
+        id: format!("{}:{}", tx.signature, index),
+        side: match trade.side {
+            TradeSide::Buy => Side::Buy,
+            TradeSide::Sell => Side::Sell,
+        },
+        amount,
+        price: if amount > 0.0 { sol / amount } else { 0.0 },
+        timestamp,
+        wallet: trade.trader.to_string(),
+        signature: tx.signature.clone(),
+        mint: trade.mint.to_string(),
+        slot: tx.slot,
+        trade_seq: trade.stats.trade_seq,
+        token_amount: trade.token_amount,
+        sol_amount: trade.sol_amount,
+        decimals,
+    };
+
+    let snapshot = CurveSnapshot {
+        mint: trade.mint.to_string(),
+        signature: tx.signature.clone(),
+        slot: tx.slot,
+        timestamp,
+        price: trade.price as f64 / LAMPORTS_PER_SOL,
+        price_lamports: trade.price,
+        sol_reserves: trade.sol_reserves,
+        token_reserves: trade.token_reserves,
+        decimals,
+    };
+
+    (decoded_trade, snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::InnerInstruction;
+    use asterlaunch_program::{
+        events::{Event, EVENT_IX_TAG_LE},
+        state::TradeStats,
+    };
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    fn trade_event(side: TradeSide) -> events::Trade {
+        events::Trade {
+            mint: Pubkey::new_unique(),
+            trader: Pubkey::new_unique(),
+            side,
+            token_amount: 2_000_000,
+            sol_amount: 101_000_000,
+            liquidity_fee: 0,
+            platform_fee: 800_000,
+            creator_fee: 200_000,
+            referral_fee: 0,
+            sol_reserves: 100_000_000,
+            token_reserves: 998_000_000,
+            price: 51_000,
+            timestamp: 1_700_000_000,
+            stats: TradeStats {
+                trade_seq: 1,
+                ..TradeStats::default()
+            },
+        }
+    }
+
+    fn data_log(event: &impl Event) -> String {
+        format!("Program data: {}", STANDARD.encode(event.data()))
+    }
+
+    #[test]
+    fn test_native_events_through_cpi() {
+        let native = Pubkey::new_unique();
+        let router = Pubkey::new_unique();
+        let mut decoder = Decoder::new(Programs { native: Some(native), anchor: None });
+
+        let trade = trade_event(TradeSide::Buy);
+        let tx = TransactionRecord {
+            signature: "sig".to_string(),
+            slot: 42,
+            log_messages: vec![
+                format!("Program {} invoke [1]", router),
+                // Another program logging an AsterLaunch event is ignored
+                data_log(&trade),
+                format!("Program {} invoke [2]", native),
+                "Program log: Instruction: BuyTokens".to_string(),
+                data_log(&trade),
+                format!("Program {} consumed 5000 of 190000 compute units", native),
+                format!("Program {} success", native),
+                data_log(&trade),
+                format!("Program {} success", router),
+            ],
+            ..TransactionRecord::default()
+        };
+
+        let decoded = decoder.decode(&tx);
+        assert_eq!(decoded.events.len(), 1);
+        assert_eq!(decoded.trades.len(), 1);
+        assert!(!decoded.logs_truncated);
+
+        // Native tokens have no decimals: 2M tokens for 0.101 SOL
+        let decoded_trade = &decoded.trades[0];
+        assert_eq!(decoded_trade.id, "sig:0");
+        assert_eq!(decoded_trade.side, Side::Buy);
+        assert_eq!(decoded_trade.amount, 2_000_000.0);
+        assert_eq!(decoded_trade.timestamp, 1_700_000_000_000);
+        assert_eq!(decoded_trade.wallet, trade.trader.to_string());
+        assert_eq!(decoded.snapshots[0].price_lamports, 51_000);
+
+        // Frontend field names
+        let json = serde_json::to_value(decoded_trade).unwrap();
+        assert_eq!(json["type"], "buy");
+        assert_eq!(json["txHash"], "sig");
+    }
+
+    #[test]
+    fn test_anchor_events_and_failed_transactions() {
+        let anchor = Pubkey::new_unique();
+        let mut decoder = Decoder::new(Programs { native: None, anchor: Some(anchor) });
+
+        let trade = trade_event(TradeSide::Sell);
+        let mut data = EVENT_IX_TAG_LE.to_vec();
+        data.extend_from_slice(&trade.data());
+        let mut tx = TransactionRecord {
+            signature: "sig".to_string(),
+            inner_instructions: vec![
+                InnerInstruction { program_id: Pubkey::new_unique(), data: data.clone(), stack_height: Some(2) },
+                InnerInstruction { program_id: anchor, data, stack_height: Some(2) },
+            ],
+            ..TransactionRecord::default()
+        };
+
+        let decoded = decoder.decode(&tx);
+        assert_eq!(decoded.trades.len(), 1);
+        // Anchor tokens have 6 decimals
+        assert_eq!(decoded.trades[0].amount, 2.0);
+        assert_eq!(decoded.trades[0].side, Side::Sell);
+
+        tx.failed = true;
+        let decoded = decoder.decode(&tx);
+        assert!(decoded.failed);
+        assert!(decoded.events.is_empty() && decoded.trades.is_empty());
+    }
+
+    #[test]
+    fn test_truncated_logs() {
+        let native = Pubkey::new_unique();
+        let decoder = Decoder::new(Programs { native: Some(native), anchor: None });
+
+        let tx = TransactionRecord {
+            log_messages: vec![
+                format!("Program {} invoke [1]", native),
+                "Log truncated".to_string(),
+            ],
+            ..TransactionRecord::default()
+        };
+        let (events, truncated) = decoder.events(&tx);
+        assert!(events.is_empty());
+        assert!(truncated);
+    }
+}
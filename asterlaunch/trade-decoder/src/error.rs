@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+/// Errors converting an RPC transaction for decoding
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("Transaction Has No Status Metadata")]
+    MissingMeta,
+
+    #[error("Transaction Has No Signature")]
+    MissingSignature,
+
+    #[error("Unsupported Transaction Encoding")]
+    UnsupportedEncoding,
+
+    #[error("Invalid Pubkey: {0}")]
+    InvalidPubkey(String),
+
+    #[error("Invalid Account Index: {0}")]
+    InvalidAccountIndex(u8),
+
+    #[error("Invalid Instruction Data")]
+    InvalidInstructionData,
+
+    #[error("Invalid Token Amount: {0}")]
+    InvalidTokenAmount(String),
+}
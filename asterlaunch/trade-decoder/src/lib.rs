@@ -0,0 +1,20 @@
+/*
+ * AsterLaunch Trade Decoder
+ * 
+ * Turns confirmed transactions of either AsterLaunch program into records
+ * shaped like the frontend's `Trade` and `PricePoint` types:
+ * - Events are read from `Program data:` logs (native program) and
+ *   `emit_cpi!` inner instructions (Anchor program)
+ * - Failed transactions decode to nothing
+ * - Programs invoked through CPI are attributed by the log invocation stack
+ */
+
+pub mod decoder;
+pub mod error;
+pub mod model;
+pub mod transaction;
+
+pub use decoder::{DecodedEvent, DecodedTransaction, Decoder, ProgramKind, Programs};
+pub use error::DecodeError;
+pub use model::{CurveSnapshot, Side, Trade};
+pub use transaction::{InnerInstruction, Instruction, TokenBalance, TransactionRecord};
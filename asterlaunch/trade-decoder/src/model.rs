@@ -0,0 +1,89 @@
+//! Records shaped like the frontend's data model (`src/types/index.ts`)
+//!
+//! Fields the frontend reads keep its names and units (whole tokens, SOL,
+//! milliseconds); the exact on-chain values travel alongside them.
+
+use serde::{Deserialize, Serialize};
+
+/// Lamports per SOL
+pub const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Direction of a trade, serialized as the frontend's `'buy' | 'sell'`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// A trade, matching the frontend's `Trade`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trade {
+    /// `<signature>:<event index>`, unique per trade
+    pub id: String,
+
+    #[serde(rename = "type")]
+    pub side: Side,
+
+    /// Tokens traded (whole tokens)
+    pub amount: f64,
+
+    /// SOL per whole token the trader paid or received, fees included
+    pub price: f64,
+
+    /// On-chain clock at the trade (milliseconds since the epoch)
+    pub timestamp: i64,
+
+    /// Trader
+    pub wallet: String,
+
+    #[serde(rename = "txHash")]
+    pub signature: String,
+
+    pub mint: String,
+    pub slot: u64,
+
+    /// The mint's trade number, one more than its previous trade's (0 if
+    /// the program did not report it)
+    pub trade_seq: u64,
+
+    /// Tokens traded (base units)
+    pub token_amount: u64,
+
+    /// Lamports paid by the buyer or received by the seller
+    pub sol_amount: u64,
+
+    pub decimals: u8,
+}
+
+/// Curve state after a trade, the source of the frontend's `PricePoint`s
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurveSnapshot {
+    pub mint: String,
+    pub signature: String,
+    pub slot: u64,
+
+    /// Milliseconds since the epoch
+    pub timestamp: i64,
+
+    /// SOL per whole token
+    pub price: f64,
+
+    /// Lamports per whole token
+    pub price_lamports: u64,
+
+    /// Lamports held by the curve, excluding rent
+    pub sol_reserves: u64,
+
+    /// Unsold tokens (base units)
+    pub token_reserves: u64,
+
+    pub decimals: u8,
+}
+
+/// Convert base units to whole tokens
+pub fn ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
@@ -0,0 +1,200 @@
+//! Program-agnostic view of a confirmed transaction
+//!
+//! Only the parts the decoders need: status, logs, instructions with their
+//! program IDs resolved and post-transaction token balances.
+
+use std::str::FromStr;
+
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    EncodedTransaction, UiInstruction, UiMessage, UiTransactionTokenBalance,
+};
+
+use crate::error::DecodeError;
+
+/// A top-level instruction of the transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// An instruction invoked by a program during the transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerInstruction {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+
+    /// Invocation depth, 2 for instructions invoked by a top-level instruction
+    pub stack_height: Option<u32>,
+}
+
+/// Balance of a token account after the transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenBalance {
+    pub account: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Option<Pubkey>,
+
+    /// Base units
+    pub amount: u64,
+}
+
+/// A confirmed transaction to decode
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+
+    /// The transaction failed and none of its effects were committed
+    pub failed: bool,
+
+    pub log_messages: Vec<String>,
+
+    pub instructions: Vec<Instruction>,
+
+    /// Inner instructions of every top-level instruction, in execution order
+    pub inner_instructions: Vec<InnerInstruction>,
+
+    /// Token accounts the transaction touched
+    pub token_balances: Vec<TokenBalance>,
+}
+
+impl TransactionRecord {
+    /// Convert a transaction fetched with `getTransaction`
+    ///
+    /// Supports the `base58`, `base64` and `json` encodings; `jsonParsed`
+    /// inner instructions carry no raw data and are rejected
+    pub fn from_encoded(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Result<Self, DecodeError> {
+        let meta = tx
+            .transaction
+            .meta
+            .as_ref()
+            .ok_or(DecodeError::MissingMeta)?;
+
+        let (signature, mut account_keys, compiled) = match &tx.transaction.transaction {
+            EncodedTransaction::Json(ui_tx) => {
+                let UiMessage::Raw(message) = &ui_tx.message else {
+                    return Err(DecodeError::UnsupportedEncoding);
+                };
+                let keys = message
+                    .account_keys
+                    .iter()
+                    .map(|key| parse_pubkey(key))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let compiled = message
+                    .instructions
+                    .iter()
+                    .map(|instruction| Ok((instruction.program_id_index, decode_data(&instruction.data)?)))
+                    .collect::<Result<Vec<_>, DecodeError>>()?;
+                (ui_tx.signatures.first().cloned(), keys, compiled)
+            }
+            encoded => {
+                let versioned = encoded.decode().ok_or(DecodeError::UnsupportedEncoding)?;
+                let compiled = versioned
+                    .message
+                    .instructions()
+                    .iter()
+                    .map(|instruction| (instruction.program_id_index, instruction.data.clone()))
+                    .collect();
+                (
+                    versioned.signatures.first().map(|signature| signature.to_string()),
+                    versioned.message.static_account_keys().to_vec(),
+                    compiled,
+                )
+            }
+        };
+
+        // Keys loaded from lookup tables follow the static keys
+        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+            for key in loaded.writable.iter().chain(&loaded.readonly) {
+                account_keys.push(parse_pubkey(key)?);
+            }
+        }
+
+        let instructions = compiled
+            .into_iter()
+            .map(|(program_id_index, data)| {
+                Ok(Instruction {
+                    program_id: account_key(&account_keys, program_id_index)?,
+                    data,
+                })
+            })
+            .collect::<Result<Vec<_>, DecodeError>>()?;
+
+        let mut inner_instructions = Vec::new();
+        if let OptionSerializer::Some(inner) = &meta.inner_instructions {
+            for instruction in inner.iter().flat_map(|inner| &inner.instructions) {
+                let UiInstruction::Compiled(compiled) = instruction else {
+                    return Err(DecodeError::UnsupportedEncoding);
+                };
+                inner_instructions.push(InnerInstruction {
+                    program_id: account_key(&account_keys, compiled.program_id_index)?,
+                    data: decode_data(&compiled.data)?,
+                    stack_height: compiled.stack_height,
+                });
+            }
+        }
+
+        let log_messages = match &meta.log_messages {
+            OptionSerializer::Some(logs) => logs.clone(),
+            _ => Vec::new(),
+        };
+
+        let token_balances = match &meta.post_token_balances {
+            OptionSerializer::Some(balances) => balances
+                .iter()
+                .map(|balance| token_balance(&account_keys, balance))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            signature: signature.ok_or(DecodeError::MissingSignature)?,
+            slot: tx.slot,
+            block_time: tx.block_time,
+            failed: meta.err.is_some(),
+            log_messages,
+            instructions,
+            inner_instructions,
+            token_balances,
+        })
+    }
+}
+
+fn parse_pubkey(key: &str) -> Result<Pubkey, DecodeError> {
+    Pubkey::from_str(key).map_err(|_| DecodeError::InvalidPubkey(key.to_string()))
+}
+
+fn account_key(account_keys: &[Pubkey], index: u8) -> Result<Pubkey, DecodeError> {
+    account_keys
+        .get(index as usize)
+        .copied()
+        .ok_or(DecodeError::InvalidAccountIndex(index))
+}
+
+fn decode_data(data: &str) -> Result<Vec<u8>, DecodeError> {
+    bs58::decode(data)
+        .into_vec()
+        .map_err(|_| DecodeError::InvalidInstructionData)
+}
+
+fn token_balance(account_keys: &[Pubkey], balance: &UiTransactionTokenBalance) -> Result<TokenBalance, DecodeError> {
+    let owner = match &balance.owner {
+        OptionSerializer::Some(owner) => Some(parse_pubkey(owner)?),
+        _ => None,
+    };
+
+    Ok(TokenBalance {
+        account: account_key(account_keys, balance.account_index)?,
+        mint: parse_pubkey(&balance.mint)?,
+        owner,
+        amount: balance
+            .ui_token_amount
+            .amount
+            .parse()
+            .map_err(|_| DecodeError::InvalidTokenAmount(balance.ui_token_amount.amount.clone()))?,
+    })
+}
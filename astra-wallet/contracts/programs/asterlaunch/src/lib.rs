@@ -1,11 +1,18 @@
 use anchor_lang::prelude::*;
+use asterlaunch_curve::{ConstantProductCurve, CurveError, PlatformFee};
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
 
 declare_id!("ASTRLaunchProgramId11111111111111111111111");
 
+/// Decimals of every launched token's mint
+pub const TOKEN_DECIMALS: u8 = 6;
+
+/// Supply minted to every launched token's curve: 1B tokens
+pub const TOKEN_SUPPLY: u64 = 1_000_000_000 * 10u64.pow(TOKEN_DECIMALS as u32);
+
 /// AsterLaunch - Meme Coin Launchpad Program
-/// 
+///
 /// This program implements a bonding curve-based token launchpad where:
 /// 1. Users can create new SPL tokens with automatic bonding curve trading
 /// 2. Tokens trade on a mathematical curve ensuring 100% liquidity
@@ -16,20 +23,31 @@ pub mod asterlaunch {
     use super::*;
 
     /// Initialize the AsterLaunch platform
-    /// Sets up global configuration and treasury accounts
+    /// Sets up global configuration and the program-owned treasury vault
     pub fn initialize_platform(
         ctx: Context<InitializePlatform>,
-        treasury_authority: Pubkey,
         graduation_threshold: u64, // Market cap threshold in lamports (69K USD worth of SOL)
     ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.bump = ctx.bumps.treasury;
+
         let platform_config = &mut ctx.accounts.platform_config;
         platform_config.authority = ctx.accounts.authority.key();
-        platform_config.treasury_authority = treasury_authority;
+        platform_config.treasury = treasury.key();
         platform_config.graduation_threshold = graduation_threshold;
         platform_config.total_tokens_created = 0;
         platform_config.total_volume = 0;
         platform_config.platform_fee_bps = 100; // 1% = 100 basis points
         platform_config.bump = ctx.bumps.platform_config;
+        platform_config.pause_flags = 0;
+        platform_config.total_fees_collected = 0;
+        platform_config.fee_recipient_count = 0;
+        platform_config.fee_recipients = [FeeRecipient::default(); MAX_FEE_RECIPIENTS];
+        platform_config.creator_fee_bps = 20; // 0.2% of each trade, out of the 1% fee
+        platform_config.referral_fee_bps = 2000; // 20% of the platform fee
+        platform_config.total_trades = 0;
+        platform_config.total_trade_fees = 0;
+        platform_config.reserve_limits = ReserveLimits::DEFAULT;
         
         msg!("AsterLaunch platform initialized with graduation threshold: {} lamports", graduation_threshold);
         Ok(())
@@ -42,6 +60,8 @@ pub mod asterlaunch {
     /// 2. Sets up the bonding curve state
     /// 3. Mints initial supply to the curve
     /// 4. Collects creation fee
+    ///
+    /// The virtual reserves must fall within the platform's reserve limits
     pub fn create_token(
         ctx: Context<CreateToken>,
         name: String,
@@ -53,6 +73,14 @@ pub mod asterlaunch {
         require!(name.len() <= 32, ErrorCode::NameTooLong);
         require!(symbol.len() <= 10, ErrorCode::SymbolTooLong);
         require!(uri.len() <= 200, ErrorCode::UriTooLong);
+        require!(
+            !ctx.accounts.platform_config.is_paused(PlatformConfig::PAUSE_CREATIONS),
+            ErrorCode::PlatformPaused
+        );
+        ctx.accounts.platform_config.reserve_limits.check(
+            initial_virtual_sol_reserves,
+            initial_virtual_token_reserves,
+        )?;
 
         let platform_config = &mut ctx.accounts.platform_config;
         let bonding_curve = &mut ctx.accounts.bonding_curve;
@@ -68,10 +96,16 @@ pub mod asterlaunch {
         bonding_curve.virtual_token_reserves = initial_virtual_token_reserves;
         bonding_curve.real_sol_reserves = 0;
         bonding_curve.real_token_reserves = 0;
-        bonding_curve.total_supply = 1_000_000_000 * 10u64.pow(6); // 1B tokens with 6 decimals
+        bonding_curve.total_supply = TOKEN_SUPPLY;
         bonding_curve.created_at = clock.unix_timestamp;
         bonding_curve.graduated = false;
         bonding_curve.bump = ctx.bumps.bonding_curve;
+        bonding_curve.paused = false;
+        bonding_curve.creator_fee_bps = platform_config.creator_fee_bps;
+        bonding_curve.creator_fees_accrued = 0;
+        bonding_curve.migrated = false;
+        bonding_curve.stats = TradeStats::default();
+        ctx.accounts.creator_vault.bump = ctx.bumps.creator_vault;
 
         // Mint total supply to bonding curve
         let cpi_accounts = token::MintTo {
@@ -95,6 +129,19 @@ pub mod asterlaunch {
         )?;
 
         platform_config.total_tokens_created += 1;
+        platform_config.total_fees_collected += creation_fee;
+
+        emit_cpi!(TokenCreated {
+            mint: bonding_curve.mint,
+            creator: bonding_curve.creator,
+            bonding_curve: bonding_curve.key(),
+            name: bonding_curve.name.clone(),
+            symbol: bonding_curve.symbol.clone(),
+            uri: bonding_curve.uri.clone(),
+            total_supply: bonding_curve.total_supply,
+            decimals: TOKEN_DECIMALS,
+            timestamp: clock.unix_timestamp,
+        });
 
         msg!("Token created: {} ({})", bonding_curve.name, bonding_curve.symbol);
         msg!("Bonding curve initialized with {} virtual SOL and {} virtual tokens", 
@@ -106,32 +153,50 @@ pub mod asterlaunch {
     /// Buy tokens from the bonding curve
     /// 
     /// Uses the constant product formula: k = virtual_sol * virtual_token
-    /// Price increases as more tokens are purchased
+    /// Price increases as more tokens are purchased. Returns the fill, so
+    /// a calling program can read it from return data.
     pub fn buy_tokens(
         ctx: Context<TradeTokens>,
         sol_amount: u64,
         min_tokens_out: u64,
-    ) -> Result<()> {
+    ) -> Result<TradeResult> {
+        require!(
+            !ctx.accounts.platform_config.is_paused(PlatformConfig::PAUSE_BUYS),
+            ErrorCode::PlatformPaused
+        );
+
         let bonding_curve = &mut ctx.accounts.bonding_curve;
+        require!(!bonding_curve.paused, ErrorCode::TokenPaused);
         require!(!bonding_curve.graduated, ErrorCode::TokenGraduated);
         require!(sol_amount > 0, ErrorCode::InvalidAmount);
+        let curve_info = bonding_curve.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(curve_info.data_len());
+        bonding_curve.sync_reserves(curve_info.lamports(), rent_minimum)?;
 
-        // Calculate platform fee (1%)
-        let platform_fee = sol_amount * bonding_curve.platform_fee_bps() / 10000;
-        let sol_after_fee = sol_amount - platform_fee;
-
-        // Calculate tokens out using bonding curve formula
+        // Platform fee (1%), part of which goes to the creator; the rest of
+        // the SOL buys tokens on the curve
         // k = (virtual_sol + real_sol) * (virtual_token - real_token)
-        let current_sol_reserves = bonding_curve.virtual_sol_reserves + bonding_curve.real_sol_reserves;
-        let current_token_reserves = bonding_curve.virtual_token_reserves - bonding_curve.real_token_reserves;
-        
-        let k = current_sol_reserves * current_token_reserves;
-        let new_sol_reserves = current_sol_reserves + sol_after_fee;
-        let new_token_reserves = k / new_sol_reserves;
-        let tokens_out = current_token_reserves - new_token_reserves;
+        let quote = bonding_curve
+            .curve()
+            .quote_buy(&bonding_curve.fees(), sol_amount, ctx.accounts.platform_config.graduation_threshold)
+            .map_err(curve_error)?;
+        let platform_fee = quote.fees.total_fee();
+        let creator_fee = quote.fees.creator_fee;
+        let referral_fee = referral_fee(
+            &ctx.accounts.platform_config,
+            &ctx.accounts.user,
+            &ctx.accounts.user_referral,
+            ctx.bumps.user_referral,
+            &mut ctx.accounts.referral,
+            &ctx.accounts.system_program,
+            quote.fees.platform_fee,
+            sol_amount,
+        )?;
+        let treasury_fee = quote.fees.platform_fee - referral_fee;
+        let sol_after_fee = quote.cost;
+        let tokens_out = quote.token_amount;
 
         require!(tokens_out >= min_tokens_out, ErrorCode::SlippageTooHigh);
-        require!(tokens_out <= current_token_reserves, ErrorCode::InsufficientLiquidity);
 
         // Update bonding curve state
         bonding_curve.real_sol_reserves += sol_after_fee;
@@ -140,22 +205,51 @@ pub mod asterlaunch {
         // Transfer SOL from buyer to bonding curve
         let ix = anchor_lang::system_program::Transfer {
             from: ctx.accounts.user.to_account_info(),
-            to: ctx.accounts.bonding_curve.to_account_info(),
+            to: bonding_curve.to_account_info(),
         };
         anchor_lang::system_program::transfer(
             CpiContext::new(ctx.accounts.system_program.to_account_info(), ix),
             sol_after_fee,
         )?;
 
-        // Transfer platform fee to treasury
+        // Transfer platform fee to the treasury vault
         let ix = anchor_lang::system_program::Transfer {
             from: ctx.accounts.user.to_account_info(),
             to: ctx.accounts.treasury.to_account_info(),
         };
         anchor_lang::system_program::transfer(
             CpiContext::new(ctx.accounts.system_program.to_account_info(), ix),
-            platform_fee,
+            treasury_fee,
         )?;
+        ctx.accounts.platform_config.total_fees_collected += treasury_fee;
+        ctx.accounts.platform_config.accrue_platform_fee(treasury_fee)?;
+
+        // Transfer creator fee to the creator vault
+        if creator_fee > 0 {
+            let ix = anchor_lang::system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.creator_vault.to_account_info(),
+            };
+            anchor_lang::system_program::transfer(
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), ix),
+                creator_fee,
+            )?;
+            bonding_curve.creator_fees_accrued += creator_fee;
+        }
+
+        // Transfer referral fee to the referrer's referral account
+        if let Some(referral) = &ctx.accounts.referral {
+            if referral_fee > 0 {
+                let ix = anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: referral.to_account_info(),
+                };
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(ctx.accounts.system_program.to_account_info(), ix),
+                    referral_fee,
+                )?;
+            }
+        }
 
         // Transfer tokens from bonding curve to buyer
         let seeds = &[
@@ -168,56 +262,106 @@ pub mod asterlaunch {
         let cpi_accounts = Transfer {
             from: ctx.accounts.bonding_curve_token_account.to_account_info(),
             to: ctx.accounts.user_token_account.to_account_info(),
-            authority: ctx.accounts.bonding_curve.to_account_info(),
+            authority: bonding_curve.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
         token::transfer(cpi_ctx, tokens_out)?;
 
+        bonding_curve.check_solvency(curve_info.lamports(), rent_minimum)?;
+
         // Check if graduation threshold is reached
-        let market_cap = calculate_market_cap(bonding_curve)?;
+        let market_cap = bonding_curve.curve().market_cap().map_err(curve_error)?;
         if market_cap >= ctx.accounts.platform_config.graduation_threshold {
             bonding_curve.graduated = true;
             msg!("Token graduated! Market cap: {} lamports", market_cap);
             // Note: Actual liquidity migration to Raydium would happen in a separate instruction
         }
 
+        let clock = Clock::get()?;
+        let price = bonding_curve.spot_price()?;
+        bonding_curve.stats.record(TradeSide::Buy, sol_amount, platform_fee, clock.slot, price)?;
+        ctx.accounts.platform_config.record_trade(sol_amount, platform_fee)?;
+
+        emit_cpi!(Trade {
+            mint: bonding_curve.mint,
+            trader: ctx.accounts.user.key(),
+            side: TradeSide::Buy,
+            token_amount: tokens_out,
+            sol_amount,
+            liquidity_fee: 0,
+            platform_fee: treasury_fee,
+            creator_fee,
+            referral_fee,
+            sol_reserves: bonding_curve.real_sol_reserves,
+            token_reserves: bonding_curve.total_supply - bonding_curve.real_token_reserves,
+            price,
+            timestamp: clock.unix_timestamp,
+            stats: bonding_curve.stats,
+        });
+
         msg!("Bought {} tokens for {} SOL (fee: {} SOL)", tokens_out, sol_after_fee, platform_fee);
-        Ok(())
+        Ok(TradeResult {
+            tokens: tokens_out,
+            sol: sol_amount,
+            fees: platform_fee,
+            new_price: price,
+        })
     }
 
     /// Sell tokens to the bonding curve
     /// 
     /// Uses the same constant product formula but in reverse
-    /// Price decreases as tokens are sold
+    /// Price decreases as tokens are sold. Returns the fill, so a calling
+    /// program can read it from return data.
     pub fn sell_tokens(
         ctx: Context<TradeTokens>,
         token_amount: u64,
         min_sol_out: u64,
-    ) -> Result<()> {
+    ) -> Result<TradeResult> {
+        require!(
+            !ctx.accounts.platform_config.is_paused(PlatformConfig::PAUSE_SELLS),
+            ErrorCode::PlatformPaused
+        );
+
         let bonding_curve = &mut ctx.accounts.bonding_curve;
+        require!(!bonding_curve.paused, ErrorCode::TokenPaused);
         require!(!bonding_curve.graduated, ErrorCode::TokenGraduated);
         require!(token_amount > 0, ErrorCode::InvalidAmount);
+        require!(token_amount <= bonding_curve.real_token_reserves, ErrorCode::InsufficientLiquidity);
+        let curve_info = bonding_curve.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(curve_info.data_len());
+        bonding_curve.sync_reserves(curve_info.lamports(), rent_minimum)?;
 
-        // Calculate SOL out using bonding curve formula
-        let current_sol_reserves = bonding_curve.virtual_sol_reserves + bonding_curve.real_sol_reserves;
-        let current_token_reserves = bonding_curve.virtual_token_reserves - bonding_curve.real_token_reserves;
-        
-        let k = current_sol_reserves * current_token_reserves;
-        let new_token_reserves = current_token_reserves + token_amount;
-        let new_sol_reserves = k / new_token_reserves;
-        let sol_out_before_fee = current_sol_reserves - new_sol_reserves;
-
-        // Calculate platform fee (1%)
-        let platform_fee = sol_out_before_fee * bonding_curve.platform_fee_bps() / 10000;
-        let sol_out = sol_out_before_fee - platform_fee;
+        // Value the tokens on the curve and take the platform fee (1%), part
+        // of which goes to the creator, out of it. The seller and every fee
+        // are paid out of the curve's SOL reserves, which must cover them.
+        let quote = bonding_curve
+            .curve()
+            .quote_sell(&bonding_curve.fees(), token_amount, ctx.accounts.platform_config.graduation_threshold)
+            .map_err(curve_error)?;
+        let sol_out_before_fee = quote.proceeds;
+        let platform_fee = quote.fees.total_fee();
+        let creator_fee = quote.fees.creator_fee;
+        let referral_fee = referral_fee(
+            &ctx.accounts.platform_config,
+            &ctx.accounts.user,
+            &ctx.accounts.user_referral,
+            ctx.bumps.user_referral,
+            &mut ctx.accounts.referral,
+            &ctx.accounts.system_program,
+            quote.fees.platform_fee,
+            sol_out_before_fee,
+        )?;
+        let treasury_fee = quote.fees.platform_fee - referral_fee;
+        let sol_out = quote.sol_out;
 
         require!(sol_out >= min_sol_out, ErrorCode::SlippageTooHigh);
-        require!(sol_out <= bonding_curve.real_sol_reserves, ErrorCode::InsufficientLiquidity);
 
         // Update bonding curve state
         bonding_curve.real_sol_reserves -= sol_out_before_fee;
         bonding_curve.real_token_reserves -= token_amount;
+        bonding_curve.creator_fees_accrued += creator_fee;
 
         // Transfer tokens from seller to bonding curve
         let cpi_accounts = Transfer {
@@ -233,12 +377,54 @@ pub mod asterlaunch {
         **ctx.accounts.bonding_curve.to_account_info().try_borrow_mut_lamports()? -= sol_out;
         **ctx.accounts.user.to_account_info().try_borrow_mut_lamports()? += sol_out;
 
-        // Transfer platform fee to treasury
-        **ctx.accounts.bonding_curve.to_account_info().try_borrow_mut_lamports()? -= platform_fee;
-        **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+        // Transfer platform fee to the treasury vault
+        **ctx.accounts.bonding_curve.to_account_info().try_borrow_mut_lamports()? -= treasury_fee;
+        **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += treasury_fee;
+        ctx.accounts.platform_config.total_fees_collected += treasury_fee;
+        ctx.accounts.platform_config.accrue_platform_fee(treasury_fee)?;
+
+        // Transfer creator fee to the creator vault
+        **ctx.accounts.bonding_curve.to_account_info().try_borrow_mut_lamports()? -= creator_fee;
+        **ctx.accounts.creator_vault.to_account_info().try_borrow_mut_lamports()? += creator_fee;
+
+        // Transfer referral fee to the referrer's referral account
+        if let Some(referral) = &ctx.accounts.referral {
+            **ctx.accounts.bonding_curve.to_account_info().try_borrow_mut_lamports()? -= referral_fee;
+            **referral.to_account_info().try_borrow_mut_lamports()? += referral_fee;
+        }
+
+        ctx.accounts.bonding_curve.check_solvency(curve_info.lamports(), rent_minimum)?;
+
+        let clock = Clock::get()?;
+        let price = ctx.accounts.bonding_curve.spot_price()?;
+        ctx.accounts.bonding_curve.stats.record(TradeSide::Sell, sol_out, platform_fee, clock.slot, price)?;
+        ctx.accounts.platform_config.record_trade(sol_out, platform_fee)?;
+
+        let bonding_curve = &ctx.accounts.bonding_curve;
+        emit_cpi!(Trade {
+            mint: bonding_curve.mint,
+            trader: ctx.accounts.user.key(),
+            side: TradeSide::Sell,
+            token_amount,
+            sol_amount: sol_out,
+            liquidity_fee: 0,
+            platform_fee: treasury_fee,
+            creator_fee,
+            referral_fee,
+            sol_reserves: bonding_curve.real_sol_reserves,
+            token_reserves: bonding_curve.total_supply - bonding_curve.real_token_reserves,
+            price,
+            timestamp: clock.unix_timestamp,
+            stats: bonding_curve.stats,
+        });
 
         msg!("Sold {} tokens for {} SOL (fee: {} SOL)", token_amount, sol_out, platform_fee);
-        Ok(())
+        Ok(TradeResult {
+            tokens: token_amount,
+            sol: sol_out,
+            fees: platform_fee,
+            new_price: price,
+        })
     }
 
     /// Graduate token to external DEX (Raydium)
@@ -248,8 +434,15 @@ pub mod asterlaunch {
     /// 2. Transfers all SOL and remaining tokens to the pool
     /// 3. Burns the LP tokens to permanently lock liquidity
     pub fn graduate_to_dex(ctx: Context<GraduateToDex>) -> Result<()> {
+        require!(
+            !ctx.accounts.platform_config.is_paused(PlatformConfig::PAUSE_GRADUATIONS),
+            ErrorCode::PlatformPaused
+        );
+
         let bonding_curve = &mut ctx.accounts.bonding_curve;
+        require!(!bonding_curve.paused, ErrorCode::TokenPaused);
         require!(bonding_curve.graduated, ErrorCode::NotReadyForGraduation);
+        require!(!bonding_curve.migrated, ErrorCode::TokenGraduated);
 
         // Calculate final liquidity amounts
         let sol_liquidity = bonding_curve.real_sol_reserves;
@@ -261,36 +454,364 @@ pub mod asterlaunch {
         // 1. Create a Raydium pool
         // 2. Add liquidity to the pool
         // 3. Burn the LP tokens
-        // 4. Update the bonding curve state
 
         bonding_curve.real_sol_reserves = 0;
         bonding_curve.real_token_reserves = 0;
+        bonding_curve.migrated = true;
+
+        emit_cpi!(Graduated {
+            mint: bonding_curve.mint,
+            bonding_curve: bonding_curve.key(),
+            sol_liquidity,
+            token_liquidity,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
         msg!("Token successfully graduated to Raydium!");
         Ok(())
     }
+
+    /// Withdraw collected fees from the treasury vault
+    /// 
+    /// Only the platform authority can withdraw; the vault always keeps
+    /// its rent-exempt minimum
+    pub fn withdraw_treasury(
+        ctx: Context<WithdrawTreasury>,
+        amount: u64,
+        destination: Pubkey,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        // Fees accrued to recipients are theirs to claim
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(treasury.data_len());
+        let available = treasury
+            .lamports()
+            .saturating_sub(rent_minimum)
+            .saturating_sub(ctx.accounts.platform_config.total_fees_accrued()?);
+        require!(amount <= available, ErrorCode::InsufficientTreasuryBalance);
+
+        **treasury.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        msg!("Withdrew {} lamports from treasury to {}", amount, destination);
+        Ok(())
+    }
+
+    /// Replace the platform fee recipients
+    /// 
+    /// Weights must sum to 10000 bps. Recipients that stay keep their
+    /// unclaimed balance; a recipient with unclaimed fees cannot be removed.
+    pub fn set_fee_recipients(ctx: Context<UpdatePlatform>, recipients: Vec<FeeSplit>) -> Result<()> {
+        require!(recipients.len() <= MAX_FEE_RECIPIENTS, ErrorCode::InvalidFeeSplit);
+        let total_weight: u32 = recipients.iter().map(|r| r.weight_bps as u32).sum();
+        require!(recipients.is_empty() || total_weight == 10000, ErrorCode::InvalidFeeSplit);
+        for (i, split) in recipients.iter().enumerate() {
+            require!(split.weight_bps > 0, ErrorCode::InvalidFeeSplit);
+            require!(
+                !recipients[..i].iter().any(|r| r.recipient == split.recipient),
+                ErrorCode::InvalidFeeSplit
+            );
+        }
+
+        let platform_config = &mut ctx.accounts.platform_config;
+        for old in platform_config.fee_recipients() {
+            require!(
+                old.accrued == 0 || recipients.iter().any(|r| r.recipient == old.recipient),
+                ErrorCode::UnclaimedFees
+            );
+        }
+
+        let mut fee_recipients = [FeeRecipient::default(); MAX_FEE_RECIPIENTS];
+        for (slot, split) in fee_recipients.iter_mut().zip(&recipients) {
+            let accrued = platform_config
+                .fee_recipients()
+                .iter()
+                .find(|r| r.recipient == split.recipient)
+                .map_or(0, |r| r.accrued);
+            *slot = FeeRecipient {
+                recipient: split.recipient,
+                weight_bps: split.weight_bps,
+                accrued,
+            };
+        }
+        platform_config.fee_recipients = fee_recipients;
+        platform_config.fee_recipient_count = recipients.len() as u8;
+
+        msg!("Platform fee split across {} recipients", recipients.len());
+        Ok(())
+    }
+
+    /// Claim the signer's accrued share of platform fees from the treasury vault
+    pub fn claim_platform_fees(ctx: Context<ClaimPlatformFees>) -> Result<()> {
+        let recipient_key = ctx.accounts.recipient.key();
+        let platform_config = &mut ctx.accounts.platform_config;
+        let count = platform_config.fee_recipient_count as usize;
+        let entry = platform_config.fee_recipients[..count]
+            .iter_mut()
+            .find(|r| r.recipient == recipient_key)
+            .ok_or(ErrorCode::Unauthorized)?;
+        let amount = entry.accrued;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        entry.accrued = 0;
+
+        **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        msg!("Claimed {} lamports of platform fees for {}", amount, recipient_key);
+        Ok(())
+    }
+
+    /// Set the creator share of the trading fee for newly created tokens
+    pub fn set_creator_fee(ctx: Context<UpdatePlatform>, creator_fee_bps: u16) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+        require!(
+            creator_fee_bps as u64 <= platform_config.platform_fee_bps,
+            ErrorCode::InvalidCreatorFee
+        );
+        platform_config.creator_fee_bps = creator_fee_bps;
+
+        msg!("Creator fee: {}bps", creator_fee_bps);
+        Ok(())
+    }
+
+    /// Set the virtual reserves new tokens may be created with
+    ///
+    /// Equal bounds fix a reserve to a single preset
+    pub fn set_reserve_limits(ctx: Context<UpdatePlatform>, reserve_limits: ReserveLimits) -> Result<()> {
+        reserve_limits.validate()?;
+        ctx.accounts.platform_config.reserve_limits = reserve_limits;
+
+        msg!(
+            "Reserve limits: {}-{} virtual SOL, {}-{} virtual tokens",
+            reserve_limits.min_virtual_sol_reserves,
+            reserve_limits.max_virtual_sol_reserves,
+            reserve_limits.min_virtual_token_reserves,
+            reserve_limits.max_virtual_token_reserves
+        );
+        Ok(())
+    }
+
+    /// Claim a token's accrued creator fees from its creator vault
+    pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+        let bonding_curve = &mut ctx.accounts.bonding_curve;
+        let amount = bonding_curve.creator_fees_accrued;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        bonding_curve.creator_fees_accrued = 0;
+
+        **ctx.accounts.creator_vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.creator.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        msg!("Claimed {} lamports of creator fees for {}", amount, bonding_curve.mint);
+        Ok(())
+    }
+
+    /// Create the signer's referral account
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+        referral.referrer = ctx.accounts.referrer.key();
+        referral.accrued = 0;
+        referral.total_earned = 0;
+        referral.referred_volume = 0;
+        referral.referred_trades = 0;
+        referral.referred_users = 0;
+        referral.bump = ctx.bumps.referral;
+
+        msg!("Registered referrer {}", referral.referrer);
+        Ok(())
+    }
+
+    /// Claim a referrer's accrued referral fees
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+        let amount = referral.accrued;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        referral.accrued = 0;
+
+        **referral.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.referrer.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        msg!("Claimed {} lamports of referral fees for {}", amount, referral.referrer);
+        Ok(())
+    }
+
+    /// Set the referrer share of the platform fee (basis points of the platform fee)
+    pub fn set_referral_fee(ctx: Context<UpdatePlatform>, referral_fee_bps: u16) -> Result<()> {
+        require!(referral_fee_bps <= 10000, ErrorCode::InvalidReferralFee);
+        ctx.accounts.platform_config.referral_fee_bps = referral_fee_bps;
+
+        msg!("Referral fee: {}bps of the platform fee", referral_fee_bps);
+        Ok(())
+    }
+
+    /// Pause platform operations (circuit breaker)
+    /// 
+    /// `flags` is a bitmask of `PlatformConfig::PAUSE_*`, so buys or
+    /// creations can be paused on their own
+    pub fn pause_platform(ctx: Context<UpdatePlatform>, flags: u8) -> Result<()> {
+        require!(flags & !PlatformConfig::PAUSE_ALL == 0, ErrorCode::InvalidPauseFlags);
+
+        let platform_config = &mut ctx.accounts.platform_config;
+        platform_config.pause_flags |= flags;
+
+        msg!("Platform pause flags: {:#06b}", platform_config.pause_flags);
+        Ok(())
+    }
+
+    /// Resume paused platform operations
+    pub fn unpause_platform(ctx: Context<UpdatePlatform>, flags: u8) -> Result<()> {
+        require!(flags & !PlatformConfig::PAUSE_ALL == 0, ErrorCode::InvalidPauseFlags);
+
+        let platform_config = &mut ctx.accounts.platform_config;
+        platform_config.pause_flags &= !flags;
+
+        msg!("Platform pause flags: {:#06b}", platform_config.pause_flags);
+        Ok(())
+    }
+
+    /// Pause or resume trading and graduation of a single token
+    pub fn pause_token(ctx: Context<PauseToken>, paused: bool) -> Result<()> {
+        let bonding_curve = &mut ctx.accounts.bonding_curve;
+        bonding_curve.paused = paused;
+
+        msg!("Token {} paused: {}", bonding_curve.mint, paused);
+        Ok(())
+    }
 }
 
-/// Calculate current market cap based on bonding curve state
-fn calculate_market_cap(bonding_curve: &BondingCurve) -> Result<u64> {
-    let current_sol_reserves = bonding_curve.virtual_sol_reserves + bonding_curve.real_sol_reserves;
-    let current_token_reserves = bonding_curve.virtual_token_reserves - bonding_curve.real_token_reserves;
-    
-    if current_token_reserves == 0 {
-        return Ok(0);
+/// Map a curve math error to the program's errors
+fn curve_error(error: CurveError) -> Error {
+    match error {
+        CurveError::MathOverflow => ErrorCode::MathOverflow,
+        CurveError::InvalidTradeAmount => ErrorCode::InvalidAmount,
+        CurveError::InsufficientLiquidity => ErrorCode::InsufficientLiquidity,
+        CurveError::InvalidFeeStructure => ErrorCode::InvalidCreatorFee,
+        CurveError::InvalidCurveParameters => ErrorCode::InvalidCurveParameters,
     }
+    .into()
+}
 
-    // Market cap = (total_supply * current_price)
-    // Current price = sol_reserves / token_reserves
-    let price_per_token = current_sol_reserves / current_token_reserves;
-    let market_cap = bonding_curve.total_supply * price_per_token;
-    
-    Ok(market_cap)
+/// Credit the user's referrer, if any, for a trade and return its fee
+///
+/// The referrer is recorded on the user's first referred trade; after that
+/// every trade of the user must credit the recorded referrer, so leaving the
+/// referral account out fails with `ReferrerMismatch`
+#[allow(clippy::too_many_arguments)]
+fn referral_fee<'info>(
+    platform_config: &PlatformConfig,
+    user: &Signer<'info>,
+    user_referral: &UncheckedAccount<'info>,
+    user_referral_bump: u8,
+    referral: &mut Option<Account<'info, Referral>>,
+    system_program: &Program<'info, System>,
+    platform_fee: u64,
+    volume: u64,
+) -> Result<u64> {
+    if user_referral.data_is_empty() {
+        let Some(referral) = referral.as_mut() else {
+            return Ok(0);
+        };
+        require_keys_neq!(referral.referrer, user.key(), ErrorCode::InvalidReferrer);
+
+        let space = 8 + UserReferral::INIT_SPACE;
+        let seeds: &[&[u8]] = &[b"user_referral", user.key.as_ref(), &[user_referral_bump]];
+        anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                anchor_lang::system_program::CreateAccount {
+                    from: user.to_account_info(),
+                    to: user_referral.to_account_info(),
+                },
+                &[seeds],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+        )?;
+        let record = UserReferral {
+            user: user.key(),
+            referrer: referral.referrer,
+            created_at: Clock::get()?.unix_timestamp,
+        };
+        record.try_serialize(&mut &mut user_referral.try_borrow_mut_data()?[..])?;
+        referral.referred_users += 1;
+        msg!("Referral recorded: {} -> {}", user.key(), referral.referrer);
+    } else {
+        require_keys_eq!(
+            *user_referral.owner,
+            crate::ID,
+            anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram
+        );
+        let record = UserReferral::try_deserialize(&mut &user_referral.try_borrow_data()?[..])?;
+        let referral = referral.as_ref().ok_or(ErrorCode::ReferrerMismatch)?;
+        require_keys_eq!(record.referrer, referral.referrer, ErrorCode::ReferrerMismatch);
+    }
+    let referral = referral.as_mut().ok_or(ErrorCode::ReferrerMismatch)?;
+
+    let fee = asterlaunch_curve::fees::referral_fee(platform_fee, platform_config.referral_fee_bps)
+        .map_err(curve_error)?;
+    referral.accrued += fee;
+    referral.total_earned += fee;
+    referral.referred_volume += volume;
+    referral.referred_trades += 1;
+    Ok(fee)
 }
 
 impl BondingCurve {
-    pub fn platform_fee_bps(&self) -> u64 {
-        100 // 1% = 100 basis points
+    /// Pricing state of the curve
+    pub fn curve(&self) -> ConstantProductCurve {
+        ConstantProductCurve {
+            virtual_sol_reserves: self.virtual_sol_reserves,
+            virtual_token_reserves: self.virtual_token_reserves,
+            real_sol_reserves: self.real_sol_reserves,
+            real_token_reserves: self.real_token_reserves,
+            total_supply: self.total_supply,
+            decimals: TOKEN_DECIMALS,
+        }
+    }
+
+    /// Fees on this token's trades (1%, part of which goes to the creator)
+    pub fn fees(&self) -> PlatformFee {
+        PlatformFee::with_creator_fee(self.creator_fee_bps)
+    }
+
+    /// Spot price in lamports per whole token
+    pub fn spot_price(&self) -> Result<u64> {
+        self.curve().spot_price().map_err(curve_error)
+    }
+
+    /// SOL returned for `token_amount` (before fees)
+    /// 
+    /// Rounds in the curve's favour so k never decreases
+    pub fn sol_out(&self, token_amount: u64) -> Result<u64> {
+        self.curve().sol_out(token_amount).map_err(curve_error)
+    }
+
+    /// Credit lamports sent straight to the curve account to its SOL reserves,
+    /// so the reserves match the account balance before a trade
+    pub fn sync_reserves(&mut self, lamports: u64, rent_minimum: u64) -> Result<()> {
+        let accounted = rent_minimum
+            .checked_add(self.real_sol_reserves)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if let Some(surplus) = lamports.checked_sub(accounted).filter(|s| *s > 0) {
+            self.real_sol_reserves += surplus;
+            msg!("Swept {} unaccounted lamports into the reserves", surplus);
+        }
+        Ok(())
+    }
+
+    /// Check the curve account holds exactly its rent minimum plus its SOL
+    /// reserves, and that those reserves cover selling back every circulating token
+    pub fn check_solvency(&self, lamports: u64, rent_minimum: u64) -> Result<()> {
+        let accounted = rent_minimum
+            .checked_add(self.real_sol_reserves)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(lamports == accounted, ErrorCode::ReserveInsolvent);
+        require!(
+            self.sol_out(self.real_token_reserves)? <= self.real_sol_reserves,
+            ErrorCode::ReserveInsolvent
+        );
+        Ok(())
     }
 }
 
@@ -309,12 +830,19 @@ pub struct InitializePlatform<'info> {
     )]
     pub platform_config: Account<'info, PlatformConfig>,
     
-    /// CHECK: Treasury account to receive fees
-    pub treasury: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
     
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateToken<'info> {
     #[account(mut)]
@@ -330,7 +858,7 @@ pub struct CreateToken<'info> {
     #[account(
         init,
         payer = creator,
-        mint::decimals = 6,
+        mint::decimals = TOKEN_DECIMALS,
         mint::authority = mint,
     )]
     pub mint: Account<'info, Mint>,
@@ -352,9 +880,21 @@ pub struct CreateToken<'info> {
     )]
     pub bonding_curve_token_account: Account<'info, TokenAccount>,
     
-    /// CHECK: Treasury account to receive creation fee
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + CreatorVault::INIT_SPACE,
+        seeds = [b"creator_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
     
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -362,12 +902,14 @@ pub struct CreateToken<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct TradeTokens<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
     
     #[account(
+        mut,
         seeds = [b"platform_config"],
         bump = platform_config.bump
     )]
@@ -395,15 +937,43 @@ pub struct TradeTokens<'info> {
     )]
     pub user_token_account: Account<'info, TokenAccount>,
     
-    /// CHECK: Treasury account to receive fees
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_vault", bonding_curve.mint.as_ref()],
+        bump = creator_vault.bump
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+    
+    /// Sticky referral record of the trader, created on the first referred trade
+    /// CHECK: address checked by seeds; created and read by `referral_fee`
+    #[account(
+        mut,
+        seeds = [b"user_referral", user.key().as_ref()],
+        bump
+    )]
+    pub user_referral: UncheckedAccount<'info>,
+    
+    /// Referrer's account; required once the trader has a referral record
+    #[account(
+        mut,
+        seeds = [b"referral", referral.referrer.as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, Referral>>,
     
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct GraduateToDex<'info> {
     #[account(mut)]
@@ -416,20 +986,319 @@ pub struct GraduateToDex<'info> {
     )]
     pub bonding_curve: Account<'info, BondingCurve>,
     
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
     // Additional accounts for Raydium integration would go here
 }
 
+#[derive(Accounts)]
+#[instruction(amount: u64, destination: Pubkey)]
+pub struct WithdrawTreasury<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// CHECK: Any account may receive the withdrawal; it must match `destination`
+    #[account(mut, address = destination)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPlatformFees<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCreatorFees<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"bonding_curve", bonding_curve.mint.as_ref()],
+        bump = bonding_curve.bump,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_vault", bonding_curve.mint.as_ref()],
+        bump = creator_vault.bump
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + Referral::INIT_SPACE,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump = referral.bump,
+        has_one = referrer @ ErrorCode::Unauthorized
+    )]
+    pub referral: Account<'info, Referral>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatform<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct PauseToken<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"bonding_curve", bonding_curve.mint.as_ref()],
+        bump = bonding_curve.bump
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+}
+
+/// Maximum number of platform fee recipients
+pub const MAX_FEE_RECIPIENTS: usize = 8;
+
+/// A platform fee recipient and its share, as passed to `set_fee_recipients`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct FeeSplit {
+    pub recipient: Pubkey,
+    pub weight_bps: u16,
+}
+
+/// A share of the platform fee and its unclaimed balance
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FeeRecipient {
+    pub recipient: Pubkey,
+    pub weight_bps: u16,
+    pub accrued: u64,
+}
+
+/// Virtual reserves new tokens may be created with, inclusive
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct ReserveLimits {
+    /// Lamports
+    pub min_virtual_sol_reserves: u64,
+    pub max_virtual_sol_reserves: u64,
+    /// Base units
+    pub min_virtual_token_reserves: u64,
+    pub max_virtual_token_reserves: u64,
+}
+
+impl ReserveLimits {
+    /// 10 to 100 SOL against half to all of the supply
+    pub const DEFAULT: Self = Self {
+        min_virtual_sol_reserves: 10_000_000_000,
+        max_virtual_sol_reserves: 100_000_000_000,
+        min_virtual_token_reserves: TOKEN_SUPPLY / 2,
+        max_virtual_token_reserves: TOKEN_SUPPLY,
+    };
+
+    /// Check the limits only admit curves that can be priced and filled
+    ///
+    /// Both reserves must be non-zero, or the first trade divides by zero,
+    /// and the token reserves cannot exceed the minted supply, or the curve
+    /// could sell tokens it does not hold
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_virtual_sol_reserves > 0
+                && self.min_virtual_sol_reserves <= self.max_virtual_sol_reserves
+                && self.min_virtual_token_reserves > 0
+                && self.min_virtual_token_reserves <= self.max_virtual_token_reserves
+                && self.max_virtual_token_reserves <= TOKEN_SUPPLY,
+            ErrorCode::InvalidReserveLimits
+        );
+        Ok(())
+    }
+
+    /// Check a creator's virtual reserves fall within the limits
+    ///
+    /// Valid limits exclude zero reserves and token reserves above the supply
+    pub fn check(&self, virtual_sol_reserves: u64, virtual_token_reserves: u64) -> Result<()> {
+        require!(
+            (self.min_virtual_sol_reserves..=self.max_virtual_sol_reserves).contains(&virtual_sol_reserves)
+                && (self.min_virtual_token_reserves..=self.max_virtual_token_reserves)
+                    .contains(&virtual_token_reserves),
+            ErrorCode::VirtualReservesOutOfRange
+        );
+        Ok(())
+    }
+}
+
 /// State Accounts
 #[account]
 #[derive(InitSpace)]
 pub struct PlatformConfig {
     pub authority: Pubkey,
-    pub treasury_authority: Pubkey,
+    pub treasury: Pubkey,
     pub graduation_threshold: u64,
     pub total_tokens_created: u64,
     pub total_volume: u64,
     pub platform_fee_bps: u64,
     pub bump: u8,
+    pub pause_flags: u8,
+    pub total_fees_collected: u64,
+    pub fee_recipient_count: u8,
+    pub fee_recipients: [FeeRecipient; MAX_FEE_RECIPIENTS],
+    pub creator_fee_bps: u16,
+    pub referral_fee_bps: u16,
+    /// Trades and trade fees across all tokens; `total_volume` counts
+    /// their SOL the same way as `TradeStats`
+    pub total_trades: u64,
+    pub total_trade_fees: u64,
+    pub reserve_limits: ReserveLimits,
+}
+
+impl PlatformConfig {
+    pub const PAUSE_BUYS: u8 = 1 << 0;
+    pub const PAUSE_SELLS: u8 = 1 << 1;
+    pub const PAUSE_CREATIONS: u8 = 1 << 2;
+    pub const PAUSE_GRADUATIONS: u8 = 1 << 3;
+    pub const PAUSE_ALL: u8 = Self::PAUSE_BUYS
+        | Self::PAUSE_SELLS
+        | Self::PAUSE_CREATIONS
+        | Self::PAUSE_GRADUATIONS;
+
+    pub fn is_paused(&self, flags: u8) -> bool {
+        self.pause_flags & flags != 0
+    }
+
+    pub fn fee_recipients(&self) -> &[FeeRecipient] {
+        &self.fee_recipients[..self.fee_recipient_count as usize]
+    }
+
+    /// Fees owed to recipients that are still held in the treasury vault
+    pub fn total_fees_accrued(&self) -> Result<u64> {
+        self.fee_recipients()
+            .iter()
+            .try_fold(0u64, |total, r| total.checked_add(r.accrued))
+            .ok_or_else(|| error!(ErrorCode::MathOverflow))
+    }
+
+    /// Roll a trade into the platform totals
+    pub fn record_trade(&mut self, sol_amount: u64, fees: u64) -> Result<()> {
+        self.total_trades = self.total_trades.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        self.total_volume = self.total_volume.checked_add(sol_amount).ok_or(ErrorCode::MathOverflow)?;
+        self.total_trade_fees = self.total_trade_fees.checked_add(fees).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Credit a platform fee to the recipients by weight; rounding dust, or
+    /// the whole fee when no recipients are set, stays unallocated
+    pub fn accrue_platform_fee(&mut self, platform_fee: u64) -> Result<()> {
+        let count = self.fee_recipient_count as usize;
+        for recipient in self.fee_recipients[..count].iter_mut() {
+            let share = (platform_fee as u128 * recipient.weight_bps as u128 / 10000) as u64;
+            recipient.accrued = recipient
+                .accrued
+                .checked_add(share)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        Ok(())
+    }
+}
+
+/// Program-owned vault receiving all platform fees
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub bump: u8,
+}
+
+/// Referral account of a referrer; holds its unclaimed fees as lamports
+#[account]
+#[derive(InitSpace)]
+pub struct Referral {
+    pub referrer: Pubkey,
+    pub accrued: u64,
+    pub total_earned: u64,
+    pub referred_volume: u64,
+    pub referred_trades: u64,
+    pub referred_users: u64,
+    pub bump: u8,
+}
+
+/// Sticky referral attribution, recorded on a user's first referred trade
+#[account]
+#[derive(InitSpace)]
+pub struct UserReferral {
+    pub user: Pubkey,
+    pub referrer: Pubkey,
+    pub created_at: i64,
+}
+
+/// Per-token vault holding the creator's unclaimed fee share
+#[account]
+#[derive(InitSpace)]
+pub struct CreatorVault {
+    pub bump: u8,
 }
 
 #[account]
@@ -451,6 +1320,112 @@ pub struct BondingCurve {
     pub created_at: i64,
     pub graduated: bool,
     pub bump: u8,
+    pub paused: bool,
+    pub creator_fee_bps: u16,
+    pub creator_fees_accrued: u64,
+    pub migrated: bool,
+    pub stats: TradeStats,
+}
+
+/// Fill of a trade, returned by `buy_tokens` and `sell_tokens`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TradeResult {
+    /// Tokens bought or sold
+    pub tokens: u64,
+    /// SOL paid by the buyer including fees, or received by the seller after fees
+    pub sol: u64,
+    /// Platform fee charged on the trade, including the creator's and referrer's cuts
+    pub fees: u64,
+    /// Spot price after the trade
+    pub new_price: u64,
+}
+
+/// Trading statistics of a bonding curve, same layout as the native
+/// program's `TradeStats`
+///
+/// `trade_seq` goes up by one per trade, so indexers can spot missed trades
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq, InitSpace)]
+pub struct TradeStats {
+    pub trade_seq: u64,
+    pub buy_count: u64,
+    pub sell_count: u64,
+    /// SOL paid by buyers including fees, and received by sellers after fees
+    pub buy_volume: u64,
+    pub sell_volume: u64,
+    pub fees_paid: u64,
+    pub last_trade_slot: u64,
+    /// Spot price after the latest trade, in lamports per whole token
+    pub last_price: u64,
+}
+
+impl TradeStats {
+    pub fn record(&mut self, side: TradeSide, sol_amount: u64, fees: u64, slot: u64, price: u64) -> Result<()> {
+        let (count, volume) = match side {
+            TradeSide::Buy => (&mut self.buy_count, &mut self.buy_volume),
+            TradeSide::Sell => (&mut self.sell_count, &mut self.sell_volume),
+        };
+        *count = count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        *volume = volume.checked_add(sol_amount).ok_or(ErrorCode::MathOverflow)?;
+        self.fees_paid = self.fees_paid.checked_add(fees).ok_or(ErrorCode::MathOverflow)?;
+        self.trade_seq = self.trade_seq.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        self.last_trade_slot = slot;
+        self.last_price = price;
+        Ok(())
+    }
+}
+
+/// Events
+///
+/// Same names and layouts as the native program's events, so one decoder
+/// (`asterlaunch_program::events::AsterLaunchEvent`) reads both programs
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+#[event]
+pub struct TokenCreated {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub total_supply: u64,
+    pub decimals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Trade {
+    pub mint: Pubkey,
+    pub trader: Pubkey,
+    pub side: TradeSide,
+    pub token_amount: u64,
+    /// SOL paid by the buyer including fees, or received by the seller after fees
+    pub sol_amount: u64,
+    pub liquidity_fee: u64,
+    pub platform_fee: u64,
+    pub creator_fee: u64,
+    pub referral_fee: u64,
+    /// Real reserves after the trade
+    pub sol_reserves: u64,
+    pub token_reserves: u64,
+    /// Spot price after the trade, in lamports per whole token
+    pub price: u64,
+    pub timestamp: i64,
+    /// The curve's trading statistics after the trade
+    pub stats: TradeStats,
+}
+
+#[event]
+pub struct Graduated {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub sol_liquidity: u64,
+    pub token_liquidity: u64,
+    pub timestamp: i64,
 }
 
 /// Error Codes
@@ -472,4 +1447,122 @@ pub enum ErrorCode {
     TokenGraduated,
     #[msg("Token is not ready for graduation")]
     NotReadyForGraduation,
-}
\ No newline at end of file
+    #[msg("Signer is not the platform authority")]
+    Unauthorized,
+    #[msg("Platform operation is paused")]
+    PlatformPaused,
+    #[msg("Token trading is paused")]
+    TokenPaused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("Treasury balance too low for withdrawal")]
+    InsufficientTreasuryBalance,
+    #[msg("Fee recipient weights are invalid")]
+    InvalidFeeSplit,
+    #[msg("Fee recipient still has unclaimed fees")]
+    UnclaimedFees,
+    #[msg("No fees to claim")]
+    NothingToClaim,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Creator fee exceeds the platform fee")]
+    InvalidCreatorFee,
+    #[msg("Referral fee exceeds the platform fee")]
+    InvalidReferralFee,
+    #[msg("Users cannot refer themselves")]
+    InvalidReferrer,
+    #[msg("Referrer does not match the recorded referral")]
+    ReferrerMismatch,
+    #[msg("Bonding curve reserves cannot cover circulating tokens")]
+    ReserveInsolvent,
+    #[msg("Curve parameters cannot price the full supply")]
+    InvalidCurveParameters,
+    #[msg("Reserve limits are empty or exceed the token supply")]
+    InvalidReserveLimits,
+    #[msg("Virtual reserves are outside the platform's reserve limits")]
+    VirtualReservesOutOfRange,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_reserve_limits_validate() {
+        assert!(ReserveLimits::DEFAULT.validate().is_ok());
+
+        let invalid = [
+            ReserveLimits { min_virtual_sol_reserves: 0, ..ReserveLimits::DEFAULT },
+            ReserveLimits { min_virtual_token_reserves: 0, ..ReserveLimits::DEFAULT },
+            ReserveLimits { max_virtual_token_reserves: TOKEN_SUPPLY + 1, ..ReserveLimits::DEFAULT },
+            ReserveLimits {
+                min_virtual_sol_reserves: 2 * LAMPORTS_PER_SOL,
+                max_virtual_sol_reserves: LAMPORTS_PER_SOL,
+                ..ReserveLimits::DEFAULT
+            },
+            ReserveLimits {
+                min_virtual_token_reserves: TOKEN_SUPPLY,
+                max_virtual_token_reserves: TOKEN_SUPPLY - 1,
+                ..ReserveLimits::DEFAULT
+            },
+        ];
+        for limits in invalid {
+            assert_eq!(limits.validate(), Err(ErrorCode::InvalidReserveLimits.into()), "{:?}", limits);
+        }
+
+        // Single-value ranges are valid
+        let exact = ReserveLimits {
+            min_virtual_sol_reserves: LAMPORTS_PER_SOL,
+            max_virtual_sol_reserves: LAMPORTS_PER_SOL,
+            min_virtual_token_reserves: TOKEN_SUPPLY,
+            max_virtual_token_reserves: TOKEN_SUPPLY,
+        };
+        assert!(exact.validate().is_ok());
+    }
+
+    #[test]
+    fn test_reserve_limits_check() {
+        let limits = ReserveLimits::DEFAULT;
+
+        // Bounds are inclusive
+        assert!(limits.check(10 * LAMPORTS_PER_SOL, TOKEN_SUPPLY / 2).is_ok());
+        assert!(limits.check(100 * LAMPORTS_PER_SOL, TOKEN_SUPPLY).is_ok());
+
+        let out_of_range = [
+            (0, TOKEN_SUPPLY),
+            (30 * LAMPORTS_PER_SOL, 0),
+            (10 * LAMPORTS_PER_SOL - 1, TOKEN_SUPPLY),
+            (100 * LAMPORTS_PER_SOL + 1, TOKEN_SUPPLY),
+            (30 * LAMPORTS_PER_SOL, TOKEN_SUPPLY / 2 - 1),
+            (30 * LAMPORTS_PER_SOL, TOKEN_SUPPLY + 1),
+        ];
+        for (sol, tokens) in out_of_range {
+            assert_eq!(
+                limits.check(sol, tokens),
+                Err(ErrorCode::VirtualReservesOutOfRange.into()),
+                "{} lamports, {} tokens",
+                sol,
+                tokens
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_reserve_limits_can_graduate() {
+        // The least SOL against the most tokens passes a 400 SOL market cap
+        // with a tenth of the virtual tokens left
+        let limits = ReserveLimits::DEFAULT;
+        let curve = ConstantProductCurve {
+            virtual_sol_reserves: limits.min_virtual_sol_reserves,
+            virtual_token_reserves: limits.max_virtual_token_reserves,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+            total_supply: TOKEN_SUPPLY,
+            decimals: TOKEN_DECIMALS,
+        };
+        let sold = asterlaunch_curve::solver::constant_product_after(&curve, TOKEN_SUPPLY / 10 * 9).unwrap();
+        assert!(sold.market_cap().unwrap() >= 400 * LAMPORTS_PER_SOL);
+    }
+}
@@ -1,4 +1,5 @@
 import React, { createContext, useContext, useState, useEffect, ReactNode } from 'react';
+import { CurveState, TOKEN_DECIMALS } from '../lib/quote';
 
 export interface Token {
   id: string;
@@ -17,13 +18,40 @@ export interface Token {
   volume24h: number;
   liquidity: number;
   priceHistory: { timestamp: number; price: number }[];
+  /** The token's bonding curve, which trade estimates are quoted from */
+  curve: CurveState;
 }
 
+/** Market cap (lamports) at which the platform graduates a curve */
+export const GRADUATION_THRESHOLD = BigInt(400) * BigInt(1000000000);
+
+const UNITS_PER_TOKEN = BigInt(10 ** TOKEN_DECIMALS);
+
+/** A fresh curve with `tokensSold` whole tokens bought out of it */
+const mockCurve = (tokensSold: number): CurveState => {
+  const virtualSolReserves = BigInt(30) * BigInt(1000000000);
+  const virtualTokenReserves = BigInt(1073000000) * UNITS_PER_TOKEN;
+  const realTokenReserves = BigInt(Math.floor(tokensSold)) * UNITS_PER_TOKEN;
+  // The least SOL that buys `tokensSold` off the constant product
+  const k = virtualSolReserves * virtualTokenReserves;
+  const remaining = virtualTokenReserves - realTokenReserves;
+  const realSolReserves = (k + remaining - BigInt(1)) / remaining - virtualSolReserves;
+
+  return {
+    virtualSolReserves,
+    virtualTokenReserves,
+    realSolReserves,
+    realTokenReserves,
+    totalSupply: BigInt(1000000000) * UNITS_PER_TOKEN,
+    creatorFeeBps: 0,
+  };
+};
+
 export interface DataContextType {
   tokens: Token[];
   loading: boolean;
   refreshTokens: () => void;
-  addToken: (token: Omit<Token, 'id' | 'createdAt' | 'priceHistory'>) => void;
+  addToken: (token: Omit<Token, 'id' | 'createdAt' | 'priceHistory' | 'curve'>) => void;
   getToken: (ticker: string) => Token | undefined;
 }
 
@@ -47,6 +75,7 @@ const generateMockTokens = (): Token[] => {
 
   return mockNames.map((name, index) => {
     const basePrice = Math.random() * 0.001 + 0.0001;
+    const graduationProgress = Math.random() * 100;
     const priceHistory = Array.from({ length: 100 }, (_, i) => ({
       timestamp: Date.now() - (99 - i) * 60000,
       price: basePrice * (1 + (Math.random() - 0.5) * 0.1)
@@ -58,7 +87,7 @@ const generateMockTokens = (): Token[] => {
       ticker: mockTickers[index],
       marketCap: Math.random() * 50000 + 5000,
       change24h: (Math.random() - 0.5) * 200,
-      graduationProgress: Math.random() * 100,
+      graduationProgress,
       description: `${name} is the next big meme coin on Solana!`,
       creator: '7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU',
       createdAt: new Date(Date.now() - Math.random() * 7 * 24 * 60 * 60 * 1000),
@@ -67,7 +96,9 @@ const generateMockTokens = (): Token[] => {
       price: priceHistory[priceHistory.length - 1].price,
       volume24h: Math.random() * 100000 + 1000,
       liquidity: Math.random() * 200000 + 10000,
-      priceHistory
+      priceHistory,
+      // Up to 800M tokens sold, in step with the graduation progress
+      curve: mockCurve(graduationProgress * 8000000)
     };
   });
 };
@@ -85,12 +116,13 @@ export const DataProvider: React.FC<DataProviderProps> = ({ children }) => {
     }, 500);
   };
 
-  const addToken = (newToken: Omit<Token, 'id' | 'createdAt' | 'priceHistory'>) => {
+  const addToken = (newToken: Omit<Token, 'id' | 'createdAt' | 'priceHistory' | 'curve'>) => {
     const token: Token = {
       ...newToken,
       id: `token-${Date.now()}`,
       createdAt: new Date(),
-      priceHistory: [{ timestamp: Date.now(), price: newToken.price }]
+      priceHistory: [{ timestamp: Date.now(), price: newToken.price }],
+      curve: mockCurve(0)
     };
     setTokens(prev => [token, ...prev]);
   };
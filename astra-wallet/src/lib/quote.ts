@@ -0,0 +1,116 @@
+import init, { AnchorCurve } from './curve-wasm/asterlaunch_curve_wasm';
+
+// Quotes from the Anchor program's own integer math (asterlaunch-curve
+// compiled to WebAssembly), so estimates match what a trade charges to the
+// lamport. Build the bindings first with `npm run build:wasm`.
+
+/** A token's `BondingCurve` account fields that price trades */
+export interface CurveState {
+  virtualSolReserves: bigint;
+  virtualTokenReserves: bigint;
+  realSolReserves: bigint;
+  realTokenReserves: bigint;
+  totalSupply: bigint;
+  creatorFeeBps: number;
+}
+
+/** Decimals of every token the program mints */
+export const TOKEN_DECIMALS = 6;
+
+const LAMPORTS_PER_SOL = BigInt(1000000000);
+const UNITS_PER_TOKEN = BigInt(10 ** TOKEN_DECIMALS);
+
+export interface TradeEstimate {
+  /** Tokens bought or sold (base units) */
+  tokens: bigint;
+  /** SOL paid including fees, or received after fees (lamports) */
+  sol: bigint;
+  /** 1% platform fee, creator share included (lamports) */
+  fee: bigint;
+  priceImpactBps: bigint;
+  graduationProgressBps: number;
+}
+
+let ready: Promise<unknown> | null = null;
+
+const load = () => {
+  ready ??= init();
+  return ready;
+};
+
+const withCurve = async <T>(state: CurveState, quote: (curve: AnchorCurve) => T): Promise<T> => {
+  await load();
+  const curve = new AnchorCurve(
+    state.virtualSolReserves,
+    state.virtualTokenReserves,
+    state.realSolReserves,
+    state.realTokenReserves,
+    state.totalSupply,
+    TOKEN_DECIMALS,
+    state.creatorFeeBps,
+  );
+  try {
+    return quote(curve);
+  } finally {
+    curve.free();
+  }
+};
+
+/** Parse a decimal amount into base units without going through floats */
+const parseUnits = (amount: string, decimals: number): bigint | null => {
+  const match = new RegExp(`^(\\d*)(?:\\.(\\d{0,${decimals}})\\d*)?$`).exec(amount.trim());
+  if (!match || (!match[1] && !match[2])) return null;
+  const whole = BigInt(match[1] || '0');
+  const fraction = BigInt((match[2] || '').padEnd(decimals, '0') || '0');
+  return whole * BigInt(10 ** decimals) + fraction;
+};
+
+const formatUnits = (units: bigint, scale: bigint, digits: number): string => {
+  const whole = units / scale;
+  const fraction = (units % scale).toString().padStart(scale.toString().length - 1, '0').slice(0, digits);
+  return digits > 0 ? `${whole.toLocaleString()}.${fraction}` : whole.toLocaleString();
+};
+
+/** Parse a decimal SOL amount into lamports */
+export const parseSol = (sol: string): bigint | null => parseUnits(sol, 9);
+
+/** Parse a decimal token amount into base units */
+export const parseTokens = (tokens: string): bigint | null => parseUnits(tokens, TOKEN_DECIMALS);
+
+export const formatSol = (lamports: bigint, digits = 4): string => formatUnits(lamports, LAMPORTS_PER_SOL, digits);
+
+export const formatTokens = (units: bigint, digits = 2): string => formatUnits(units, UNITS_PER_TOKEN, digits);
+
+/** Buying with `lamports`, fees included */
+export const estimateBuy = (state: CurveState, lamports: bigint, graduationThreshold: bigint): Promise<TradeEstimate> =>
+  withCurve(state, (curve) => {
+    const quote = curve.quoteBuy(lamports, graduationThreshold);
+    try {
+      return {
+        tokens: quote.tokenAmount,
+        sol: quote.totalCost,
+        fee: quote.fees.totalFee,
+        priceImpactBps: quote.priceImpactBps,
+        graduationProgressBps: quote.graduationProgressBps,
+      };
+    } finally {
+      quote.free();
+    }
+  });
+
+/** Selling `tokens` base units */
+export const estimateSell = (state: CurveState, tokens: bigint, graduationThreshold: bigint): Promise<TradeEstimate> =>
+  withCurve(state, (curve) => {
+    const quote = curve.quoteSell(tokens, graduationThreshold);
+    try {
+      return {
+        tokens: quote.tokenAmount,
+        sol: quote.solOut,
+        fee: quote.fees.totalFee,
+        priceImpactBps: quote.priceImpactBps,
+        graduationProgressBps: quote.graduationProgressBps,
+      };
+    } finally {
+      quote.free();
+    }
+  });
@@ -1,7 +1,8 @@
 import React, { useState, useEffect } from 'react';
 import { useParams, Link } from 'react-router-dom';
-import { useData } from '../contexts/DataContext';
+import { GRADUATION_THRESHOLD, useData } from '../contexts/DataContext';
 import { useWallet } from '../contexts/WalletContext';
+import { estimateBuy, estimateSell, formatSol, formatTokens, parseSol, parseTokens } from '../lib/quote';
 import { 
   ArrowLeft, 
   TrendingUp, 
@@ -25,12 +26,49 @@ const TradingView: React.FC = () => {
   const [sellAmount, setSellAmount] = useState('');
   const [activeTab, setActiveTab] = useState<'buy' | 'sell'>('buy');
   const [copied, setCopied] = useState(false);
+  const [estimate, setEstimate] = useState<string | null>(null);
 
   useEffect(() => {
     const foundToken = getToken(ticker || '');
     setToken(foundToken);
   }, [ticker, getToken]);
 
+  // Quote with the program's integer math
+  useEffect(() => {
+    setEstimate(null);
+    if (!token) return;
+
+    let quote: Promise<string> | null = null;
+    if (activeTab === 'buy') {
+      const lamports = parseSol(buyAmount);
+      if (lamports) {
+        quote = estimateBuy(token.curve, lamports, GRADUATION_THRESHOLD).then(
+          (estimate) => `${formatTokens(estimate.tokens)} ${token.ticker}`
+        );
+      }
+    } else {
+      const tokens = parseTokens(sellAmount);
+      if (tokens) {
+        quote = estimateSell(token.curve, tokens, GRADUATION_THRESHOLD).then(
+          (estimate) => `${formatSol(estimate.sol)} SOL`
+        );
+      }
+    }
+    if (!quote) return;
+
+    let cancelled = false;
+    quote
+      .then((text) => {
+        if (!cancelled) setEstimate(text);
+      })
+      .catch(() => {
+        if (!cancelled) setEstimate('Amount exceeds the curve');
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [token, activeTab, buyAmount, sellAmount]);
+
   if (!token) {
     return (
       <div className="text-center py-2xl">
@@ -84,22 +122,6 @@ const TradingView: React.FC = () => {
     }
   };
 
-  const calculateBuyOutput = (solAmount: number) => {
-    // Simplified bonding curve calculation
-    const currentSupply = token.totalSupply * (token.graduationProgress / 100);
-    const k = 0.000001; // Curve steepness
-    const tokensOut = solAmount / (k * Math.sqrt(currentSupply + solAmount * 1000000));
-    return tokensOut;
-  };
-
-  const calculateSellOutput = (tokenAmount: number) => {
-    // Simplified bonding curve calculation for selling
-    const currentSupply = token.totalSupply * (token.graduationProgress / 100);
-    const k = 0.000001;
-    const solOut = tokenAmount * k * Math.sqrt(currentSupply);
-    return solOut;
-  };
-
   const handleTrade = async (type: 'buy' | 'sell') => {
     if (!connected) {
       alert('Please connect your wallet first');
@@ -231,6 +253,54 @@ const TradingView: React.FC = () => {
         )}
       </div>
 
+      {/* Trade */}
+      <div className="card mb-xl">
+        <div className="flex gap-sm mb-lg">
+          <button
+            onClick={() => setActiveTab('buy')}
+            className={`btn ${activeTab === 'buy' ? 'btn-success' : 'btn-secondary'}`}
+          >
+            Buy
+          </button>
+          <button
+            onClick={() => setActiveTab('sell')}
+            className={`btn ${activeTab === 'sell' ? 'btn-danger' : 'btn-secondary'}`}
+          >
+            Sell
+          </button>
+        </div>
+
+        <label className="block text-sm font-medium mb-sm">
+          {activeTab === 'buy' ? 'Amount (SOL)' : `Amount (${token.ticker})`}
+        </label>
+        <input
+          type="text"
+          inputMode="decimal"
+          value={activeTab === 'buy' ? buyAmount : sellAmount}
+          onChange={(e) => (activeTab === 'buy' ? setBuyAmount : setSellAmount)(e.target.value)}
+          placeholder="0.0"
+          className="input"
+        />
+        {connected && activeTab === 'buy' && (
+          <p className="text-muted text-sm mt-xs">Balance: {balance.toFixed(4)} SOL</p>
+        )}
+
+        {(activeTab === 'buy' ? buyAmount : sellAmount) && (
+          <div className="flex justify-between mt-md text-sm">
+            <span className="text-muted">You will receive (estimated)</span>
+            <span>{estimate ?? '…'}</span>
+          </div>
+        )}
+
+        <button
+          onClick={() => handleTrade(activeTab)}
+          className={`btn btn-lg ${activeTab === 'buy' ? 'btn-success' : 'btn-danger'} mt-lg`}
+          style={{ width: '100%' }}
+        >
+          {activeTab === 'buy' ? 'Buy' : 'Sell'} {token.ticker}
+        </button>
+      </div>
+
       {/* Price Chart */}
       <div className="card mb-xl">
         <h3 className="mb-lg">Price Chart</h3>